@@ -0,0 +1,339 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::inspection::{search_class_limits, ToleranceClass};
+use crate::measurements::{parse_samples, Samples};
+use crate::parse_input;
+use crate::statistics::{mean, normal_cdf, std_dev, within_sigma};
+
+/// Число интервалов гистограммы в пределах поля допуска.
+const HISTOGRAM_BINS: usize = 10;
+/// Наибольшее число интервалов гистограммы за каждым из пределов.
+const HISTOGRAM_OUTSIDE_BINS: usize = 5;
+const HISTOGRAM_WIDTH: usize = 40;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CapabilityQuery {
+    pub class: ToleranceClass,
+    pub samples: Samples,
+    /// Объём подгруппы для внутригруппового σ; 1 - по скользящему размаху.
+    pub subgroup: usize,
+}
+
+/// Показатели воспроизводимости процесса относительно пределов поля допуска.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Capability {
+    pub count: usize,
+    pub mean: f64,
+    pub sigma_within: f64,
+    pub sigma_overall: f64,
+    pub cp: f64,
+    pub cpk: f64,
+    pub pp: f64,
+    pub ppk: f64,
+    /// Прогноз доли за верхним и нижним пределом по общему σ, ppm.
+    pub ppm_above: f64,
+    pub ppm_below: f64,
+    /// Фактическое число значений за верхним и нижним пределом.
+    pub observed_above: usize,
+    pub observed_below: usize,
+}
+
+pub fn parse_capability_input(input: &str) -> Option<CapabilityQuery> {
+    let mut tokens = input.strip_prefix("cpk")?.split_whitespace();
+    let class = parse_input(tokens.next()?)?;
+    let (samples, params) = parse_samples(tokens, &["subgroup"])?;
+    let subgroup = params.number_or("subgroup", 1.0)?;
+    if subgroup.fract() != 0.0 || !(1.0..=10.0).contains(&subgroup) {
+        return None;
+    }
+    Some(CapabilityQuery {
+        class,
+        samples,
+        subgroup: subgroup as usize,
+    })
+}
+
+/// Cp и Pp - по ширине поля, Cpk и Ppk - по ближайшему пределу;
+/// Cp/Cpk - по внутригрупповому σ, Pp/Ppk - по общему.
+pub fn calc_capability(values: &[f64], limits: (f64, f64), subgroup: usize) -> Option<Capability> {
+    let (upper, lower) = limits;
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = mean(values);
+    let sigma_within = within_sigma(values, subgroup)?;
+    let sigma_overall = std_dev(values);
+    let width = upper - lower;
+    let nearest = (upper - mean).min(mean - lower);
+    Some(Capability {
+        count: values.len(),
+        mean,
+        sigma_within,
+        sigma_overall,
+        cp: width / (6.0 * sigma_within),
+        cpk: nearest / (3.0 * sigma_within),
+        pp: width / (6.0 * sigma_overall),
+        ppk: nearest / (3.0 * sigma_overall),
+        ppm_above: (1.0 - normal_cdf((upper - mean) / sigma_overall)) * 1e6,
+        ppm_below: normal_cdf((lower - mean) / sigma_overall) * 1e6,
+        observed_above: values.iter().filter(|value| **value > upper).count(),
+        observed_below: values.iter().filter(|value| **value < lower).count(),
+    })
+}
+
+/// Гистограмма с границами интервалов на пределах поля: (от, до, число значений).
+/// Значения дальше крайних интервалов попадают в крайние интервалы.
+pub fn histogram(values: &[f64], limits: (f64, f64)) -> Vec<(f64, f64, usize)> {
+    let (upper, lower) = limits;
+    let step = (upper - lower) / HISTOGRAM_BINS as f64;
+    let min = values.iter().copied().fold(lower, f64::min);
+    let max = values.iter().copied().fold(upper, f64::max);
+    let bins_below = (((lower - min) / step).ceil() as usize).min(HISTOGRAM_OUTSIDE_BINS);
+    let bins_above = (((max - upper) / step).ceil() as usize).min(HISTOGRAM_OUTSIDE_BINS);
+    let start = lower - step * bins_below as f64;
+    let bins = bins_below + HISTOGRAM_BINS + bins_above;
+    let mut counts = vec![0; bins];
+    for value in values {
+        let mut bin = ((value - start) / step)
+            .floor()
+            .clamp(0.0, (bins - 1) as f64) as usize;
+        if *value >= lower {
+            bin = bin.max(bins_below);
+        }
+        if *value <= upper {
+            bin = bin.min(bins_below + HISTOGRAM_BINS - 1);
+        }
+        counts[bin] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(bin, count)| {
+            let from = start + step * bin as f64;
+            (from, from + step, count)
+        })
+        .collect()
+}
+
+pub fn handle_capability(capability_query: &CapabilityQuery) {
+    let values: Vec<f64> = match capability_query.samples.load() {
+        Ok(rows) => rows.into_iter().flatten().collect(),
+        Err(e) => {
+            println!("Ошибка: {}", e);
+            return;
+        }
+    };
+    match search_class_limits(&capability_query.class) {
+        Ok(Some((upper, lower))) => {
+            let limits = (
+                upper.to_f64().unwrap_or_default(),
+                lower.to_f64().unwrap_or_default(),
+            );
+            match calc_capability(&values, limits, capability_query.subgroup) {
+                Some(capability)
+                    if capability.sigma_within == 0.0 || capability.sigma_overall == 0.0 =>
+                {
+                    println!(
+                        "Разброс значений равен нулю (σ = 0): индексы Cp, Cpk, Pp, Ppk не определены; \
+                         проверьте цену деления прибора и данные"
+                    )
+                }
+                Some(capability) => {
+                    print_capability(capability_query, limits, &capability, &values)
+                }
+                None => println!(
+                    "Недостаточно значений: нужно не меньше двух и хотя бы одна полная подгруппа"
+                ),
+            }
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn index_color(index: f64) -> Color {
+    if index >= 1.33 {
+        Color::Green
+    } else if index >= 1.0 {
+        Color::Yellow
+    } else {
+        Color::Red
+    }
+}
+
+fn print_capability(
+    capability_query: &CapabilityQuery,
+    limits: (f64, f64),
+    capability: &Capability,
+    values: &[f64],
+) {
+    let (size, field, accuracy) = &capability_query.class;
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new(format!("{}{}{}", size, field, accuracy)),
+            Cell::new(format!("{} .. {}", limits.1, limits.0)),
+        ])
+        .add_row(vec![Cell::new("значений"), Cell::new(capability.count)])
+        .add_row(vec![
+            Cell::new("среднее"),
+            Cell::new(format!("{:.5}", capability.mean)),
+        ])
+        .add_row(vec![
+            Cell::new(format!(
+                "σ внутригрупповое ({})",
+                if capability_query.subgroup == 1 {
+                    "MR̄/1.128".to_string()
+                } else {
+                    format!("R̄/d2, n = {}", capability_query.subgroup)
+                }
+            )),
+            Cell::new(format!("{:.5}", capability.sigma_within)),
+        ])
+        .add_row(vec![
+            Cell::new("σ общее"),
+            Cell::new(format!("{:.5}", capability.sigma_overall)),
+        ]);
+    for (name, index) in [
+        ("Cp", capability.cp),
+        ("Cpk", capability.cpk),
+        ("Pp", capability.pp),
+        ("Ppk", capability.ppk),
+    ] {
+        table_result.add_row(vec![
+            Cell::new(name),
+            Cell::new(format!("{:.2}", index)).fg(index_color(index)),
+        ]);
+    }
+    table_result
+        .add_row(vec![
+            Cell::new("за верхним пределом, ppm (факт)"),
+            Cell::new(format!(
+                "{:.1} ({})",
+                capability.ppm_above, capability.observed_above
+            ))
+            .fg(Color::Red),
+        ])
+        .add_row(vec![
+            Cell::new("за нижним пределом, ppm (факт)"),
+            Cell::new(format!(
+                "{:.1} ({})",
+                capability.ppm_below, capability.observed_below
+            ))
+            .fg(Color::Cyan),
+        ]);
+    println!("{table_result}");
+
+    let histogram = histogram(values, limits);
+    let max_count = histogram
+        .iter()
+        .map(|(_, _, count)| *count)
+        .max()
+        .unwrap_or(0);
+    let mut table_histogram = Table::new();
+    table_histogram
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["размер от", "до", "шт.", ""]);
+    for (from, to, count) in histogram {
+        let middle = (from + to) * 0.5;
+        let color = if middle > limits.0 || middle < limits.1 {
+            Color::Red
+        } else {
+            Color::Green
+        };
+        let bar_length = if max_count > 0 {
+            (count as f64 / max_count as f64 * HISTOGRAM_WIDTH as f64).round() as usize
+        } else {
+            0
+        };
+        table_histogram.add_row(vec![
+            Cell::new(format!("{:.4}", from)),
+            Cell::new(format!("{:.4}", to)),
+            Cell::new(count),
+            Cell::new("█".repeat(bar_length)).fg(color),
+        ]);
+    }
+    println!("{table_histogram}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, epsilon: f64) {
+        assert!(
+            (actual - expected).abs() < epsilon,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_capability_input() {
+        let query = parse_capability_input("cpk 30h6 29,995 29.992 29.998").unwrap();
+        assert_eq!(
+            query.class,
+            ("30".to_string(), "h".to_string(), "6".to_string())
+        );
+        assert_eq!(query.samples, Samples::Values(vec![29.995, 29.992, 29.998]));
+        assert_eq!(query.subgroup, 1);
+
+        let query = parse_capability_input("cpk 30h6 file=data.csv subgroup=5").unwrap();
+        assert_eq!(query.samples, Samples::File("data.csv".to_string()));
+        assert_eq!(query.subgroup, 5);
+
+        assert_eq!(parse_capability_input("cpk 30h6"), None);
+        assert_eq!(parse_capability_input("cpk 30h6 29.995 subgroup=2.5"), None);
+        assert_eq!(parse_capability_input("cpk 30h6 29.995 subgroup=11"), None);
+    }
+
+    #[test]
+    fn test_calc_capability() {
+        let values = [10.0, 10.2, 9.8, 10.1, 9.9];
+        let capability = calc_capability(&values, (10.6, 9.4), 1).unwrap();
+        assert_eq!(capability.count, 5);
+        assert_close(capability.mean, 10.0, 1e-12);
+        assert_close(capability.cp, 0.820364, 1e-6);
+        assert_close(capability.cpk, 0.820364, 1e-6);
+        assert_close(capability.pp, 1.264911, 1e-6);
+        assert_close(capability.ppm_above, 73.9, 0.2);
+        assert_close(capability.ppm_below, 73.9, 0.2);
+        assert_eq!(capability.observed_above, 0);
+
+        let capability = calc_capability(&values, (10.15, 9.4), 1).unwrap();
+        assert_close(capability.cpk, 0.15 / (3.0 * 0.243794326), 1e-6);
+        assert_eq!(capability.observed_above, 1);
+        assert_eq!(capability.observed_below, 0);
+
+        // Одинаковые значения: σ = 0, индексы не выводятся.
+        let capability = calc_capability(&[10.0; 4], (10.6, 9.4), 2).unwrap();
+        assert_eq!(capability.sigma_within, 0.0);
+        assert_eq!(capability.sigma_overall, 0.0);
+
+        assert_eq!(calc_capability(&[10.0], (10.6, 9.4), 1), None);
+        assert_eq!(calc_capability(&values, (10.6, 9.4), 6), None);
+    }
+
+    #[test]
+    fn test_histogram() {
+        let histogram = histogram(&[10.05, 10.05, 10.6, 9.4, 10.75, 9.0], (10.6, 9.4));
+        assert_eq!(histogram.len(), 16);
+        assert_close(histogram[4].0, 9.4, 1e-9);
+        assert_close(histogram[14].0, 10.6, 1e-9);
+        assert_eq!(histogram[0].2, 1);
+        assert_eq!(histogram[4].2, 1);
+        assert_eq!(histogram[9].2, 2);
+        assert_eq!(histogram[13].2, 1);
+        assert_eq!(histogram[15].2, 1);
+        assert_eq!(
+            histogram.iter().map(|(_, _, count)| count).sum::<usize>(),
+            6
+        );
+    }
+}
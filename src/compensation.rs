@@ -0,0 +1,317 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::params::Params;
+use crate::replace_comma_with_dot;
+use crate::stackup::{
+    calc_stack, parse_contributor, parse_dimension, resolve_links, Contributor, Link,
+};
+use crate::statistics::Distribution;
+
+/// Запас на погрешность округления при делении диапазона на ступени.
+const STEP_EPSILON: f64 = 1e-9;
+const MAX_STEPS: usize = 50;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ShimQuery {
+    pub contributors: Vec<Contributor>,
+    /// Индекс компенсирующего звена; его допуск - допуск изготовления прокладки.
+    pub compensator: usize,
+    /// Замыкающее звено: номинал, верхнее и нижнее отклонения.
+    pub closing: (Decimal, Decimal, Decimal),
+}
+
+/// Ступень неподвижного компенсатора: прокладка толщиной `thickness` (середина поля)
+/// ставится, когда замыкающий размер без прокладки попадает в `gap`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ShimStep {
+    pub gap: (f64, f64),
+    pub thickness: f64,
+}
+
+/// Метод регулирования неподвижным компенсатором.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Compensation {
+    /// Предельные значения замыкающего размера без компенсатора.
+    pub produced: (f64, f64),
+    /// Наибольшая компенсация Vk = TΔ' - TΔ.
+    pub range: f64,
+    /// Ступень толщины прокладок S = TΔ - Tk.
+    pub step: f64,
+    pub steps: Vec<ShimStep>,
+}
+
+pub fn parse_shim_input(input: &str) -> Option<ShimQuery> {
+    let tokens = input.strip_prefix("shim ")?.split_whitespace();
+    let (params, links): (Vec<&str>, Vec<&str>) = tokens.partition(|token| token.contains('='));
+    let params = Params::parse(params.into_iter(), &["closing", "comp"])?;
+    let contributors = links
+        .into_iter()
+        .map(parse_contributor)
+        .collect::<Option<Vec<Contributor>>>()?;
+    if contributors.len() < 2 {
+        return None;
+    }
+    let compensator = params.number_or("comp", contributors.len() as f64)?;
+    if compensator.fract() != 0.0 || !(1.0..=contributors.len() as f64).contains(&compensator) {
+        return None;
+    }
+    Some(ShimQuery {
+        contributors,
+        compensator: compensator as usize - 1,
+        closing: parse_dimension(&replace_comma_with_dot(params.text("closing")?))?,
+    })
+}
+
+/// Ступени компенсатора: диапазон замыкающего размера без прокладки `produced`
+/// делится на участки шириной S; каждой ступени соответствует прокладка, при которой
+/// замыкающее звено с учётом допуска прокладки `shim_tolerance` укладывается в `required`.
+pub fn calc_compensation(
+    produced: (f64, f64),
+    required: (f64, f64),
+    increasing: bool,
+    shim_tolerance: f64,
+) -> Result<Compensation, String> {
+    let (produced_min, produced_max) = produced;
+    let (required_min, required_max) = required;
+    let step = required_max - required_min - shim_tolerance;
+    if step <= 0.0 {
+        return Err("допуск прокладки не меньше допуска замыкающего звена".to_string());
+    }
+    let count = (((produced_max - produced_min) / step) - STEP_EPSILON)
+        .ceil()
+        .max(1.0) as usize;
+    if count > MAX_STEPS {
+        return Err(format!(
+            "требуется {} ступеней прокладок, уточните допуски звеньев",
+            count
+        ));
+    }
+    let sign = if increasing { 1.0 } else { -1.0 };
+    let steps = (0..count)
+        .map(|index| {
+            let from = produced_min + index as f64 * step;
+            // Поправка, которую вносит прокладка в замыкающий размер.
+            let correction = required_min - from + shim_tolerance / 2.0;
+            ShimStep {
+                gap: (from, (from + step).min(produced_max)),
+                thickness: sign * correction,
+            }
+        })
+        .collect();
+    Ok(Compensation {
+        produced,
+        range: (produced_max - produced_min) - (required_max - required_min),
+        step,
+        steps,
+    })
+}
+
+pub fn handle_shim(shim_query: &ShimQuery) {
+    let links = match resolve_links(&shim_query.contributors, Distribution::Normal(3.0)) {
+        Ok(Some(links)) => links,
+        Ok(None) => {
+            println!("Ничего не найдено для заданных параметров");
+            return;
+        }
+        Err(e) => {
+            println!("Ошибка при поиске в БД: {}", e);
+            return;
+        }
+    };
+    let compensator = &links[shim_query.compensator];
+    let others: Vec<Link> = links
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != shim_query.compensator)
+        .map(|(_, link)| *link)
+        .collect();
+    let produced = calc_stack(&others).worst_case;
+    let (nominal, upper, lower) = shim_query.closing;
+    let required = (
+        (nominal + lower).to_f64().unwrap_or_default(),
+        (nominal + upper).to_f64().unwrap_or_default(),
+    );
+    match calc_compensation(
+        produced,
+        required,
+        compensator.sign > 0.0,
+        compensator.tolerance(),
+    ) {
+        Ok(compensation) => print_shim(shim_query, compensator, required, &compensation),
+        Err(e) => println!("Ошибка: {}", e),
+    }
+}
+
+fn print_shim(
+    shim_query: &ShimQuery,
+    compensator: &Link,
+    required: (f64, f64),
+    compensation: &Compensation,
+) {
+    let (produced_min, produced_max) = compensation.produced;
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new(format!(
+                "компенсатор A{} = {}",
+                shim_query.compensator + 1,
+                shim_query.contributors[shim_query.compensator]
+                    .source
+                    .name()
+            )),
+            Cell::new(if compensator.sign > 0.0 {
+                "увеличивающее"
+            } else {
+                "уменьшающее"
+            }),
+        ])
+        .add_row(vec![
+            Cell::new("замыкающее звено по чертежу"),
+            Cell::new(format!(
+                "{:.4} … {:.4}, TΔ = {:.4}",
+                required.0,
+                required.1,
+                required.1 - required.0
+            ))
+            .fg(Color::Green),
+        ])
+        .add_row(vec![
+            Cell::new("без компенсатора"),
+            Cell::new(format!(
+                "{:.4} … {:.4}, TΔ' = {:.4}",
+                produced_min,
+                produced_max,
+                produced_max - produced_min
+            ))
+            .fg(Color::Red),
+        ])
+        .add_row(vec![
+            Cell::new("наибольшая компенсация Vk"),
+            Cell::new(format!("{:.4}", compensation.range.max(0.0))).fg(Color::Blue),
+        ])
+        .add_row(vec![
+            Cell::new("допуск прокладки Tk"),
+            Cell::new(format!("{:.4}", compensator.tolerance())),
+        ])
+        .add_row(vec![
+            Cell::new("ступень S = TΔ - Tk"),
+            Cell::new(format!("{:.4}", compensation.step)),
+        ])
+        .add_row(vec![
+            Cell::new("число ступеней N"),
+            Cell::new(compensation.steps.len()).fg(Color::Blue),
+        ]);
+    println!("{table_result}");
+    if compensation.range <= 0.0 {
+        println!("Цепь замыкается без компенсатора: достаточно полной взаимозаменяемости");
+        return;
+    }
+
+    let tolerance = compensator.tolerance() / 2.0;
+    let mut table_steps = Table::new();
+    table_steps
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("ступень"),
+            Cell::new("размер без прокладки от"),
+            Cell::new("до"),
+            Cell::new("толщина прокладки").fg(Color::Green),
+        ]);
+    for (index, step) in compensation.steps.iter().enumerate() {
+        let color = if step.thickness - tolerance < 0.0 {
+            Color::Red
+        } else {
+            Color::Green
+        };
+        table_steps.add_row(vec![
+            Cell::new(index + 1),
+            Cell::new(format!("{:.4}", step.gap.0)),
+            Cell::new(format!("{:.4}", step.gap.1)),
+            Cell::new(format!("{:.4} ±{:.4}", step.thickness, tolerance)).fg(color),
+        ]);
+    }
+    println!("{table_steps}");
+    if compensation
+        .steps
+        .iter()
+        .any(|step| step.thickness - tolerance < 0.0)
+    {
+        println!(
+            "Отрицательная толщина прокладки: измените номиналы звеньев, \
+             чтобы компенсатор всегда имел толщину"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, epsilon: f64) {
+        assert!(
+            (actual - expected).abs() < epsilon,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_shim_input() {
+        let shim_query = parse_shim_input("shim +120±0.1 -50h9 -2±0,01 closing=0+0.2-0").unwrap();
+        assert_eq!(shim_query.contributors.len(), 3);
+        assert_eq!(shim_query.compensator, 2);
+        assert_eq!(
+            shim_query.closing,
+            (Decimal::ZERO, "0.2".parse().unwrap(), Decimal::ZERO)
+        );
+        assert_eq!(
+            parse_shim_input("shim +120±0.1 -2 comp=1 closing=0+0.2-0")
+                .unwrap()
+                .compensator,
+            0
+        );
+        assert_eq!(parse_shim_input("shim +120±0.1 -2"), None);
+        assert_eq!(
+            parse_shim_input("shim +120±0.1 -2 closing=0+0.2-0 comp=3"),
+            None
+        );
+        assert_eq!(parse_shim_input("shim -2 closing=0+0.2-0"), None);
+    }
+
+    #[test]
+    fn test_calc_compensation() {
+        // Без прокладки зазор 0.6 … 1.4, требуется 0.1 … 0.3, прокладка уменьшает зазор.
+        let compensation = calc_compensation((0.6, 1.4), (0.1, 0.3), false, 0.02).unwrap();
+        assert_close(compensation.range, 0.6, 1e-12);
+        assert_close(compensation.step, 0.18, 1e-12);
+        assert_eq!(compensation.steps.len(), 5);
+        assert_close(compensation.steps[0].thickness, 0.49, 1e-12);
+        assert_close(compensation.steps[1].thickness, 0.67, 1e-12);
+        assert_close(compensation.steps[4].gap.1, 1.4, 1e-12);
+        for step in &compensation.steps {
+            for gap in [step.gap.0, step.gap.1] {
+                let closing = (gap - step.thickness - 0.01, gap - step.thickness + 0.01);
+                assert!(closing.0 >= 0.1 - 1e-12 && closing.1 <= 0.3 + 1e-12);
+            }
+        }
+
+        // Прокладка увеличивает замыкающий размер.
+        let compensation = calc_compensation((-0.5, -0.3), (0.0, 0.1), true, 0.0).unwrap();
+        assert_eq!(compensation.steps.len(), 2);
+        assert_close(compensation.steps[0].thickness, 0.5, 1e-12);
+        assert_close(compensation.steps[1].thickness, 0.4, 1e-12);
+
+        let compensation = calc_compensation((0.1, 0.2), (0.0, 0.3), false, 0.0).unwrap();
+        assert_eq!(compensation.steps.len(), 1);
+        assert!(compensation.range < 0.0);
+
+        assert!(calc_compensation((0.6, 1.4), (0.1, 0.3), false, 0.2).is_err());
+    }
+}
@@ -0,0 +1,660 @@
+use std::fs;
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::inspection::{search_class_limits, ToleranceClass};
+use crate::measurements::{parse_samples, Samples};
+use crate::parse_input;
+use crate::statistics::{a2, d2, d3, d4, mean, range};
+
+const CHART_HEIGHT: usize = 15;
+const SVG_WIDTH: f64 = 800.0;
+const SVG_PANEL_HEIGHT: f64 = 280.0;
+const SVG_MARGIN: f64 = 70.0;
+
+/// Правила Western Electric / Nelson для карты средних и индивидуальных значений.
+pub const RULES: [&str; 8] = [
+    "точка за контрольной границей (3σ)",
+    "9 точек подряд по одну сторону от центральной линии",
+    "6 точек подряд монотонно возрастают или убывают",
+    "14 точек подряд поочерёдно растут и убывают",
+    "2 из 3 точек подряд за 2σ по одну сторону",
+    "4 из 5 точек подряд за 1σ по одну сторону",
+    "15 точек подряд в пределах 1σ",
+    "8 точек подряд за 1σ по обе стороны",
+];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ChartKind {
+    XbarR,
+    IndividualsMr,
+}
+
+impl ChartKind {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input {
+            "xr" => Some(ChartKind::XbarR),
+            "imr" => Some(ChartKind::IndividualsMr),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SpcQuery {
+    pub class: ToleranceClass,
+    pub samples: Samples,
+    /// None - X̄-R для файла с подгруппами в строках, иначе I-MR.
+    pub kind: Option<ChartKind>,
+    /// Объём подгруппы при разбиении потока значений; 0 - строки файла.
+    pub subgroup: usize,
+    pub svg: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ControlChart {
+    pub name: &'static str,
+    pub points: Vec<f64>,
+    pub center: f64,
+    pub upper: f64,
+    pub lower: f64,
+}
+
+impl ControlChart {
+    pub fn sigma(&self) -> f64 {
+        (self.upper - self.center) / 3.0
+    }
+}
+
+/// Нарушение правила `rule` (номер с 1) на точке `index`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Violation {
+    pub rule: usize,
+    pub index: usize,
+}
+
+pub fn parse_spc_input(input: &str) -> Option<SpcQuery> {
+    let mut tokens = input.strip_prefix("spc")?.split_whitespace();
+    let class = parse_input(tokens.next()?)?;
+    let (samples, params) = parse_samples(tokens, &["chart", "subgroup", "svg"])?;
+    let kind = match params.text("chart") {
+        Some(kind) => Some(ChartKind::parse(kind)?),
+        None => None,
+    };
+    let subgroup = params.number_or("subgroup", 0.0)?;
+    if subgroup.fract() != 0.0 || subgroup == 1.0 || !(0.0..=10.0).contains(&subgroup) {
+        return None;
+    }
+    Some(SpcQuery {
+        class,
+        samples,
+        kind,
+        subgroup: subgroup as usize,
+        svg: params.text("svg").map(str::to_string),
+    })
+}
+
+/// Карты X̄ и R по подгруппам одинакового объёма 2-10.
+pub fn xbar_r_charts(subgroups: &[Vec<f64>]) -> Option<(ControlChart, ControlChart)> {
+    let size = subgroups.first()?.len();
+    if subgroups.len() < 2 || subgroups.iter().any(|subgroup| subgroup.len() != size) {
+        return None;
+    }
+    let means: Vec<f64> = subgroups.iter().map(|subgroup| mean(subgroup)).collect();
+    let ranges: Vec<f64> = subgroups.iter().map(|subgroup| range(subgroup)).collect();
+    let grand_mean = mean(&means);
+    let mean_range = mean(&ranges);
+    let a2 = a2(size)?;
+    Some((
+        ControlChart {
+            name: "X̄",
+            points: means,
+            center: grand_mean,
+            upper: grand_mean + a2 * mean_range,
+            lower: grand_mean - a2 * mean_range,
+        },
+        ControlChart {
+            name: "R",
+            points: ranges,
+            center: mean_range,
+            upper: d4(size)? * mean_range,
+            lower: d3(size)? * mean_range,
+        },
+    ))
+}
+
+/// Карты индивидуальных значений и скользящих размахов.
+pub fn individuals_charts(values: &[f64]) -> Option<(ControlChart, ControlChart)> {
+    if values.len() < 2 {
+        return None;
+    }
+    let moving_ranges: Vec<f64> = values
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).abs())
+        .collect();
+    let center = mean(values);
+    let mean_range = mean(&moving_ranges);
+    let sigma = mean_range / d2(2)?;
+    Some((
+        ControlChart {
+            name: "I",
+            points: values.to_vec(),
+            center,
+            upper: center + 3.0 * sigma,
+            lower: center - 3.0 * sigma,
+        },
+        ControlChart {
+            name: "MR",
+            points: moving_ranges,
+            center: mean_range,
+            upper: d4(2)? * mean_range,
+            lower: 0.0,
+        },
+    ))
+}
+
+/// Длина серии подряд идущих точек, удовлетворяющих условию, на каждой точке.
+fn run_lengths(flags: impl Iterator<Item = bool>) -> Vec<usize> {
+    flags
+        .scan(0, |run, flag| {
+            *run = if flag { *run + 1 } else { 0 };
+            Some(*run)
+        })
+        .collect()
+}
+
+/// Проверка правил Nelson 1-8. Точка отмечается, если на ней выполняется условие правила.
+pub fn rule_violations(chart: &ControlChart) -> Vec<Violation> {
+    let sigma = chart.sigma();
+    if sigma <= 0.0 {
+        return Vec::new();
+    }
+    let z: Vec<f64> = chart
+        .points
+        .iter()
+        .map(|point| (point - chart.center) / sigma)
+        .collect();
+    let diffs: Vec<f64> = chart
+        .points
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .collect();
+    let mut violations = Vec::new();
+    let mut mark = |rule: usize, flags: Vec<bool>| {
+        for (index, flag) in flags.into_iter().enumerate() {
+            if flag {
+                violations.push(Violation { rule, index });
+            }
+        }
+    };
+    mark(
+        1,
+        chart
+            .points
+            .iter()
+            .map(|point| *point > chart.upper || *point < chart.lower)
+            .collect(),
+    );
+    let above = run_lengths(z.iter().map(|z| *z > 0.0));
+    let below = run_lengths(z.iter().map(|z| *z < 0.0));
+    mark(
+        2,
+        (0..z.len())
+            .map(|i| above[i] >= 9 || below[i] >= 9)
+            .collect(),
+    );
+    let rising = run_lengths(diffs.iter().map(|diff| *diff > 0.0));
+    let falling = run_lengths(diffs.iter().map(|diff| *diff < 0.0));
+    mark(
+        3,
+        (0..z.len())
+            .map(|i| i > 0 && (rising[i - 1] >= 5 || falling[i - 1] >= 5))
+            .collect(),
+    );
+    let alternating = run_lengths(
+        (0..diffs.len()).map(|i| diffs[i] != 0.0 && (i == 0 || diffs[i] * diffs[i - 1] < 0.0)),
+    );
+    mark(
+        4,
+        (0..z.len())
+            .map(|i| i > 0 && alternating[i - 1] >= 13)
+            .collect(),
+    );
+    let beyond = |i: usize, window: usize, limit: f64, count: usize| {
+        i + 1 >= window
+            && [1.0, -1.0].iter().any(|side| {
+                z[i + 1 - window..=i]
+                    .iter()
+                    .filter(|z| **z * side > limit)
+                    .count()
+                    >= count
+            })
+    };
+    mark(5, (0..z.len()).map(|i| beyond(i, 3, 2.0, 2)).collect());
+    mark(6, (0..z.len()).map(|i| beyond(i, 5, 1.0, 4)).collect());
+    let within = run_lengths(z.iter().map(|z| z.abs() < 1.0));
+    mark(7, within.iter().map(|run| *run >= 15).collect());
+    let outside = run_lengths(z.iter().map(|z| z.abs() > 1.0));
+    mark(
+        8,
+        (0..z.len())
+            .map(|i| {
+                let run = &z[(i + 1).saturating_sub(8)..=i];
+                outside[i] >= 8 && run.iter().any(|z| *z > 1.0) && run.iter().any(|z| *z < -1.0)
+            })
+            .collect(),
+    );
+    violations.sort_by_key(|violation| (violation.index, violation.rule));
+    violations
+}
+
+/// Текстовый график карты: контрольные границы `─`, центральная линия `·`,
+/// пределы поля допуска `═`, точки `●`, точки с нарушениями `✖`.
+pub fn render_chart(
+    chart: &ControlChart,
+    spec: Option<(f64, f64)>,
+    violations: &[Violation],
+) -> Vec<String> {
+    let mut lines = vec![
+        ("UCL", chart.upper, '─'),
+        ("CL", chart.center, '·'),
+        ("LCL", chart.lower, '─'),
+    ];
+    if let Some((upper, lower)) = spec {
+        lines.push(("USL", upper, '═'));
+        lines.push(("LSL", lower, '═'));
+    }
+    let values = chart
+        .points
+        .iter()
+        .copied()
+        .chain(lines.iter().map(|(_, value, _)| *value));
+    let max = values.clone().fold(f64::MIN, f64::max);
+    let min = values.fold(f64::MAX, f64::min);
+    let row_of = |value: f64| {
+        if max > min {
+            ((max - value) / (max - min) * (CHART_HEIGHT - 1) as f64).round() as usize
+        } else {
+            CHART_HEIGHT / 2
+        }
+    };
+    let width = chart.points.len() * 2;
+    let mut grid = vec![vec![' '; width]; CHART_HEIGHT];
+    let mut labels = vec![String::new(); CHART_HEIGHT];
+    for (name, value, symbol) in &lines {
+        let row = row_of(*value);
+        grid[row].iter_mut().for_each(|cell| *cell = *symbol);
+        if labels[row].is_empty() {
+            labels[row] = format!("{} {:.4}", name, value);
+        }
+    }
+    for (index, point) in chart.points.iter().enumerate() {
+        let violated = violations.iter().any(|violation| violation.index == index);
+        grid[row_of(*point)][index * 2] = if violated { '✖' } else { '●' };
+    }
+    grid.into_iter()
+        .zip(labels)
+        .map(|(row, label)| format!("{:>16} │{}", label, row.into_iter().collect::<String>()))
+        .collect()
+}
+
+fn svg_panel(
+    chart: &ControlChart,
+    spec: Option<(f64, f64)>,
+    violations: &[Violation],
+    top: f64,
+) -> String {
+    let mut lines = vec![
+        ("UCL", chart.upper, "#d62728", "6,4"),
+        ("CL", chart.center, "#2ca02c", "none"),
+        ("LCL", chart.lower, "#d62728", "6,4"),
+    ];
+    if let Some((upper, lower)) = spec {
+        lines.push(("USL", upper, "#9467bd", "none"));
+        lines.push(("LSL", lower, "#9467bd", "none"));
+    }
+    let values = chart
+        .points
+        .iter()
+        .copied()
+        .chain(lines.iter().map(|(_, value, _, _)| *value));
+    let max = values.clone().fold(f64::MIN, f64::max);
+    let min = values.fold(f64::MAX, f64::min);
+    let plot_height = SVG_PANEL_HEIGHT - 40.0;
+    let y = |value: f64| {
+        if max > min {
+            top + 20.0 + (max - value) / (max - min) * plot_height
+        } else {
+            top + 20.0 + plot_height * 0.5
+        }
+    };
+    let step = if chart.points.len() > 1 {
+        (SVG_WIDTH - 2.0 * SVG_MARGIN) / (chart.points.len() - 1) as f64
+    } else {
+        0.0
+    };
+    let x = |index: usize| SVG_MARGIN + step * index as f64;
+    let mut svg = format!(
+        "<text x=\"10\" y=\"{:.1}\" font-size=\"14\">{}</text>\n",
+        top + 14.0,
+        chart.name
+    );
+    for (name, value, color, dash) in &lines {
+        svg += &format!(
+            "<line x1=\"{:.1}\" y1=\"{2:.1}\" x2=\"{1:.1}\" y2=\"{2:.1}\" stroke=\"{3}\" stroke-dasharray=\"{4}\"/>\n\
+             <text x=\"{1:.1}\" y=\"{2:.1}\" font-size=\"11\" fill=\"{3}\" dx=\"4\">{5} {6:.4}</text>\n",
+            SVG_MARGIN,
+            SVG_WIDTH - SVG_MARGIN,
+            y(*value),
+            color,
+            dash,
+            name,
+            value
+        );
+    }
+    let points: Vec<String> = chart
+        .points
+        .iter()
+        .enumerate()
+        .map(|(index, point)| format!("{:.1},{:.1}", x(index), y(*point)))
+        .collect();
+    svg += &format!(
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"#1f77b4\"/>\n",
+        points.join(" ")
+    );
+    for (index, point) in chart.points.iter().enumerate() {
+        let violated = violations.iter().any(|violation| violation.index == index);
+        svg += &format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"{}\"/>\n",
+            x(index),
+            y(*point),
+            if violated { 4 } else { 3 },
+            if violated { "#d62728" } else { "#1f77b4" }
+        );
+    }
+    svg
+}
+
+/// SVG с картой средних (индивидуальных значений) и картой размахов.
+pub fn render_svg(
+    center_chart: &ControlChart,
+    range_chart: &ControlChart,
+    spec: (f64, f64),
+    violations: (&[Violation], &[Violation]),
+) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" font-family=\"sans-serif\">\n\
+         <rect width=\"{0}\" height=\"{1}\" fill=\"white\"/>\n{2}{3}</svg>\n",
+        SVG_WIDTH,
+        SVG_PANEL_HEIGHT * 2.0,
+        svg_panel(center_chart, Some(spec), violations.0, 0.0),
+        svg_panel(range_chart, None, violations.1, SVG_PANEL_HEIGHT)
+    )
+}
+
+pub fn handle_spc(spc_query: &SpcQuery) {
+    let rows = match spc_query.samples.load() {
+        Ok(rows) => rows,
+        Err(e) => {
+            println!("Ошибка: {}", e);
+            return;
+        }
+    };
+    let values: Vec<f64> = rows.iter().flatten().copied().collect();
+    let kind = spc_query.kind.unwrap_or(
+        if spc_query.subgroup > 1 || rows.iter().all(|row| row.len() > 1) {
+            ChartKind::XbarR
+        } else {
+            ChartKind::IndividualsMr
+        },
+    );
+    let charts = match kind {
+        ChartKind::XbarR if spc_query.subgroup > 1 => xbar_r_charts(
+            &values
+                .chunks_exact(spc_query.subgroup)
+                .map(|chunk| chunk.to_vec())
+                .collect::<Vec<_>>(),
+        ),
+        ChartKind::XbarR => xbar_r_charts(&rows),
+        ChartKind::IndividualsMr => individuals_charts(&values),
+    };
+    let (center_chart, range_chart) = match charts {
+        Some(charts) => charts,
+        None => {
+            println!(
+                "Недостаточно данных: для X̄-R нужно не меньше двух подгрупп одинакового \
+                 объёма 2-10, для I-MR - не меньше двух значений"
+            );
+            return;
+        }
+    };
+    match search_class_limits(&spc_query.class) {
+        Ok(Some((upper, lower))) => {
+            let spec = (
+                upper.to_f64().unwrap_or_default(),
+                lower.to_f64().unwrap_or_default(),
+            );
+            let center_violations = rule_violations(&center_chart);
+            let range_violations: Vec<Violation> = rule_violations(&range_chart)
+                .into_iter()
+                .filter(|violation| violation.rule == 1)
+                .collect();
+            print_spc(
+                spc_query,
+                (&center_chart, &range_chart),
+                spec,
+                (&center_violations, &range_violations),
+            );
+            if let Some(path) = &spc_query.svg {
+                let svg = render_svg(
+                    &center_chart,
+                    &range_chart,
+                    spec,
+                    (&center_violations, &range_violations),
+                );
+                match fs::write(path, svg) {
+                    Ok(()) => println!("Карта сохранена в {}", path),
+                    Err(e) => println!("Ошибка записи {}: {}", path, e),
+                }
+            }
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_spc(
+    spc_query: &SpcQuery,
+    charts: (&ControlChart, &ControlChart),
+    spec: (f64, f64),
+    violations: (&[Violation], &[Violation]),
+) {
+    let (size, field, accuracy) = &spc_query.class;
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new(format!("{}{}{}", size, field, accuracy)),
+            Cell::new("LCL").fg(Color::Cyan),
+            Cell::new("CL").fg(Color::Green),
+            Cell::new("UCL").fg(Color::Red),
+            Cell::new("точек"),
+        ]);
+    for chart in [charts.0, charts.1] {
+        table_result.add_row(vec![
+            Cell::new(chart.name),
+            Cell::new(format!("{:.5}", chart.lower)).fg(Color::Cyan),
+            Cell::new(format!("{:.5}", chart.center)).fg(Color::Green),
+            Cell::new(format!("{:.5}", chart.upper)).fg(Color::Red),
+            Cell::new(chart.points.len()),
+        ]);
+    }
+    table_result.add_row(vec![
+        Cell::new("поле допуска"),
+        Cell::new(format!("{:.5}", spec.1)).fg(Color::Cyan),
+        Cell::new(""),
+        Cell::new(format!("{:.5}", spec.0)).fg(Color::Red),
+        Cell::new(""),
+    ]);
+    println!("{table_result}");
+
+    for line in render_chart(charts.0, Some(spec), violations.0) {
+        println!("{}", line);
+    }
+    println!();
+    for line in render_chart(charts.1, None, violations.1) {
+        println!("{}", line);
+    }
+
+    if violations.0.is_empty() && violations.1.is_empty() {
+        println!("Процесс статистически управляем: нарушений правил нет");
+        return;
+    }
+    let mut table_violations = Table::new();
+    table_violations
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["карта", "точка", "правило"]);
+    for (chart, chart_violations) in [(charts.0, violations.0), (charts.1, violations.1)] {
+        for violation in chart_violations {
+            table_violations.add_row(vec![
+                Cell::new(chart.name),
+                Cell::new(violation.index + 1),
+                Cell::new(format!("{}: {}", violation.rule, RULES[violation.rule - 1]))
+                    .fg(Color::Red),
+            ]);
+        }
+    }
+    println!("{table_violations}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, epsilon: f64) {
+        assert!(
+            (actual - expected).abs() < epsilon,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    fn chart(points: Vec<f64>) -> ControlChart {
+        ControlChart {
+            name: "I",
+            points,
+            center: 0.0,
+            upper: 3.0,
+            lower: -3.0,
+        }
+    }
+
+    fn rules(chart: &ControlChart) -> Vec<(usize, usize)> {
+        rule_violations(chart)
+            .iter()
+            .map(|violation| (violation.rule, violation.index))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_spc_input() {
+        let query = parse_spc_input("spc 30h6 file=data.csv svg=chart.svg").unwrap();
+        assert_eq!(query.samples, Samples::File("data.csv".to_string()));
+        assert_eq!(query.kind, None);
+        assert_eq!(query.subgroup, 0);
+        assert_eq!(query.svg, Some("chart.svg".to_string()));
+
+        let query = parse_spc_input("spc 30h6 29.99 29.995 30 chart=imr").unwrap();
+        assert_eq!(query.kind, Some(ChartKind::IndividualsMr));
+
+        let query = parse_spc_input("spc 30h6 file=data.csv chart=xr subgroup=5").unwrap();
+        assert_eq!(query.kind, Some(ChartKind::XbarR));
+        assert_eq!(query.subgroup, 5);
+
+        assert_eq!(parse_spc_input("spc 30h6 file=data.csv chart=p"), None);
+        assert_eq!(parse_spc_input("spc 30h6 file=data.csv subgroup=1"), None);
+        assert_eq!(parse_spc_input("spc 30h6"), None);
+    }
+
+    #[test]
+    fn test_xbar_r_charts() {
+        let (xbar, r) = xbar_r_charts(&[vec![1.0, 2.0, 3.0], vec![2.0, 3.0, 4.0]]).unwrap();
+        assert_eq!(xbar.points, vec![2.0, 3.0]);
+        assert_close(xbar.center, 2.5, 1e-12);
+        assert_close(xbar.upper, 4.546, 1e-9);
+        assert_close(xbar.lower, 0.454, 1e-9);
+        assert_close(r.center, 2.0, 1e-12);
+        assert_close(r.upper, 5.148, 1e-9);
+        assert_close(r.lower, 0.0, 1e-12);
+
+        assert_eq!(xbar_r_charts(&[vec![1.0, 2.0], vec![1.0, 2.0, 3.0]]), None);
+        assert_eq!(xbar_r_charts(&[vec![1.0, 2.0]]), None);
+        assert_eq!(xbar_r_charts(&[vec![1.0], vec![2.0]]), None);
+    }
+
+    #[test]
+    fn test_individuals_charts() {
+        let (individuals, moving_range) = individuals_charts(&[1.0, 2.0, 3.0]).unwrap();
+        assert_close(individuals.center, 2.0, 1e-12);
+        assert_close(individuals.upper, 2.0 + 3.0 / 1.128, 1e-9);
+        assert_eq!(moving_range.points, vec![1.0, 1.0]);
+        assert_close(moving_range.upper, 3.267, 1e-9);
+        assert_eq!(individuals_charts(&[1.0]), None);
+    }
+
+    #[test]
+    fn test_rule_violations() {
+        assert_eq!(rules(&chart(vec![0.5, -3.5, 0.2])), vec![(1, 1)]);
+        assert_eq!(rules(&chart(vec![0.5; 9])), vec![(2, 8)]);
+        assert_eq!(
+            rules(&chart(vec![-1.2, -0.8, -0.4, 0.0, 0.4, 0.8])),
+            vec![(3, 5)]
+        );
+        let alternating: Vec<f64> = (0..14)
+            .map(|i| if i % 2 == 0 { 0.5 } else { -0.5 })
+            .collect();
+        assert_eq!(rules(&chart(alternating)), vec![(4, 13)]);
+        assert_eq!(rules(&chart(vec![2.5, 0.0, 2.5])), vec![(5, 2)]);
+        assert_eq!(rules(&chart(vec![1.5, 1.5, -0.5, 1.5, 1.5])), vec![(6, 4)]);
+        let within: Vec<f64> = (0..15)
+            .map(|i| if i % 3 == 0 { 0.1 } else { -0.1 })
+            .collect();
+        assert_eq!(rules(&chart(within)), vec![(7, 14)]);
+        let outside: Vec<f64> = (0..8).map(|i| if i % 4 < 2 { 1.5 } else { -1.5 }).collect();
+        assert_eq!(rules(&chart(outside)), vec![(8, 7)]);
+        // По одну сторону правило 8 не срабатывает, только правило 6.
+        assert_eq!(
+            rules(&chart(vec![1.5; 8])),
+            vec![(6, 4), (6, 5), (6, 6), (6, 7)]
+        );
+    }
+
+    #[test]
+    fn test_render_chart() {
+        let chart = chart(vec![0.0, 4.0]);
+        let violations = rule_violations(&chart);
+        let lines = render_chart(&chart, Some((5.0, -5.0)), &violations);
+        assert_eq!(lines.len(), CHART_HEIGHT);
+        assert!(lines[0].contains("USL 5.0000"));
+        assert!(lines[CHART_HEIGHT - 1].contains("LSL -5.0000"));
+        assert!(lines.iter().any(|line| line.contains('✖')));
+        assert!(lines
+            .iter()
+            .any(|line| line.contains("CL 0.0000") && line.contains('●')));
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let (individuals, moving_range) = individuals_charts(&[1.0, 2.0, 3.0]).unwrap();
+        let svg = render_svg(&individuals, &moving_range, (4.0, 0.0), (&[], &[]));
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert_eq!(svg.matches("<circle").count(), 5);
+    }
+}
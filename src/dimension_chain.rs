@@ -0,0 +1,801 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use regex::Regex;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::params::Params;
+use crate::stackup::{calc_stack, parse_contributor, parse_dimension, resolve_links, Contributor};
+use crate::statistics::{normal_quantile, Distribution};
+use crate::{replace_comma_with_dot, search_it_value};
+
+/// Единица допуска i, мкм, по интервалам размеров до 500 мм (ГОСТ 16320-80).
+const TOLERANCE_UNITS: [(f64, f64); 13] = [
+    (3.0, 0.55),
+    (6.0, 0.73),
+    (10.0, 0.90),
+    (18.0, 1.08),
+    (30.0, 1.31),
+    (50.0, 1.56),
+    (80.0, 1.86),
+    (120.0, 2.17),
+    (180.0, 2.52),
+    (250.0, 2.89),
+    (315.0, 3.22),
+    (400.0, 3.54),
+    (500.0, 3.89),
+];
+/// Число единиц допуска в квалитетах 5-18.
+#[rustfmt::skip]
+const GRADE_UNITS: [(u8, f64); 14] = [
+    (5, 7.0), (6, 10.0), (7, 16.0), (8, 25.0), (9, 40.0), (10, 64.0), (11, 100.0),
+    (12, 160.0), (13, 250.0), (14, 400.0), (15, 640.0), (16, 1000.0), (17, 1600.0), (18, 2500.0),
+];
+const FINEST_GRADE: u8 = 1;
+const COARSEST_GRADE: u8 = 18;
+/// Процент риска по умолчанию: выход за пределы ±3σ.
+const DEFAULT_RISK: f64 = 0.27;
+
+/// Расчёт замыкающего звена: на максимум-минимум или вероятностный
+/// с коэффициентом риска t.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Calculation {
+    MaxMin,
+    Probabilistic(f64),
+}
+
+impl Calculation {
+    /// Допуск замыкающего звена по допускам составляющих.
+    pub fn closing_tolerance(&self, tolerances: &[f64], distribution: Distribution) -> f64 {
+        match self {
+            Calculation::MaxMin => tolerances.iter().sum(),
+            Calculation::Probabilistic(t) => {
+                2.0 * t
+                    * tolerances
+                        .iter()
+                        .map(|tolerance| distribution.sigma(0.0, *tolerance).powi(2))
+                        .sum::<f64>()
+                        .sqrt()
+            }
+        }
+    }
+
+    /// Допуск звена, при котором цепь замыкается точно. None - остальные звенья
+    /// уже выбирают весь допуск замыкающего звена.
+    pub fn remaining_tolerance(
+        &self,
+        closing: f64,
+        tolerances: &[f64],
+        distribution: Distribution,
+    ) -> Option<f64> {
+        let remaining = match self {
+            Calculation::MaxMin => closing - tolerances.iter().sum::<f64>(),
+            Calculation::Probabilistic(t) => {
+                let sigma = closing / (2.0 * t);
+                let variance = sigma.powi(2)
+                    - tolerances
+                        .iter()
+                        .map(|tolerance| distribution.sigma(0.0, *tolerance).powi(2))
+                        .sum::<f64>();
+                if variance > 0.0 {
+                    variance.sqrt() / distribution.sigma(0.0, 1.0)
+                } else {
+                    0.0
+                }
+            }
+        };
+        (remaining > 0.0).then_some(remaining)
+    }
+
+    /// Масштаб, на который умножаются «веса» звеньев, чтобы получить допуск замыкающего
+    /// звена: для способа равных допусков вес - 1, для способа одного квалитета - i.
+    pub fn scale(&self, closing: f64, weights: &[f64], distribution: Distribution) -> f64 {
+        closing / self.closing_tolerance(weights, distribution)
+    }
+}
+
+/// Расположение поля допуска звена: в тело вала (h), отверстия (H) или симметрично (js).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FieldPosition {
+    Shaft,
+    Hole,
+    Symmetric,
+}
+
+impl FieldPosition {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input {
+            "h" => Some(FieldPosition::Shaft),
+            "H" => Some(FieldPosition::Hole),
+            "" | "js" | "JS" => Some(FieldPosition::Symmetric),
+            _ => None,
+        }
+    }
+
+    pub fn field(&self) -> &'static str {
+        match self {
+            FieldPosition::Shaft => "h",
+            FieldPosition::Hole => "H",
+            FieldPosition::Symmetric => "js",
+        }
+    }
+
+    /// Верхнее и нижнее отклонения при допуске `tolerance`.
+    pub fn deviations(&self, tolerance: Decimal) -> (Decimal, Decimal) {
+        match self {
+            FieldPosition::Shaft => (Decimal::ZERO, -tolerance),
+            FieldPosition::Hole => (tolerance, Decimal::ZERO),
+            FieldPosition::Symmetric => (tolerance / dec!(2), -tolerance / dec!(2)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ChainMethod {
+    EqualTolerance,
+    EqualPrecision,
+}
+
+/// Звено обратной задачи: номинал и расположение поля допуска.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ChainLink {
+    pub increasing: bool,
+    pub nominal: Decimal,
+    pub position: FieldPosition,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ChainQuery {
+    /// Прямая задача: замыкающее звено по составляющим.
+    Direct {
+        contributors: Vec<Contributor>,
+        distribution: Distribution,
+        t: f64,
+    },
+    /// Обратная задача: допуски составляющих по замыкающему звену (номинал, ES, EI).
+    Inverse {
+        links: Vec<ChainLink>,
+        closing: (Decimal, Decimal, Decimal),
+        method: ChainMethod,
+        calculation: Calculation,
+        distribution: Distribution,
+        /// Индекс увязочного звена.
+        adjusting: usize,
+    },
+}
+
+/// Назначенные отклонения звена; `grade` - None у увязочного звена.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LinkTolerance {
+    pub grade: Option<u8>,
+    pub upper: Decimal,
+    pub lower: Decimal,
+}
+
+impl LinkTolerance {
+    pub fn tolerance(&self) -> Decimal {
+        self.upper - self.lower
+    }
+
+    pub fn middle(&self) -> Decimal {
+        (self.upper + self.lower) / dec!(2)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ChainSolution {
+    /// Средний допуск (способ равных допусков, мм) или число единиц допуска a.
+    pub target: f64,
+    pub links: Vec<LinkTolerance>,
+}
+
+/// Звено вида `+100H`, `-40h` или `-58` (поле js).
+pub fn parse_chain_link(input: &str) -> Option<ChainLink> {
+    let regex = Regex::new(r"^(?P<sign>[+-])(?P<nominal>\d+(?:[.,]\d+)?)(?P<field>h|H|js|JS)?$")
+        .expect("Ошибка обработки RegEx");
+    let captures = regex.captures(input)?;
+    Some(ChainLink {
+        increasing: &captures["sign"] == "+",
+        nominal: replace_comma_with_dot(&captures["nominal"]).parse().ok()?,
+        position: FieldPosition::parse(captures.name("field").map_or("", |field| field.as_str()))?,
+    })
+}
+
+pub fn parse_chain_input(input: &str) -> Option<ChainQuery> {
+    let tokens = input.strip_prefix("chain ")?.split_whitespace();
+    let (params, links): (Vec<&str>, Vec<&str>) = tokens.partition(|token| token.contains('='));
+    let params = Params::parse(
+        params.into_iter(),
+        &["closing", "method", "calc", "risk", "dist", "adjust"],
+    )?;
+    if links.is_empty() {
+        return None;
+    }
+    let risk = params.number_or("risk", DEFAULT_RISK)?;
+    if !(0.0 < risk && risk < 100.0) {
+        return None;
+    }
+    let t = normal_quantile(1.0 - risk / 200.0);
+    let distribution = match params.text("dist") {
+        Some(distribution) => Distribution::parse(distribution)?,
+        None => Distribution::Normal(3.0),
+    };
+    let closing = match params.text("closing") {
+        Some(closing) => parse_dimension(&replace_comma_with_dot(closing))?,
+        None => {
+            if ["method", "calc", "adjust"]
+                .iter()
+                .any(|key| params.text(key).is_some())
+            {
+                return None;
+            }
+            return Some(ChainQuery::Direct {
+                contributors: links
+                    .into_iter()
+                    .map(parse_contributor)
+                    .collect::<Option<Vec<Contributor>>>()?,
+                distribution,
+                t,
+            });
+        }
+    };
+    let links = links
+        .into_iter()
+        .map(parse_chain_link)
+        .collect::<Option<Vec<ChainLink>>>()?;
+    let method = match params.text("method") {
+        None | Some("precision") => ChainMethod::EqualPrecision,
+        Some("tolerance") => ChainMethod::EqualTolerance,
+        Some(_) => return None,
+    };
+    let calculation = match params.text("calc") {
+        None | Some("max") => Calculation::MaxMin,
+        Some("prob") => Calculation::Probabilistic(t),
+        Some(_) => return None,
+    };
+    let adjusting = params.number_or("adjust", links.len() as f64)?;
+    if adjusting.fract() != 0.0 || !(1.0..=links.len() as f64).contains(&adjusting) {
+        return None;
+    }
+    Some(ChainQuery::Inverse {
+        links,
+        closing,
+        method,
+        calculation,
+        distribution,
+        adjusting: adjusting as usize - 1,
+    })
+}
+
+/// Единица допуска i, мкм; свыше 500 мм - I = 0.004·D + 2.1.
+pub fn tolerance_unit(size: f64) -> f64 {
+    TOLERANCE_UNITS
+        .iter()
+        .find(|(upper, _)| size <= *upper)
+        .map_or(0.004 * size + 2.1, |(_, unit)| *unit)
+}
+
+/// Наиболее грубый квалитет с числом единиц допуска не больше `units`.
+pub fn precision_grade(units: f64) -> Option<u8> {
+    GRADE_UNITS
+        .iter()
+        .rev()
+        .find(|(_, grade_units)| *grade_units <= units)
+        .map(|(grade, _)| *grade)
+}
+
+/// Номинал замыкающего звена: сумма увеличивающих минус сумма уменьшающих.
+pub fn closing_nominal(links: &[ChainLink]) -> Decimal {
+    links
+        .iter()
+        .map(|link| {
+            if link.increasing {
+                link.nominal
+            } else {
+                -link.nominal
+            }
+        })
+        .sum()
+}
+
+/// Обратная задача. Звеньям, кроме увязочного, назначаются квалитеты выбранным способом
+/// (`it` - допуск квалитета для номинала); увязочное звено замыкает цепь.
+/// Если на увязочное звено допуска не остаётся, квалитеты уточняются на единицу.
+pub fn solve_inverse<F>(
+    links: &[ChainLink],
+    closing: (Decimal, Decimal),
+    method: ChainMethod,
+    calculation: Calculation,
+    distribution: Distribution,
+    adjusting: usize,
+    it: F,
+) -> Result<Option<ChainSolution>, String>
+where
+    F: Fn(Decimal, u8) -> Result<Option<Decimal>, String>,
+{
+    let (closing_upper, closing_lower) = closing;
+    let closing_tolerance = (closing_upper - closing_lower).to_f64().unwrap_or_default();
+    let to_f64 = |value: Decimal| value.to_f64().unwrap_or_default();
+    let (target, mut grades) = match method {
+        ChainMethod::EqualTolerance => {
+            let weights = vec![1.0; links.len()];
+            let average = calculation.scale(closing_tolerance, &weights, distribution);
+            let mut grades = Vec::new();
+            for link in links {
+                let mut grade = None;
+                for candidate in FINEST_GRADE..=COARSEST_GRADE {
+                    match it(link.nominal, candidate)? {
+                        Some(value) if to_f64(value) <= average => grade = Some(candidate),
+                        Some(_) => break,
+                        None => return Ok(None),
+                    }
+                }
+                grades.push(grade);
+            }
+            (average, grades)
+        }
+        ChainMethod::EqualPrecision => {
+            let units: Vec<f64> = links
+                .iter()
+                .map(|link| tolerance_unit(to_f64(link.nominal)))
+                .collect();
+            let a = calculation.scale(closing_tolerance * 1000.0, &units, distribution);
+            (a, vec![precision_grade(a); links.len()])
+        }
+    };
+    loop {
+        if grades
+            .iter()
+            .enumerate()
+            .any(|(index, grade)| index != adjusting && grade.is_none())
+        {
+            return Err(
+                "допуск замыкающего звена слишком мал для стандартных квалитетов".to_string(),
+            );
+        }
+        let mut tolerances = Vec::new();
+        for (index, (link, grade)) in links.iter().zip(&grades).enumerate() {
+            if index == adjusting {
+                tolerances.push(None);
+                continue;
+            }
+            let grade = grade.unwrap_or(FINEST_GRADE);
+            let value = match it(link.nominal, grade)? {
+                Some(value) => value,
+                None => return Ok(None),
+            };
+            let (upper, lower) = link.position.deviations(value);
+            tolerances.push(Some(LinkTolerance {
+                grade: Some(grade),
+                upper,
+                lower,
+            }));
+        }
+        let others: Vec<f64> = tolerances
+            .iter()
+            .flatten()
+            .map(|link| to_f64(link.tolerance()))
+            .collect();
+        if let Some(remaining) =
+            calculation.remaining_tolerance(closing_tolerance, &others, distribution)
+        {
+            let remaining = Decimal::from_f64(remaining)
+                .unwrap_or_default()
+                .round_dp_with_strategy(4, rust_decimal::RoundingStrategy::ToZero);
+            let middle_others: Decimal = links
+                .iter()
+                .zip(&tolerances)
+                .filter_map(|(link, tolerance)| {
+                    tolerance.map(|tolerance| {
+                        if link.increasing {
+                            tolerance.middle()
+                        } else {
+                            -tolerance.middle()
+                        }
+                    })
+                })
+                .sum();
+            let closing_middle = (closing_upper + closing_lower) / dec!(2);
+            let middle = if links[adjusting].increasing {
+                closing_middle - middle_others
+            } else {
+                middle_others - closing_middle
+            };
+            tolerances[adjusting] = Some(LinkTolerance {
+                grade: None,
+                upper: (middle + remaining / dec!(2)).normalize(),
+                lower: (middle - remaining / dec!(2)).normalize(),
+            });
+            return Ok(Some(ChainSolution {
+                target,
+                links: tolerances.into_iter().flatten().collect(),
+            }));
+        }
+        grades = grades
+            .into_iter()
+            .map(|grade| grade.and_then(|grade| grade.checked_sub(1)))
+            .map(|grade| grade.filter(|grade| *grade >= FINEST_GRADE))
+            .collect();
+    }
+}
+
+/// Допуск квалитета IT по таблицам БД.
+pub fn it_value(nominal: Decimal, grade: u8) -> Result<Option<Decimal>, String> {
+    search_it_value(&nominal.normalize().to_string(), grade).map_err(|e| e.to_string())
+}
+
+pub fn handle_chain(chain_query: &ChainQuery) {
+    match chain_query {
+        ChainQuery::Direct {
+            contributors,
+            distribution,
+            t,
+        } => match resolve_links(contributors, *distribution) {
+            Ok(Some(links)) => print_direct(contributors, &links, *t),
+            Ok(None) => println!("Ничего не найдено для заданных параметров"),
+            Err(e) => println!("Ошибка при поиске в БД: {}", e),
+        },
+        ChainQuery::Inverse {
+            links,
+            closing,
+            method,
+            calculation,
+            distribution,
+            adjusting,
+        } => {
+            let (nominal, upper, lower) = *closing;
+            if closing_nominal(links) != nominal {
+                println!(
+                    "Номинал замыкающего звена по составляющим {}, а задан {}",
+                    closing_nominal(links).normalize(),
+                    nominal
+                );
+                return;
+            }
+            match solve_inverse(
+                links,
+                (upper, lower),
+                *method,
+                *calculation,
+                *distribution,
+                *adjusting,
+                it_value,
+            ) {
+                Ok(Some(solution)) => print_inverse(chain_query, &solution),
+                Ok(None) => println!("Ничего не найдено для заданных параметров"),
+                Err(e) => println!("Ошибка: {}", e),
+            }
+        }
+    }
+}
+
+fn print_direct(contributors: &[Contributor], links: &[crate::stackup::Link], t: f64) {
+    let mut table_links = Table::new();
+    table_links
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("звено"),
+            Cell::new("ξ"),
+            Cell::new("ES").fg(Color::Red),
+            Cell::new("EI").fg(Color::Cyan),
+            Cell::new("Ec").fg(Color::Green),
+            Cell::new("T").fg(Color::Blue),
+        ]);
+    for (contributor, link) in contributors.iter().zip(links) {
+        table_links.add_row(vec![
+            Cell::new(format!(
+                "A{} = {}",
+                table_links.row_count() + 1,
+                contributor.source.name()
+            )),
+            Cell::new(format!("{:+}", link.sign)),
+            Cell::new(format!("{:+.4}", link.upper - link.nominal)).fg(Color::Red),
+            Cell::new(format!("{:+.4}", link.lower - link.nominal)).fg(Color::Cyan),
+            Cell::new(format!(
+                "{:+.4}",
+                (link.upper + link.lower) * 0.5 - link.nominal
+            ))
+            .fg(Color::Green),
+            Cell::new(format!("{:.4}", link.tolerance())).fg(Color::Blue),
+        ]);
+    }
+    println!("{table_links}");
+
+    let result = calc_stack(links);
+    let probabilistic = (
+        result.mean - t * result.sigma,
+        result.mean + t * result.sigma,
+    );
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new(format!("AΔ = {:.4}", result.nominal)),
+            Cell::new("ESΔ").fg(Color::Red),
+            Cell::new("EIΔ").fg(Color::Cyan),
+            Cell::new("EcΔ").fg(Color::Green),
+            Cell::new("TΔ").fg(Color::Blue),
+        ]);
+    for (name, (min, max)) in [
+        ("максимум-минимум".to_string(), result.worst_case),
+        (format!("вероятностный, t = {:.2}", t), probabilistic),
+    ] {
+        table_result.add_row(vec![
+            Cell::new(name),
+            Cell::new(format!("{:+.4}", max - result.nominal)).fg(Color::Red),
+            Cell::new(format!("{:+.4}", min - result.nominal)).fg(Color::Cyan),
+            Cell::new(format!("{:+.4}", result.mean - result.nominal)).fg(Color::Green),
+            Cell::new(format!("{:.4}", max - min)).fg(Color::Blue),
+        ]);
+    }
+    println!("{table_result}");
+}
+
+fn print_inverse(chain_query: &ChainQuery, solution: &ChainSolution) {
+    let (links, closing, method, calculation, adjusting) = match chain_query {
+        ChainQuery::Inverse {
+            links,
+            closing,
+            method,
+            calculation,
+            adjusting,
+            ..
+        } => (links, closing, method, calculation, *adjusting),
+        ChainQuery::Direct { .. } => return,
+    };
+    let mut table_links = Table::new();
+    table_links
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("звено"),
+            Cell::new("ξ"),
+            Cell::new("обозначение"),
+            Cell::new("ES").fg(Color::Red),
+            Cell::new("EI").fg(Color::Cyan),
+            Cell::new("T").fg(Color::Blue),
+        ]);
+    for (index, (link, tolerance)) in links.iter().zip(&solution.links).enumerate() {
+        let designation = match tolerance.grade {
+            Some(grade) => Cell::new(format!(
+                "{}{}{}",
+                link.nominal.normalize(),
+                link.position.field(),
+                grade
+            ))
+            .fg(Color::Green),
+            None => Cell::new(format!(
+                "{} {:+} {:+} (увязочное)",
+                link.nominal.normalize(),
+                tolerance.upper,
+                tolerance.lower
+            ))
+            .fg(Color::Yellow),
+        };
+        table_links.add_row(vec![
+            Cell::new(format!("A{}", index + 1)),
+            Cell::new(if link.increasing { "+1" } else { "-1" }),
+            designation,
+            Cell::new(format!("{:+}", tolerance.upper.normalize())).fg(Color::Red),
+            Cell::new(format!("{:+}", tolerance.lower.normalize())).fg(Color::Cyan),
+            Cell::new(tolerance.tolerance().normalize()).fg(Color::Blue),
+        ]);
+    }
+    println!("{table_links}");
+    let (nominal, upper, lower) = closing;
+    println!(
+        "AΔ = {} {:+} {:+}, TΔ = {}; {}, {}",
+        nominal,
+        upper,
+        lower,
+        (upper - lower).normalize(),
+        match method {
+            ChainMethod::EqualTolerance =>
+                format!("способ равных допусков: Tср = {:.4} мм", solution.target),
+            ChainMethod::EqualPrecision => format!(
+                "способ одного квалитета: a = {:.1} ед. допуска",
+                solution.target
+            ),
+        },
+        match calculation {
+            Calculation::MaxMin => "расчёт на максимум-минимум".to_string(),
+            Calculation::Probabilistic(t) => format!("вероятностный расчёт, t = {:.2}", t),
+        }
+    );
+    println!(
+        "Увязочное звено A{} назначено с нестандартными отклонениями, замыкающими цепь",
+        adjusting + 1
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, epsilon: f64) {
+        assert!(
+            (actual - expected).abs() < epsilon,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    fn link(increasing: bool, nominal: Decimal, position: FieldPosition) -> ChainLink {
+        ChainLink {
+            increasing,
+            nominal,
+            position,
+        }
+    }
+
+    /// Допуски IT по ГОСТ 25346 для интервалов 30-50 и 80-120 мм, квалитеты 5-12.
+    fn it(nominal: Decimal, grade: u8) -> Result<Option<Decimal>, String> {
+        let values = if nominal <= dec!(50) {
+            [11, 16, 25, 39, 62, 100, 160, 250]
+        } else {
+            [15, 22, 35, 54, 87, 140, 220, 350]
+        };
+        Ok(Some(match grade {
+            5..=12 => Decimal::from(values[grade as usize - 5]) / dec!(1000),
+            1..=4 => dec!(0.001),
+            _ => dec!(1),
+        }))
+    }
+
+    #[test]
+    fn test_parse_chain_input() {
+        match parse_chain_input("chain +60h9 -25±0.05 risk=1").unwrap() {
+            ChainQuery::Direct {
+                contributors, t, ..
+            } => {
+                assert_eq!(contributors.len(), 2);
+                assert_close(t, 2.5758, 1e-4);
+            }
+            ChainQuery::Inverse { .. } => panic!("ожидалась прямая задача"),
+        }
+        assert_eq!(
+            parse_chain_input("chain +100H -40h -58 closing=2+0.6-0,2 calc=prob").unwrap(),
+            ChainQuery::Inverse {
+                links: vec![
+                    link(true, dec!(100), FieldPosition::Hole),
+                    link(false, dec!(40), FieldPosition::Shaft),
+                    link(false, dec!(58), FieldPosition::Symmetric),
+                ],
+                closing: (dec!(2), dec!(0.6), dec!(-0.2)),
+                method: ChainMethod::EqualPrecision,
+                calculation: Calculation::Probabilistic(normal_quantile(1.0 - 0.27 / 200.0)),
+                distribution: Distribution::Normal(3.0),
+                adjusting: 2,
+            }
+        );
+        assert_eq!(parse_chain_input("chain +60h9 calc=prob"), None);
+        assert_eq!(
+            parse_chain_input("chain +100 -98 closing=2±0.1 adjust=3"),
+            None
+        );
+        assert_eq!(parse_chain_input("chain +100f -98 closing=2±0.1"), None);
+        assert_eq!(parse_chain_input("chain closing=2±0.1"), None);
+    }
+
+    #[test]
+    fn test_tolerance_units() {
+        assert_eq!(tolerance_unit(40.0), 1.56);
+        assert_eq!(tolerance_unit(3.0), 0.55);
+        assert_close(tolerance_unit(1000.0), 6.1, 1e-12);
+        assert_eq!(precision_grade(70.0), Some(10));
+        assert_eq!(precision_grade(64.0), Some(10));
+        assert_eq!(precision_grade(6.0), None);
+    }
+
+    #[test]
+    fn test_calculation() {
+        let normal = Distribution::Normal(3.0);
+        assert_close(
+            Calculation::MaxMin.closing_tolerance(&[0.1, 0.2], normal),
+            0.3,
+            1e-12,
+        );
+        let probabilistic = Calculation::Probabilistic(3.0);
+        assert_close(
+            probabilistic.closing_tolerance(&[0.3, 0.4], normal),
+            0.5,
+            1e-12,
+        );
+        assert_close(
+            probabilistic
+                .remaining_tolerance(0.5, &[0.3], normal)
+                .unwrap(),
+            0.4,
+            1e-12,
+        );
+        assert_eq!(
+            Calculation::MaxMin.remaining_tolerance(0.3, &[0.2, 0.1], normal),
+            None
+        );
+        assert_close(
+            Calculation::MaxMin.scale(0.3, &[1.0, 1.0, 1.0], normal),
+            0.1,
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn test_solve_inverse() {
+        let links = [
+            link(true, dec!(100), FieldPosition::Hole),
+            link(false, dec!(40), FieldPosition::Shaft),
+            link(false, dec!(58), FieldPosition::Symmetric),
+        ];
+        assert_eq!(closing_nominal(&links), dec!(2));
+        let normal = Distribution::Normal(3.0);
+        let solution = solve_inverse(
+            &links,
+            (dec!(0.6), dec!(-0.2)),
+            ChainMethod::EqualPrecision,
+            Calculation::MaxMin,
+            normal,
+            2,
+            it,
+        )
+        .unwrap()
+        .unwrap();
+        assert_close(solution.target, 800.0 / (2.17 + 1.56 + 1.86), 1e-9);
+        // a = 143 единицы допуска - квалитет 11.
+        assert_eq!(
+            solution.links[0],
+            LinkTolerance {
+                grade: Some(11),
+                upper: dec!(0.22),
+                lower: dec!(0),
+            }
+        );
+        assert_eq!(
+            solution.links[1],
+            LinkTolerance {
+                grade: Some(11),
+                upper: dec!(0),
+                lower: dec!(-0.16),
+            }
+        );
+        // TΔ = 0.8 = 0.22 + 0.16 + T3; EcΔ = 0.2 = 0.11 - (-0.08) - Ec3.
+        assert_eq!(
+            solution.links[2],
+            LinkTolerance {
+                grade: None,
+                upper: dec!(0.2),
+                lower: dec!(-0.22),
+            }
+        );
+
+        let solution = solve_inverse(
+            &links,
+            (dec!(0.1), dec!(-0.1)),
+            ChainMethod::EqualTolerance,
+            Calculation::MaxMin,
+            normal,
+            0,
+            it,
+        )
+        .unwrap()
+        .unwrap();
+        assert_close(solution.target, 0.2 / 3.0, 1e-12);
+        assert_eq!(solution.links[1].grade, Some(9));
+        assert_eq!(solution.links[2].grade, Some(8));
+        assert_eq!(solution.links[0].tolerance(), dec!(0.084));
+
+        let solution = solve_inverse(
+            &links,
+            (dec!(0.01), dec!(0)),
+            ChainMethod::EqualPrecision,
+            Calculation::MaxMin,
+            normal,
+            2,
+            it,
+        );
+        assert!(solution.is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use alloc::string::String;
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use regex::Regex;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::{
+    calc_average_tol, calc_sizes_with_tols, replace_comma_with_dot, search_tols_decimal,
+    ACCURACY_PATTERN, SIZE_PATTERN,
+};
+
+/// Отклонения отверстия и вала: ((ES, EI), (es, ei)).
+pub type FitTols = ((Decimal, Decimal), (Decimal, Decimal));
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FitQuery {
+    pub size: String,
+    pub hole_field: String,
+    pub hole_accuracy: String,
+    pub shaft_field: String,
+    pub shaft_accuracy: String,
+}
+
+impl FitQuery {
+    pub fn hole_class(&self) -> String {
+        format!("{}{}", self.hole_field, self.hole_accuracy)
+    }
+
+    pub fn shaft_class(&self) -> String {
+        format!("{}{}", self.shaft_field, self.shaft_accuracy)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FitType {
+    Clearance,
+    Transition,
+    Interference,
+}
+
+impl FitType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FitType::Clearance => "с зазором",
+            FitType::Transition => "переходная",
+            FitType::Interference => "с натягом",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            FitType::Clearance => Color::Green,
+            FitType::Transition => Color::Yellow,
+            FitType::Interference => Color::Red,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FitBasis {
+    Hole,
+    Shaft,
+    Combined,
+}
+
+impl FitBasis {
+    pub fn of(hole_field: &str, shaft_field: &str) -> Self {
+        if hole_field == "H" {
+            FitBasis::Hole
+        } else if shaft_field == "h" {
+            FitBasis::Shaft
+        } else {
+            FitBasis::Combined
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FitBasis::Hole => "система отверстия",
+            FitBasis::Shaft => "система вала",
+            FitBasis::Combined => "комбинированная",
+        }
+    }
+}
+
+/// Предельные характеристики посадки. Натяг хранится как отрицательный зазор.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FitLimits {
+    pub max_clearance: Decimal,
+    pub min_clearance: Decimal,
+    pub mean_clearance: Decimal,
+    pub fit_tolerance: Decimal,
+    pub fit_type: FitType,
+}
+
+pub fn parse_fit_input(input: &str) -> Option<FitQuery> {
+    let regex = Regex::new(&format!(
+        r"^(?P<size>{0})(?P<hole_field>[A-Z]+)(?P<hole_accuracy>{1})/(?P<shaft_field>[a-z]+)(?P<shaft_accuracy>{1})$",
+        SIZE_PATTERN, ACCURACY_PATTERN
+    ))
+    .expect("Ошибка обработки RegEx");
+    let captures = regex.captures(input)?;
+    Some(FitQuery {
+        size: replace_comma_with_dot(&captures["size"]),
+        hole_field: captures["hole_field"].to_string(),
+        hole_accuracy: captures["hole_accuracy"].to_string(),
+        shaft_field: captures["shaft_field"].to_string(),
+        shaft_accuracy: captures["shaft_accuracy"].to_string(),
+    })
+}
+
+pub fn calc_fit(hole_tols: (Decimal, Decimal), shaft_tols: (Decimal, Decimal)) -> FitLimits {
+    let max_clearance = hole_tols.0 - shaft_tols.1;
+    let min_clearance = hole_tols.1 - shaft_tols.0;
+    let fit_type = if min_clearance >= Decimal::ZERO {
+        FitType::Clearance
+    } else if max_clearance <= Decimal::ZERO {
+        FitType::Interference
+    } else {
+        FitType::Transition
+    };
+    FitLimits {
+        max_clearance: max_clearance.normalize(),
+        min_clearance: min_clearance.normalize(),
+        mean_clearance: ((max_clearance + min_clearance) * dec!(0.5)).normalize(),
+        fit_tolerance: (max_clearance - min_clearance).normalize(),
+        fit_type,
+    }
+}
+
+pub fn search_fit(fit_query: &FitQuery) -> Result<Option<FitTols>, String> {
+    let hole_tols = search_tols_decimal(
+        &fit_query.size,
+        &fit_query.hole_field,
+        &fit_query.hole_accuracy,
+    )
+    .map_err(|e| e.to_string())?;
+    let shaft_tols = search_tols_decimal(
+        &fit_query.size,
+        &fit_query.shaft_field,
+        &fit_query.shaft_accuracy,
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(hole_tols.zip(shaft_tols))
+}
+
+pub fn handle_fit_search(fit_query: &FitQuery) {
+    match search_fit(fit_query) {
+        Ok(Some((hole_tols, shaft_tols))) => {
+            let fit_limits = calc_fit(hole_tols, shaft_tols);
+            print_fit_result(fit_query, hole_tols, shaft_tols, &fit_limits)
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn format_feature_row(size: &Decimal, class: String, tols: (Decimal, Decimal)) -> Vec<Cell> {
+    let average_tol = calc_average_tol(&tols.0, &tols.1);
+    let sizes = calc_sizes_with_tols((*size, tols.0, tols.1), average_tol);
+    vec![
+        Cell::new(class),
+        Cell::new(sizes.1).fg(Color::Red),
+        Cell::new(sizes.2).fg(Color::Cyan),
+        Cell::new(tols.0.normalize()).fg(Color::Magenta),
+        Cell::new(tols.1.normalize()).fg(Color::Yellow),
+    ]
+}
+
+fn print_fit_result(
+    fit_query: &FitQuery,
+    hole_tols: (Decimal, Decimal),
+    shaft_tols: (Decimal, Decimal),
+    fit_limits: &FitLimits,
+) {
+    let size: Decimal = fit_query
+        .size
+        .parse()
+        .expect("Ошибка преобразования значения из String в Decimal");
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            format!(
+                "{}{}/{}",
+                fit_query.size,
+                fit_query.hole_class(),
+                fit_query.shaft_class()
+            )
+            .as_str(),
+            "верхний размер",
+            "нижний размер",
+            "верхнее откл.",
+            "нижнее откл.",
+        ])
+        .add_row(format_feature_row(&size, fit_query.hole_class(), hole_tols))
+        .add_row(format_feature_row(
+            &size,
+            fit_query.shaft_class(),
+            shaft_tols,
+        ));
+    println!("{table_result}");
+
+    let color = fit_limits.fit_type.color();
+    let mut table_fit = Table::new();
+    table_fit
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .add_row(vec![
+            Cell::new("посадка"),
+            Cell::new(fit_limits.fit_type.name()).fg(color),
+        ])
+        .add_row(vec![
+            Cell::new("наибольший зазор (Smax)"),
+            Cell::new(fit_limits.max_clearance).fg(color),
+        ])
+        .add_row(vec![
+            Cell::new("наименьший зазор (Smin)"),
+            Cell::new(fit_limits.min_clearance).fg(color),
+        ])
+        .add_row(vec![
+            Cell::new("средний зазор (Sm)"),
+            Cell::new(fit_limits.mean_clearance).fg(color),
+        ])
+        .add_row(vec![
+            Cell::new("допуск посадки (TS)"),
+            Cell::new(fit_limits.fit_tolerance).fg(color),
+        ]);
+    if fit_limits.fit_type != FitType::Clearance {
+        table_fit
+            .add_row(vec![
+                Cell::new("наибольший натяг (Nmax)"),
+                Cell::new(-fit_limits.min_clearance).fg(color),
+            ])
+            .add_row(vec![
+                Cell::new("наименьший натяг (Nmin)"),
+                Cell::new(-fit_limits.max_clearance).fg(color),
+            ]);
+    }
+    println!("{table_fit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn fit_query(size: &str, hole: (&str, &str), shaft: (&str, &str)) -> FitQuery {
+        FitQuery {
+            size: size.to_string(),
+            hole_field: hole.0.to_string(),
+            hole_accuracy: hole.1.to_string(),
+            shaft_field: shaft.0.to_string(),
+            shaft_accuracy: shaft.1.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_fit_input() {
+        assert_eq!(
+            parse_fit_input("50H7/g6").unwrap(),
+            fit_query("50", ("H", "7"), ("g", "6"))
+        );
+        assert_eq!(
+            parse_fit_input("12,5JS11/cd10").unwrap(),
+            fit_query("12.5", ("JS", "11"), ("cd", "10"))
+        );
+        assert_eq!(
+            parse_fit_input("3150H18/h18").unwrap(),
+            fit_query("3150", ("H", "18"), ("h", "18"))
+        );
+        assert_eq!(parse_fit_input("50H7"), None);
+        assert_eq!(parse_fit_input("50h7/g6"), None);
+        assert_eq!(parse_fit_input("50H7/G6"), None);
+        assert_eq!(parse_fit_input("50H19/g6"), None);
+        assert_eq!(parse_fit_input("3151H7/g6"), None);
+    }
+
+    #[test]
+    fn test_fit_basis() {
+        assert_eq!(FitBasis::of("H", "g"), FitBasis::Hole);
+        assert_eq!(FitBasis::of("H", "h"), FitBasis::Hole);
+        assert_eq!(FitBasis::of("G", "h"), FitBasis::Shaft);
+        assert_eq!(FitBasis::of("F", "g"), FitBasis::Combined);
+    }
+
+    #[test]
+    fn test_calc_fit() {
+        let fit = calc_fit((dec!(0.025), dec!(0)), (dec!(-0.009), dec!(-0.025)));
+        assert_eq!(fit.max_clearance, dec!(0.05));
+        assert_eq!(fit.min_clearance, dec!(0.009));
+        assert_eq!(fit.mean_clearance, dec!(0.0295));
+        assert_eq!(fit.fit_tolerance, dec!(0.041));
+        assert_eq!(fit.fit_type, FitType::Clearance);
+
+        let fit = calc_fit((dec!(0.025), dec!(0)), (dec!(0.018), dec!(0.002)));
+        assert_eq!(fit.max_clearance, dec!(0.023));
+        assert_eq!(fit.min_clearance, dec!(-0.018));
+        assert_eq!(fit.fit_type, FitType::Transition);
+
+        let fit = calc_fit((dec!(0.03), dec!(0)), (dec!(0.072), dec!(0.053)));
+        assert_eq!(fit.max_clearance, dec!(-0.023));
+        assert_eq!(fit.min_clearance, dec!(-0.072));
+        assert_eq!(fit.fit_tolerance, dec!(0.049));
+        assert_eq!(fit.fit_type, FitType::Interference);
+
+        let fit = calc_fit((dec!(0.025), dec!(0)), (dec!(0), dec!(-0.016)));
+        assert_eq!(fit.min_clearance, dec!(0));
+        assert_eq!(fit.fit_type, FitType::Clearance);
+    }
+}
@@ -0,0 +1,210 @@
+use alloc::string::String;
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, ContentArrangement, Table};
+
+use crate::fit::{calc_fit, parse_fit_input, search_fit, FitBasis, FitLimits, FitQuery, FitTols};
+
+const DELTA_FIELDS_TO_IT8: [&str; 3] = ["K", "M", "N"];
+const DELTA_FIELDS_TO_IT7: [&str; 12] = [
+    "P", "R", "S", "T", "U", "V", "X", "Y", "Z", "ZA", "ZB", "ZC",
+];
+
+pub fn parse_conversion_input(input: &str) -> Option<FitQuery> {
+    parse_fit_input(input.strip_prefix("convert")?.trim_start())
+}
+
+/// Одноимённая посадка в другой системе: H7/g6 <-> G7/h6.
+/// Квалитеты остаются за отверстием и валом, меняются только основные отклонения.
+pub fn equivalent_fit(fit_query: &FitQuery) -> Option<FitQuery> {
+    match FitBasis::of(&fit_query.hole_field, &fit_query.shaft_field) {
+        FitBasis::Hole => Some(FitQuery {
+            hole_field: fit_query.shaft_field.to_uppercase(),
+            shaft_field: "h".to_string(),
+            ..fit_query.clone()
+        }),
+        FitBasis::Shaft => Some(FitQuery {
+            hole_field: "H".to_string(),
+            shaft_field: fit_query.hole_field.to_lowercase(),
+            ..fit_query.clone()
+        }),
+        FitBasis::Combined => None,
+    }
+}
+
+/// Для отверстий K, M, N до IT8 и P..ZC до IT7 ISO 286 задаёт ES = -ei + Δ,
+/// где Δ = IT(n) - IT(n-1). Такие посадки совпадают с одноимёнными в системе
+/// отверстия только при квалитете отверстия на единицу грубее квалитета вала.
+pub fn delta_rule_applies(hole_field: &str, hole_accuracy: &str) -> bool {
+    let grade: u8 = match hole_accuracy.parse() {
+        Ok(grade) => grade,
+        Err(_) => return false,
+    };
+    (DELTA_FIELDS_TO_IT8.contains(&hole_field) && grade <= 8)
+        || (DELTA_FIELDS_TO_IT7.contains(&hole_field) && grade <= 7)
+}
+
+pub fn handle_conversion(fit_query: &FitQuery) {
+    let equivalent_query = match equivalent_fit(fit_query) {
+        Some(equivalent_query) => equivalent_query,
+        None => {
+            println!("Посадка не относится ни к системе отверстия, ни к системе вала");
+            return;
+        }
+    };
+    match search_fit(fit_query).and_then(|tols| Ok(tols.zip(search_fit(&equivalent_query)?))) {
+        Ok(Some((tols, equivalent_tols))) => {
+            let fit_limits = calc_fit(tols.0, tols.1);
+            let equivalent_limits = calc_fit(equivalent_tols.0, equivalent_tols.1);
+            print_conversion(
+                (fit_query, tols, &fit_limits),
+                (&equivalent_query, equivalent_tols, &equivalent_limits),
+            );
+            print_equivalence_note(
+                fit_query,
+                &equivalent_query,
+                &fit_limits,
+                &equivalent_limits,
+            )
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_conversion(
+    fit: (&FitQuery, FitTols, &FitLimits),
+    equivalent: (&FitQuery, FitTols, &FitLimits),
+) {
+    let title = |fit_query: &FitQuery| {
+        format!(
+            "{}{}/{} ({})",
+            fit_query.size,
+            fit_query.hole_class(),
+            fit_query.shaft_class(),
+            FitBasis::of(&fit_query.hole_field, &fit_query.shaft_field).name()
+        )
+    };
+    let row = |name: &str, value: fn(FitTols, &FitLimits) -> String| {
+        vec![
+            Cell::new(name),
+            Cell::new(value(fit.1, fit.2)).fg(fit.2.fit_type.color()),
+            Cell::new(value(equivalent.1, equivalent.2)).fg(equivalent.2.fit_type.color()),
+        ]
+    };
+
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![String::new(), title(fit.0), title(equivalent.0)])
+        .add_row(row("отверстие ES / EI", |tols, _| {
+            format!("{} / {}", tols.0 .0, tols.0 .1)
+        }))
+        .add_row(row("вал es / ei", |tols, _| {
+            format!("{} / {}", tols.1 .0, tols.1 .1)
+        }))
+        .add_row(row("Smax", |_, limits| limits.max_clearance.to_string()))
+        .add_row(row("Smin", |_, limits| limits.min_clearance.to_string()))
+        .add_row(row("тип", |_, limits| {
+            limits.fit_type.name().to_string()
+        }));
+    println!("{table_result}")
+}
+
+fn print_equivalence_note(
+    fit_query: &FitQuery,
+    equivalent_query: &FitQuery,
+    fit_limits: &FitLimits,
+    equivalent_limits: &FitLimits,
+) {
+    if fit_limits.max_clearance == equivalent_limits.max_clearance
+        && fit_limits.min_clearance == equivalent_limits.min_clearance
+    {
+        println!("Посадки эквивалентны: предельные зазоры совпадают");
+        return;
+    }
+    println!(
+        "Внимание: посадки не эквивалентны (ΔSmax = {}, ΔSmin = {})",
+        (equivalent_limits.max_clearance - fit_limits.max_clearance).normalize(),
+        (equivalent_limits.min_clearance - fit_limits.min_clearance).normalize()
+    );
+    let shaft_basis_query = match FitBasis::of(&fit_query.hole_field, &fit_query.shaft_field) {
+        FitBasis::Shaft => fit_query,
+        _ => equivalent_query,
+    };
+    if delta_rule_applies(
+        &shaft_basis_query.hole_field,
+        &shaft_basis_query.hole_accuracy,
+    ) {
+        println!(
+            "Причина: для отверстия {} действует правило Δ ISO 286 (ES = -ei + Δ)",
+            shaft_basis_query.hole_class()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fit_query(size: &str, hole: (&str, &str), shaft: (&str, &str)) -> FitQuery {
+        FitQuery {
+            size: size.to_string(),
+            hole_field: hole.0.to_string(),
+            hole_accuracy: hole.1.to_string(),
+            shaft_field: shaft.0.to_string(),
+            shaft_accuracy: shaft.1.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_conversion_input() {
+        assert_eq!(
+            parse_conversion_input("convert 50H7/g6").unwrap(),
+            fit_query("50", ("H", "7"), ("g", "6"))
+        );
+        assert_eq!(
+            parse_conversion_input("convert  50,5G7/h6").unwrap(),
+            fit_query("50.5", ("G", "7"), ("h", "6"))
+        );
+        assert_eq!(parse_conversion_input("convert 50H7"), None);
+        assert_eq!(parse_conversion_input("50H7/g6"), None);
+    }
+
+    #[test]
+    fn test_equivalent_fit() {
+        assert_eq!(
+            equivalent_fit(&fit_query("50", ("H", "7"), ("g", "6"))).unwrap(),
+            fit_query("50", ("G", "7"), ("h", "6"))
+        );
+        assert_eq!(
+            equivalent_fit(&fit_query("50", ("S", "7"), ("h", "6"))).unwrap(),
+            fit_query("50", ("H", "7"), ("s", "6"))
+        );
+        assert_eq!(
+            equivalent_fit(&fit_query("50", ("H", "8"), ("cd", "9"))).unwrap(),
+            fit_query("50", ("CD", "8"), ("h", "9"))
+        );
+        assert_eq!(
+            equivalent_fit(&fit_query("50", ("H", "7"), ("h", "6"))).unwrap(),
+            fit_query("50", ("H", "7"), ("h", "6"))
+        );
+        assert_eq!(
+            equivalent_fit(&fit_query("50", ("F", "8"), ("g", "7"))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_delta_rule_applies() {
+        assert!(delta_rule_applies("K", "7"));
+        assert!(delta_rule_applies("N", "8"));
+        assert!(!delta_rule_applies("N", "9"));
+        assert!(delta_rule_applies("S", "7"));
+        assert!(!delta_rule_applies("S", "8"));
+        assert!(delta_rule_applies("ZC", "6"));
+        assert!(!delta_rule_applies("G", "7"));
+        assert!(!delta_rule_applies("JS", "7"));
+    }
+}
@@ -0,0 +1,242 @@
+use alloc::string::String;
+use core::cmp::Reverse;
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, ContentArrangement, Table};
+use regex::Regex;
+use rust_decimal::Decimal;
+
+use crate::fit::{calc_fit, FitBasis, FitLimits};
+use crate::params::Params;
+use crate::{
+    replace_comma_with_dot, search_all_in_tables, split_class, NUMBER_PATTERN, POOL, SIZE_PATTERN,
+    TABLES_HOLES, TABLES_SHAFTS,
+};
+
+const SHOWN_FITS: usize = 10;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FitWindowQuery {
+    pub size: String,
+    pub min_clearance: Decimal,
+    pub max_clearance: Decimal,
+    /// Включать комбинированные посадки, кроме систем отверстия и вала.
+    pub combined: bool,
+    /// Сколько посадок показывать.
+    pub limit: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FitCandidate {
+    pub hole_class: String,
+    pub shaft_class: String,
+    pub hole_grade: u8,
+    pub shaft_grade: u8,
+    pub basis: FitBasis,
+    pub fit_limits: FitLimits,
+}
+
+pub fn parse_fit_window_input(input: &str) -> Option<FitWindowQuery> {
+    let regex = Regex::new(&format!(
+        r"^find\s+(?P<size>{0})\s+(?P<min>{1})\s+(?P<max>{1})(?P<params>(\s+\S+)*)$",
+        SIZE_PATTERN, NUMBER_PATTERN
+    ))
+    .expect("Ошибка обработки RegEx");
+    let captures = regex.captures(input)?;
+    let first: Decimal = replace_comma_with_dot(&captures["min"]).parse().ok()?;
+    let second: Decimal = replace_comma_with_dot(&captures["max"]).parse().ok()?;
+    let params = Params::parse(captures["params"].split_whitespace(), &["basis", "limit"])?;
+    let combined = match params.text("basis") {
+        None | Some("all") => true,
+        Some("main") => false,
+        Some(_) => return None,
+    };
+    let limit = params.number_or("limit", SHOWN_FITS as f64)?;
+    if limit.fract() != 0.0 || limit < 1.0 {
+        return None;
+    }
+    Some(FitWindowQuery {
+        size: replace_comma_with_dot(&captures["size"]),
+        min_clearance: first.min(second),
+        max_clearance: first.max(second),
+        combined,
+        limit: limit as usize,
+    })
+}
+
+/// Все сочетания полей, предельные зазоры которых лежат внутри окна; без `combined`
+/// только посадки в системе отверстия и в системе вала.
+/// Сначала идут посадки с более грубыми квалитетами (дешевле в изготовлении),
+/// при равных квалитетах - система отверстия, затем система вала.
+pub fn find_fits(
+    window_query: &FitWindowQuery,
+    holes: &[(String, Decimal, Decimal)],
+    shafts: &[(String, Decimal, Decimal)],
+) -> Vec<FitCandidate> {
+    let mut candidates: Vec<FitCandidate> = holes
+        .iter()
+        .flat_map(|hole| shafts.iter().map(move |shaft| (hole, shaft)))
+        .filter_map(
+            |((hole_class, es, ei), (shaft_class, shaft_es, shaft_ei))| {
+                let fit_limits = calc_fit((*es, *ei), (*shaft_es, *shaft_ei));
+                if fit_limits.min_clearance < window_query.min_clearance
+                    || fit_limits.max_clearance > window_query.max_clearance
+                {
+                    return None;
+                }
+                let (hole_field, hole_grade) = split_class(hole_class);
+                let (shaft_field, shaft_grade) = split_class(shaft_class);
+                let basis = FitBasis::of(&hole_field, &shaft_field);
+                if basis == FitBasis::Combined && !window_query.combined {
+                    return None;
+                }
+                Some(FitCandidate {
+                    hole_class: hole_class.clone(),
+                    shaft_class: shaft_class.clone(),
+                    hole_grade: hole_grade.parse().ok()?,
+                    shaft_grade: shaft_grade.parse().ok()?,
+                    basis,
+                    fit_limits,
+                })
+            },
+        )
+        .collect();
+    candidates.sort_by_key(|candidate| {
+        (
+            Reverse(candidate.hole_grade + candidate.shaft_grade),
+            Reverse(candidate.hole_grade.min(candidate.shaft_grade)),
+            candidate.basis as u8,
+            candidate.hole_class.clone(),
+            candidate.shaft_class.clone(),
+        )
+    });
+    candidates
+}
+
+pub fn handle_fit_window_search(window_query: &FitWindowQuery) {
+    let holes = search_all_in_tables(&POOL, &window_query.size, &TABLES_HOLES);
+    let shafts = search_all_in_tables(&POOL, &window_query.size, &TABLES_SHAFTS);
+    match holes.and_then(|holes| shafts.map(|shafts| (holes, shafts))) {
+        Ok((holes, shafts)) => {
+            let candidates = find_fits(window_query, &holes, &shafts);
+            if candidates.is_empty() {
+                println!("Ничего не найдено для заданных параметров");
+            } else {
+                print_fit_candidates(window_query, &candidates)
+            }
+        }
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_fit_candidates(window_query: &FitWindowQuery, candidates: &[FitCandidate]) {
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            format!(
+                "{}: {}..{}",
+                window_query.size, window_query.min_clearance, window_query.max_clearance
+            )
+            .as_str(),
+            "система",
+            "Smax",
+            "Smin",
+            "тип",
+        ]);
+    for candidate in candidates.iter().take(window_query.limit) {
+        let color = candidate.fit_limits.fit_type.color();
+        table_result.add_row(vec![
+            Cell::new(format!(
+                "{}/{}",
+                candidate.hole_class, candidate.shaft_class
+            )),
+            Cell::new(candidate.basis.name()),
+            Cell::new(candidate.fit_limits.max_clearance).fg(color),
+            Cell::new(candidate.fit_limits.min_clearance).fg(color),
+            Cell::new(candidate.fit_limits.fit_type.name()).fg(color),
+        ]);
+    }
+    println!("{table_result}");
+    if candidates.len() > window_query.limit {
+        println!(
+            "Найдено посадок: {}, показаны первые {} (limit=)",
+            candidates.len(),
+            window_query.limit
+        );
+    } else {
+        println!("Найдено посадок: {}", candidates.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_fit_window_input() {
+        assert_eq!(
+            parse_fit_window_input("find 40 0.02 0.06").unwrap(),
+            FitWindowQuery {
+                size: "40".to_string(),
+                min_clearance: dec!(0.02),
+                max_clearance: dec!(0.06),
+                combined: true,
+                limit: 10,
+            }
+        );
+        assert_eq!(
+            parse_fit_window_input("find 12,5 -0,06 -0,02 basis=main limit=5").unwrap(),
+            FitWindowQuery {
+                size: "12.5".to_string(),
+                min_clearance: dec!(-0.06),
+                max_clearance: dec!(-0.02),
+                combined: false,
+                limit: 5,
+            }
+        );
+        assert_eq!(
+            parse_fit_window_input("find 40 0.06 0.02")
+                .unwrap()
+                .min_clearance,
+            dec!(0.02)
+        );
+        assert_eq!(parse_fit_window_input("find 40 0.02"), None);
+        assert_eq!(parse_fit_window_input("40 0.02 0.06"), None);
+        assert_eq!(parse_fit_window_input("find 40 0.02 0.06 limit=0"), None);
+        assert_eq!(parse_fit_window_input("find 40 0.02 0.06 basis=hole"), None);
+    }
+
+    #[test]
+    fn test_find_fits() {
+        let mut window_query = FitWindowQuery {
+            size: "40".to_string(),
+            min_clearance: dec!(0.005),
+            max_clearance: dec!(0.06),
+            combined: true,
+            limit: 10,
+        };
+        let holes = vec![
+            ("H7".to_string(), dec!(0.025), dec!(0)),
+            ("G7".to_string(), dec!(0.034), dec!(0.009)),
+            ("H8".to_string(), dec!(0.039), dec!(0)),
+        ];
+        let shafts = vec![
+            ("g6".to_string(), dec!(-0.009), dec!(-0.025)),
+            ("h6".to_string(), dec!(0), dec!(-0.016)),
+            ("f7".to_string(), dec!(-0.025), dec!(-0.05)),
+        ];
+        let fits = |window_query: &FitWindowQuery| -> Vec<String> {
+            find_fits(window_query, &holes, &shafts)
+                .into_iter()
+                .map(|candidate| format!("{}/{}", candidate.hole_class, candidate.shaft_class))
+                .collect()
+        };
+        assert_eq!(fits(&window_query), vec!["H7/g6", "G7/h6", "G7/g6"]);
+        window_query.combined = false;
+        assert_eq!(fits(&window_query), vec!["H7/g6", "G7/h6"]);
+    }
+}
@@ -0,0 +1,172 @@
+use alloc::string::String;
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, ContentArrangement, Table};
+use regex::Regex;
+use rust_decimal::Decimal;
+
+use crate::fit::calc_fit;
+use crate::{
+    replace_comma_with_dot, search_all_in_tables, split_class, ACCURACY_PATTERN, POOL,
+    SIZE_PATTERN, TABLES_HOLES, TABLES_SHAFTS,
+};
+
+/// Пустой список полей означает все поля из таблиц.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MatrixQuery {
+    pub size: String,
+    pub grades: (u8, u8),
+    pub hole_fields: Vec<String>,
+    pub shaft_fields: Vec<String>,
+}
+
+pub fn parse_matrix_input(input: &str) -> Option<MatrixQuery> {
+    let regex = Regex::new(&format!(
+        r"^matrix\s+(?P<size>{0})\s+(?P<from>{1})(-(?P<to>{1}))?(\s+(?P<holes>[A-Z]+(,[A-Z]+)*))?(\s+(?P<shafts>[a-z]+(,[a-z]+)*))?$",
+        SIZE_PATTERN, ACCURACY_PATTERN
+    ))
+    .expect("Ошибка обработки RegEx");
+    let captures = regex.captures(input)?;
+    let from: u8 = captures["from"].parse().ok()?;
+    let to: u8 = match captures.name("to") {
+        Some(to) => to.as_str().parse().ok()?,
+        None => from,
+    };
+    let fields = |name: &str| -> Vec<String> {
+        captures
+            .name(name)
+            .map(|fields| fields.as_str().split(',').map(String::from).collect())
+            .unwrap_or_default()
+    };
+    Some(MatrixQuery {
+        size: replace_comma_with_dot(&captures["size"]),
+        grades: (from.min(to), from.max(to)),
+        hole_fields: fields("holes"),
+        shaft_fields: fields("shafts"),
+    })
+}
+
+pub fn filter_classes(
+    classes: Vec<(String, Decimal, Decimal)>,
+    grades: (u8, u8),
+    fields: &[String],
+) -> Vec<(String, Decimal, Decimal)> {
+    classes
+        .into_iter()
+        .filter(|(class, _, _)| {
+            let (field, accuracy) = split_class(class);
+            let in_grades = accuracy
+                .parse::<u8>()
+                .map(|grade| grade >= grades.0 && grade <= grades.1)
+                .unwrap_or(false);
+            in_grades && (fields.is_empty() || fields.contains(&field))
+        })
+        .collect()
+}
+
+pub fn handle_matrix(matrix_query: &MatrixQuery) {
+    let holes = search_all_in_tables(&POOL, &matrix_query.size, &TABLES_HOLES);
+    let shafts = search_all_in_tables(&POOL, &matrix_query.size, &TABLES_SHAFTS);
+    match holes.and_then(|holes| shafts.map(|shafts| (holes, shafts))) {
+        Ok((holes, shafts)) => {
+            let holes = filter_classes(holes, matrix_query.grades, &matrix_query.hole_fields);
+            let shafts = filter_classes(shafts, matrix_query.grades, &matrix_query.shaft_fields);
+            if holes.is_empty() || shafts.is_empty() {
+                println!("Ничего не найдено для заданных параметров");
+            } else {
+                print_matrix(&matrix_query.size, &holes, &shafts)
+            }
+        }
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_matrix(
+    size: &str,
+    holes: &[(String, Decimal, Decimal)],
+    shafts: &[(String, Decimal, Decimal)],
+) {
+    let mut header = vec![Cell::new(format!("{} мм", size))];
+    header.extend(shafts.iter().map(|(class, _, _)| Cell::new(class)));
+
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header);
+    for (hole_class, es, ei) in holes {
+        let mut row = vec![Cell::new(hole_class)];
+        row.extend(shafts.iter().map(|(_, shaft_es, shaft_ei)| {
+            let fit_limits = calc_fit((*es, *ei), (*shaft_es, *shaft_ei));
+            Cell::new(format!(
+                "{}\n{}",
+                fit_limits.max_clearance, fit_limits.min_clearance
+            ))
+            .fg(fit_limits.fit_type.color())
+        }));
+        table_result.add_row(row);
+    }
+    println!("{table_result}");
+    println!("В ячейках: Smax / Smin (натяг - отрицательный зазор)");
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_matrix_input() {
+        assert_eq!(
+            parse_matrix_input("matrix 40 6-8").unwrap(),
+            MatrixQuery {
+                size: "40".to_string(),
+                grades: (6, 8),
+                hole_fields: vec![],
+                shaft_fields: vec![],
+            }
+        );
+        assert_eq!(
+            parse_matrix_input("matrix 12,5 11-10 H,JS f,g,h").unwrap(),
+            MatrixQuery {
+                size: "12.5".to_string(),
+                grades: (10, 11),
+                hole_fields: vec!["H".to_string(), "JS".to_string()],
+                shaft_fields: vec!["f".to_string(), "g".to_string(), "h".to_string()],
+            }
+        );
+        assert_eq!(
+            parse_matrix_input("matrix 40 7 k,n").unwrap(),
+            MatrixQuery {
+                size: "40".to_string(),
+                grades: (7, 7),
+                hole_fields: vec![],
+                shaft_fields: vec!["k".to_string(), "n".to_string()],
+            }
+        );
+        assert_eq!(parse_matrix_input("matrix 40"), None);
+        assert_eq!(parse_matrix_input("matrix 40 6-19"), None);
+    }
+
+    #[test]
+    fn test_filter_classes() {
+        let classes = vec![
+            ("H6".to_string(), dec!(0.016), dec!(0)),
+            ("H7".to_string(), dec!(0.025), dec!(0)),
+            ("H12".to_string(), dec!(0.25), dec!(0)),
+            ("JS7".to_string(), dec!(0.0125), dec!(-0.0125)),
+        ];
+        let filtered: Vec<String> = filter_classes(classes.clone(), (7, 12), &[])
+            .into_iter()
+            .map(|(class, _, _)| class)
+            .collect();
+        assert_eq!(filtered, vec!["H7", "H12", "JS7"]);
+
+        let filtered: Vec<String> = filter_classes(classes, (6, 7), &["H".to_string()])
+            .into_iter()
+            .map(|(class, _, _)| class)
+            .collect();
+        assert_eq!(filtered, vec!["H6", "H7"]);
+    }
+}
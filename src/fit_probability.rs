@@ -0,0 +1,324 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use regex::Regex;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+
+use crate::calc_average_tol;
+use crate::fit::{calc_fit, parse_fit_input, search_fit, FitQuery, FitTols};
+use crate::statistics::{integrate, Distribution};
+
+const HISTOGRAM_BINS: usize = 10;
+const HISTOGRAM_WIDTH: usize = 40;
+const INTEGRATION_INTERVALS: usize = 2000;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProbabilityQuery {
+    pub fit_query: FitQuery,
+    pub hole_distribution: Distribution,
+    pub shaft_distribution: Distribution,
+}
+
+/// Распределение зазора S = D - d при независимых размерах отверстия и вала.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ClearanceDistribution {
+    pub hole: (f64, f64, Distribution),
+    pub shaft: (f64, f64, Distribution),
+}
+
+pub fn parse_probability_input(input: &str) -> Option<ProbabilityQuery> {
+    let regex = Regex::new(r"^prob\s+(?P<fit>\S+)(\s+(?P<hole>\S+))?(\s+(?P<shaft>\S+))?$")
+        .expect("Ошибка обработки RegEx");
+    let captures = regex.captures(input)?;
+    let hole_distribution = match captures.name("hole") {
+        Some(name) => Distribution::parse(name.as_str())?,
+        None => Distribution::Normal(3.0),
+    };
+    let shaft_distribution = match captures.name("shaft") {
+        Some(name) => Distribution::parse(name.as_str())?,
+        None => hole_distribution,
+    };
+    Some(ProbabilityQuery {
+        fit_query: parse_fit_input(&captures["fit"])?,
+        hole_distribution,
+        shaft_distribution,
+    })
+}
+
+impl ClearanceDistribution {
+    pub fn new(tols: FitTols, hole: Distribution, shaft: Distribution) -> Self {
+        let to_f64 = |value: Decimal| value.to_f64().unwrap_or_default();
+        ClearanceDistribution {
+            hole: (to_f64(tols.0 .1), to_f64(tols.0 .0), hole),
+            shaft: (to_f64(tols.1 .1), to_f64(tols.1 .0), shaft),
+        }
+    }
+
+    /// Смещение среднего зазора от разности середин полей при смещённых законах.
+    pub fn mean_shift(&self) -> f64 {
+        self.hole.2.mean_shift(self.hole.0, self.hole.1)
+            - self.shaft.2.mean_shift(self.shaft.0, self.shaft.1)
+    }
+
+    pub fn sigma(&self) -> f64 {
+        let hole_sigma = self.hole.2.sigma(self.hole.0, self.hole.1);
+        let shaft_sigma = self.shaft.2.sigma(self.shaft.0, self.shaft.1);
+        (hole_sigma * hole_sigma + shaft_sigma * shaft_sigma).sqrt()
+    }
+
+    /// P(S <= x) = ∫ f_вала(d) · F_отв(x + d) dd
+    pub fn cdf(&self, x: f64) -> f64 {
+        let (lower, upper, shaft) = self.shaft;
+        let (from, to) = shaft.support(lower, upper);
+        if to <= from {
+            return self.hole.2.cdf(x + lower, self.hole.0, self.hole.1);
+        }
+        integrate(
+            |d| shaft.pdf(d, lower, upper) * self.hole.2.cdf(x + d, self.hole.0, self.hole.1),
+            from,
+            to,
+            INTEGRATION_INTERVALS,
+        )
+        .clamp(0.0, 1.0)
+    }
+
+    pub fn interference_probability(&self) -> f64 {
+        self.cdf(0.0)
+    }
+
+    /// Вероятности попадания зазора в равные интервалы между Smin и Smax.
+    /// Хвосты за пределами Smin и Smax относятся к крайним интервалам.
+    pub fn histogram(
+        &self,
+        min_clearance: f64,
+        max_clearance: f64,
+        bins: usize,
+    ) -> Vec<(f64, f64, f64)> {
+        let step = (max_clearance - min_clearance) / bins as f64;
+        (0..bins)
+            .map(|bin| {
+                let from = min_clearance + step * bin as f64;
+                let to = from + step;
+                let lower_cdf = if bin == 0 { 0.0 } else { self.cdf(from) };
+                let upper_cdf = if bin == bins - 1 { 1.0 } else { self.cdf(to) };
+                (from, to, (upper_cdf - lower_cdf).max(0.0))
+            })
+            .collect()
+    }
+}
+
+pub fn handle_probability(probability_query: &ProbabilityQuery) {
+    match search_fit(&probability_query.fit_query) {
+        Ok(Some(tols)) => {
+            let distribution = ClearanceDistribution::new(
+                tols,
+                probability_query.hole_distribution,
+                probability_query.shaft_distribution,
+            );
+            print_probability(probability_query, tols, &distribution)
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_probability(
+    probability_query: &ProbabilityQuery,
+    tols: FitTols,
+    distribution: &ClearanceDistribution,
+) {
+    let fit_query = &probability_query.fit_query;
+    let fit_limits = calc_fit(tols.0, tols.1);
+    let mean_clearance = calc_average_tol(&tols.0 .0, &tols.0 .1)
+        - calc_average_tol(&tols.1 .0, &tols.1 .1)
+        + Decimal::from_f64(distribution.mean_shift())
+            .unwrap_or_default()
+            .round_dp(6);
+    let interference = distribution.interference_probability();
+
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            format!(
+                "{}{}/{}",
+                fit_query.size,
+                fit_query.hole_class(),
+                fit_query.shaft_class()
+            ),
+            fit_limits.fit_type.name().to_string(),
+        ])
+        .add_row(vec![
+            Cell::new("распределение отверстия"),
+            Cell::new(probability_query.hole_distribution.name()),
+        ])
+        .add_row(vec![
+            Cell::new("распределение вала"),
+            Cell::new(probability_query.shaft_distribution.name()),
+        ])
+        .add_row(vec![
+            Cell::new("вероятность зазора"),
+            Cell::new(format!("{:.2} %", (1.0 - interference) * 100.0)).fg(Color::Green),
+        ])
+        .add_row(vec![
+            Cell::new("вероятность натяга"),
+            Cell::new(format!("{:.2} %", interference * 100.0)).fg(Color::Red),
+        ])
+        .add_row(vec![
+            Cell::new("средний зазор"),
+            Cell::new(mean_clearance.normalize()).fg(Color::Blue),
+        ])
+        .add_row(vec![
+            Cell::new("σ зазора"),
+            Cell::new(format!("{:.6}", distribution.sigma())).fg(Color::Blue),
+        ]);
+    println!("{table_result}");
+
+    let min_clearance = fit_limits.min_clearance.to_f64().unwrap_or_default();
+    let max_clearance = fit_limits.max_clearance.to_f64().unwrap_or_default();
+    let histogram = distribution.histogram(min_clearance, max_clearance, HISTOGRAM_BINS);
+    let max_probability = histogram
+        .iter()
+        .map(|(_, _, probability)| *probability)
+        .fold(0.0, f64::max);
+    let mut table_histogram = Table::new();
+    table_histogram
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["зазор от", "до", "%", ""]);
+    for (from, to, probability) in histogram {
+        let color = if to <= 0.0 {
+            Color::Red
+        } else if from >= 0.0 {
+            Color::Green
+        } else {
+            Color::Yellow
+        };
+        let bar_length = if max_probability > 0.0 {
+            (probability / max_probability * HISTOGRAM_WIDTH as f64).round() as usize
+        } else {
+            0
+        };
+        table_histogram.add_row(vec![
+            Cell::new(format!("{:.4}", from)),
+            Cell::new(format!("{:.4}", to)),
+            Cell::new(format!("{:.2}", probability * 100.0)),
+            Cell::new("█".repeat(bar_length)).fg(color),
+        ]);
+    }
+    println!("{table_histogram}");
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::statistics::normal_cdf;
+
+    fn assert_close(actual: f64, expected: f64, epsilon: f64) {
+        assert!(
+            (actual - expected).abs() < epsilon,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_probability_input() {
+        let query = parse_probability_input("prob 50H7/k6").unwrap();
+        assert_eq!(query.fit_query.hole_class(), "H7");
+        assert_eq!(query.fit_query.shaft_class(), "k6");
+        assert_eq!(query.hole_distribution, Distribution::Normal(3.0));
+        assert_eq!(query.shaft_distribution, Distribution::Normal(3.0));
+
+        let query = parse_probability_input("prob 50H7/n6 uniform").unwrap();
+        assert_eq!(query.hole_distribution, Distribution::Uniform);
+        assert_eq!(query.shaft_distribution, Distribution::Uniform);
+
+        let query = parse_probability_input("prob 50H7/n6 uniform normal:4").unwrap();
+        assert_eq!(query.hole_distribution, Distribution::Uniform);
+        assert_eq!(query.shaft_distribution, Distribution::Normal(4.0));
+
+        let query = parse_probability_input("prob 50H7/k6 shifted:0.2:0.15 normal").unwrap();
+        assert_eq!(
+            query.hole_distribution,
+            Distribution::Shifted {
+                asymmetry: 0.2,
+                spread: 0.15
+            }
+        );
+        assert_eq!(query.shaft_distribution, Distribution::Normal(3.0));
+
+        assert_eq!(parse_probability_input("prob 50H7"), None);
+        assert_eq!(parse_probability_input("prob 50H7/k6 gauss"), None);
+    }
+
+    #[test]
+    fn test_interference_probability() {
+        let distribution = ClearanceDistribution::new(
+            ((dec!(1), dec!(0)), (dec!(1), dec!(0))),
+            Distribution::Uniform,
+            Distribution::Uniform,
+        );
+        assert_close(distribution.interference_probability(), 0.5, 1e-6);
+
+        let distribution = ClearanceDistribution::new(
+            ((dec!(1), dec!(0)), (dec!(1.5), dec!(0.5))),
+            Distribution::Uniform,
+            Distribution::Uniform,
+        );
+        assert_close(distribution.interference_probability(), 0.875, 1e-6);
+
+        // H7/k6 при 50 мм: S ~ N(0.0025, sqrt(0.025² + 0.016²) / 6)
+        let distribution = ClearanceDistribution::new(
+            ((dec!(0.025), dec!(0)), (dec!(0.018), dec!(0.002))),
+            Distribution::Normal(3.0),
+            Distribution::Normal(3.0),
+        );
+        assert_close(distribution.sigma(), 0.004947, 1e-6);
+        assert_close(distribution.interference_probability(), 0.306686, 1e-4);
+
+        // Отверстие смещено к ES на 0.2·T/2, вал к ei на 0.5·T/2; σ = T/6 и T/8.
+        let distribution = ClearanceDistribution::new(
+            ((dec!(0.025), dec!(0)), (dec!(0.018), dec!(0.002))),
+            Distribution::Shifted {
+                asymmetry: 0.2,
+                spread: 1.0 / 6.0,
+            },
+            Distribution::Shifted {
+                asymmetry: -0.5,
+                spread: 0.125,
+            },
+        );
+        assert_close(distribution.mean_shift(), 0.0025 + 0.004, 1e-12);
+        let sigma = (0.025f64 / 6.0).hypot(0.016 / 8.0);
+        assert_close(distribution.sigma(), sigma, 1e-12);
+        assert_close(
+            distribution.interference_probability(),
+            normal_cdf(-(0.0025 + 0.0065) / sigma),
+            1e-4,
+        );
+    }
+
+    #[test]
+    fn test_histogram() {
+        let distribution = ClearanceDistribution::new(
+            ((dec!(1), dec!(0)), (dec!(1), dec!(0))),
+            Distribution::Uniform,
+            Distribution::Uniform,
+        );
+        let histogram = distribution.histogram(-1.0, 1.0, 2);
+        assert_eq!(histogram.len(), 2);
+        assert_close(histogram[0].2, 0.5, 1e-6);
+        assert_close(histogram[1].2, 0.5, 1e-6);
+        let total: f64 = distribution
+            .histogram(-1.0, 1.0, 10)
+            .iter()
+            .map(|(_, _, probability)| probability)
+            .sum();
+        assert_close(total, 1.0, 1e-9);
+    }
+}
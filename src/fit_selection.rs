@@ -0,0 +1,310 @@
+use core::f64::consts::PI;
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use regex::Regex;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::fit::{calc_fit, FitLimits};
+use crate::params::Params;
+use crate::press_fit::{joint_param_keys, parse_joint, PressFitJoint};
+use crate::{
+    replace_comma_with_dot, search_all_in_tables, search_tols_decimal, split_class, POOL,
+    SIZE_PATTERN, TABLES_SHAFTS,
+};
+
+const SHOWN_CANDIDATES: usize = 10;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LoadQuery {
+    pub size: String,
+    pub joint: PressFitJoint,
+    /// Передаваемый момент, Н·м.
+    pub torque: f64,
+    /// Передаваемая осевая сила, Н.
+    pub axial_force: f64,
+    /// Запас сцепления от проворота и сдвига.
+    pub slip_safety: f64,
+    /// Запас по пределу текучести при наибольшем натяге.
+    pub yield_safety: f64,
+    pub hole_accuracy: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct InterferenceWindow {
+    pub min_pressure: f64,
+    pub max_pressure: f64,
+    pub min_interference: f64,
+    pub max_interference: f64,
+}
+
+pub fn parse_load_input(input: &str) -> Option<LoadQuery> {
+    let regex = Regex::new(&format!(
+        r"^load\s+(?P<size>{})(?P<params>(\s+\S+)*)$",
+        SIZE_PATTERN
+    ))
+    .expect("Ошибка обработки RegEx");
+    let captures = regex.captures(input)?;
+    let mut keys = joint_param_keys();
+    keys.extend(
+        ["T", "F", "S", "Sf", "hole"]
+            .iter()
+            .map(|key| key.to_string()),
+    );
+    let allowed: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let params = Params::parse(captures["params"].split_whitespace(), &allowed)?;
+
+    let size = replace_comma_with_dot(&captures["size"]);
+    let hole_accuracy = params.text("hole").unwrap_or("7").to_string();
+    let query = LoadQuery {
+        joint: parse_joint(size.parse().ok()?, &params)?,
+        size,
+        torque: params.number_or("T", 0.0)?,
+        axial_force: params.number_or("F", 0.0)?,
+        slip_safety: params.number_or("S", 1.5)?,
+        yield_safety: params.number_or("Sf", 1.0)?,
+        hole_accuracy,
+    };
+    let valid = (query.torque > 0.0 || query.axial_force > 0.0)
+        && query.torque >= 0.0
+        && query.axial_force >= 0.0
+        && query.slip_safety > 0.0
+        && query.yield_safety > 0.0
+        && Regex::new(r"^([1-9]|1[0-8])$")
+            .expect("Ошибка обработки RegEx")
+            .is_match(&query.hole_accuracy);
+    valid.then_some(query)
+}
+
+/// Наименьший натяг - из условия передачи нагрузки без проскальзывания,
+/// наибольший - из условия отсутствия пластических деформаций.
+pub fn calc_interference_window(load_query: &LoadQuery) -> InterferenceWindow {
+    let joint = &load_query.joint;
+    let tangential_force = 2000.0 * load_query.torque / joint.diameter;
+    let resultant = (tangential_force * tangential_force
+        + load_query.axial_force * load_query.axial_force)
+        .sqrt();
+    let min_pressure =
+        load_query.slip_safety * resultant / (PI * joint.diameter * joint.length * joint.friction);
+    let max_pressure = joint.yield_pressure() / load_query.yield_safety;
+    InterferenceWindow {
+        min_pressure,
+        max_pressure,
+        min_interference: joint.interference_for_pressure(min_pressure),
+        max_interference: joint.interference_for_pressure(max_pressure),
+    }
+}
+
+/// Посадки с отверстием H, обеспечивающие натяг в пределах окна. Как в
+/// стандартных посадках, вал берётся того же квалитета или на один точнее.
+/// Первой идёт самая свободная: с наименьшим натягом, при равенстве - более грубая.
+pub fn select_fits(
+    window: &InterferenceWindow,
+    hole_grade: u8,
+    hole_tols: (Decimal, Decimal),
+    shafts: &[(String, Decimal, Decimal)],
+) -> Vec<(String, FitLimits)> {
+    let mut candidates: Vec<(String, FitLimits)> = shafts
+        .iter()
+        .filter(|(class, _, _)| {
+            split_class(class)
+                .1
+                .parse::<u8>()
+                .map(|grade| grade + 1 >= hole_grade && grade <= hole_grade)
+                .unwrap_or(false)
+        })
+        .map(|(class, es, ei)| (class.clone(), calc_fit(hole_tols, (*es, *ei))))
+        .filter(|(_, fit_limits)| {
+            let min_interference = (-fit_limits.max_clearance).to_f64().unwrap_or_default();
+            let max_interference = (-fit_limits.min_clearance).to_f64().unwrap_or_default();
+            min_interference >= window.min_interference
+                && max_interference <= window.max_interference
+        })
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.1.max_clearance
+            .cmp(&a.1.max_clearance)
+            .then(b.1.fit_tolerance.cmp(&a.1.fit_tolerance))
+    });
+    candidates
+}
+
+pub fn handle_load(load_query: &LoadQuery) {
+    let window = calc_interference_window(load_query);
+    print_interference_window(load_query, &window);
+    if window.min_interference > window.max_interference {
+        println!("Передать нагрузку без пластических деформаций невозможно: увеличьте длину, диаметр ступицы или коэффициент трения");
+        return;
+    }
+    let hole_tols = search_tols_decimal(&load_query.size, "H", &load_query.hole_accuracy);
+    let shafts = search_all_in_tables(&POOL, &load_query.size, &TABLES_SHAFTS);
+    match hole_tols.and_then(|hole_tols| shafts.map(|shafts| (hole_tols, shafts))) {
+        Ok((Some(hole_tols), shafts)) => {
+            let hole_grade = load_query.hole_accuracy.parse().unwrap_or_default();
+            let candidates = select_fits(&window, hole_grade, hole_tols, &shafts);
+            if candidates.is_empty() {
+                println!("Ни одна стандартная посадка не попадает в требуемый диапазон натягов");
+            } else {
+                print_candidates(load_query, &candidates)
+            }
+        }
+        Ok((None, _)) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_interference_window(load_query: &LoadQuery, window: &InterferenceWindow) {
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            format!("{} мм", load_query.size),
+            "давление, МПа".to_string(),
+            "натяг, мм".to_string(),
+        ])
+        .add_row(vec![
+            Cell::new(format!(
+                "наименьший (запас сцепления {})",
+                load_query.slip_safety
+            )),
+            Cell::new(format!("{:.1}", window.min_pressure)).fg(Color::Cyan),
+            Cell::new(format!("{:.4}", window.min_interference)).fg(Color::Cyan),
+        ])
+        .add_row(vec![
+            Cell::new(format!(
+                "наибольший (запас по Re {})",
+                load_query.yield_safety
+            )),
+            Cell::new(format!("{:.1}", window.max_pressure)).fg(Color::Red),
+            Cell::new(format!("{:.4}", window.max_interference)).fg(Color::Red),
+        ]);
+    println!("{table_result}");
+}
+
+fn print_candidates(load_query: &LoadQuery, candidates: &[(String, FitLimits)]) {
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["посадка", "Nmin", "Nmax", "момент при Nmin, Н·м"]);
+    for (index, (shaft_class, fit_limits)) in candidates.iter().take(SHOWN_CANDIDATES).enumerate() {
+        let color = if index == 0 {
+            Color::Green
+        } else {
+            Color::White
+        };
+        let min_interference = (-fit_limits.max_clearance).to_f64().unwrap_or_default();
+        let torque = load_query
+            .joint
+            .torque(load_query.joint.pressure(min_interference));
+        table_result.add_row(vec![
+            Cell::new(format!("H{}/{}", load_query.hole_accuracy, shaft_class)).fg(color),
+            Cell::new(-fit_limits.max_clearance).fg(color),
+            Cell::new(-fit_limits.min_clearance).fg(color),
+            Cell::new(format!("{:.1}", torque)).fg(color),
+        ]);
+    }
+    println!("{table_result}");
+    println!(
+        "Рекомендуемая посадка: {}H{}/{} (подходящих посадок: {})",
+        load_query.size,
+        load_query.hole_accuracy,
+        candidates[0].0,
+        candidates.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, epsilon: f64) {
+        assert!(
+            (actual - expected).abs() < epsilon,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_load_input() {
+        let query = parse_load_input("load 60 T=1500 D=100 L=50").unwrap();
+        assert_eq!(query.size, "60");
+        assert_eq!(query.torque, 1500.0);
+        assert_eq!(query.axial_force, 0.0);
+        assert_eq!(query.slip_safety, 1.5);
+        assert_eq!(query.hole_accuracy, "7");
+        assert_eq!(query.joint.hub_diameter, 100.0);
+
+        let query = parse_load_input("load 60 F=20000 D=100 L=50 hole=6 S=2").unwrap();
+        assert_eq!(query.axial_force, 20000.0);
+        assert_eq!(query.slip_safety, 2.0);
+        assert_eq!(query.hole_accuracy, "6");
+
+        assert_eq!(parse_load_input("load 60 D=100 L=50"), None);
+        assert_eq!(parse_load_input("load 60 T=1500 D=100 L=50 hole=19"), None);
+        assert_eq!(parse_load_input("load 60 T=1500 L=50"), None);
+    }
+
+    #[test]
+    fn test_calc_interference_window() {
+        let query = parse_load_input("load 60 T=1520.0265 D=100 L=50 mu=0.12 S=1").unwrap();
+        let window = calc_interference_window(&query);
+        assert_close(window.min_pressure, 44.8, 1e-3);
+        assert_close(window.min_interference, 0.04, 1e-6);
+        assert_close(window.max_pressure, 113.6, 1e-9);
+        assert_close(window.max_interference, 0.04 * 113.6 / 44.8, 1e-9);
+    }
+
+    #[test]
+    fn test_select_fits() {
+        let window = InterferenceWindow {
+            min_pressure: 0.0,
+            max_pressure: 0.0,
+            min_interference: 0.01,
+            max_interference: 0.1,
+        };
+        let shafts = vec![
+            ("n6".to_string(), dec!(0.039), dec!(0.020)),
+            ("s6".to_string(), dec!(0.072), dec!(0.053)),
+            ("u6".to_string(), dec!(0.106), dec!(0.087)),
+            ("r6".to_string(), dec!(0.060), dec!(0.041)),
+            ("r5".to_string(), dec!(0.054), dec!(0.041)),
+            ("s7".to_string(), dec!(0.083), dec!(0.053)),
+        ];
+        let fits: Vec<String> = select_fits(&window, 7, (dec!(0.03), dec!(0)), &shafts)
+            .into_iter()
+            .map(|(class, _)| class)
+            .collect();
+        assert_eq!(fits, vec!["r6", "s7", "s6"]);
+    }
+
+    #[test]
+    fn test_help_example() {
+        // Пример из справки: 60 мм, ступица D=100, L=50, H7.
+        let query = parse_load_input("load 60 T=800 D=100 L=50 hole=7").unwrap();
+        let window = calc_interference_window(&query);
+        assert_close(window.min_interference, 0.0316, 1e-4);
+        assert_close(window.max_interference, 0.1014, 1e-4);
+        // Валы для интервала 50 … 65 мм по ГОСТ 25347.
+        let shafts = vec![
+            ("r6".to_string(), dec!(0.060), dec!(0.041)),
+            ("s6".to_string(), dec!(0.072), dec!(0.053)),
+            ("s7".to_string(), dec!(0.083), dec!(0.053)),
+            ("t6".to_string(), dec!(0.085), dec!(0.066)),
+            ("t7".to_string(), dec!(0.096), dec!(0.066)),
+            ("u6".to_string(), dec!(0.106), dec!(0.087)),
+            ("u7".to_string(), dec!(0.117), dec!(0.087)),
+        ];
+        let fits: Vec<String> = select_fits(&window, 7, (dec!(0.030), dec!(0)), &shafts)
+            .into_iter()
+            .map(|(class, _)| class)
+            .collect();
+        assert_eq!(fits, vec!["t7", "t6"]);
+    }
+}
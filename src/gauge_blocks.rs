@@ -0,0 +1,408 @@
+use std::fs;
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::inspection::{search_class_limits, ToleranceClass};
+use crate::params::Params;
+use crate::{parse_input, replace_comma_with_dot};
+
+/// Наибольший размер таблицы перебора, бит (8 МБ).
+const MAX_TABLE_BITS: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum BlockTarget {
+    Size(Decimal),
+    /// Верхний (true) или нижний (false) предельный размер поля допуска.
+    Limit(ToleranceClass, bool),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BlocksQuery {
+    pub target: BlockTarget,
+    /// Номер стандартного набора (87, 103, 112, 47) или путь к файлу набора.
+    pub set: String,
+    pub exclude: Vec<Decimal>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct GaugeBlockSet {
+    pub name: String,
+    pub blocks: Vec<Decimal>,
+}
+
+fn block_series(from: Decimal, to: Decimal, step: Decimal) -> impl Iterator<Item = Decimal> {
+    let mut value = from;
+    core::iter::from_fn(move || {
+        let current = value;
+        value += step;
+        (current <= to).then_some(current)
+    })
+}
+
+impl GaugeBlockSet {
+    /// Стандартные метрические наборы плоскопараллельных концевых мер (ISO 3650).
+    pub fn standard(pieces: &str) -> Option<Self> {
+        let mut blocks = Vec::new();
+        match pieces {
+            "87" => {
+                blocks.extend(block_series(dec!(1.001), dec!(1.009), dec!(0.001)));
+                blocks.extend(block_series(dec!(1.01), dec!(1.49), dec!(0.01)));
+                blocks.extend(block_series(dec!(0.5), dec!(9.5), dec!(0.5)));
+                blocks.extend(block_series(dec!(10), dec!(100), dec!(10)));
+            }
+            "103" => {
+                blocks.push(dec!(1.005));
+                blocks.extend(block_series(dec!(1.01), dec!(1.49), dec!(0.01)));
+                blocks.extend(block_series(dec!(0.5), dec!(24.5), dec!(0.5)));
+                blocks.extend(block_series(dec!(25), dec!(100), dec!(25)));
+            }
+            "112" => {
+                blocks.push(dec!(1.0005));
+                blocks.extend(block_series(dec!(1.001), dec!(1.009), dec!(0.001)));
+                blocks.extend(block_series(dec!(1.01), dec!(1.49), dec!(0.01)));
+                blocks.extend(block_series(dec!(0.5), dec!(24.5), dec!(0.5)));
+                blocks.extend(block_series(dec!(25), dec!(100), dec!(25)));
+            }
+            "47" => {
+                blocks.push(dec!(1.005));
+                blocks.extend(block_series(dec!(1.01), dec!(1.09), dec!(0.01)));
+                blocks.extend(block_series(dec!(1.1), dec!(1.9), dec!(0.1)));
+                blocks.extend(block_series(dec!(1), dec!(24), dec!(1)));
+                blocks.extend(block_series(dec!(25), dec!(100), dec!(25)));
+            }
+            _ => return None,
+        }
+        Some(GaugeBlockSet {
+            name: format!("стандартный набор {} шт.", pieces),
+            blocks: blocks.into_iter().map(|block| block.normalize()).collect(),
+        })
+    }
+
+    /// Набор из текста: по одной мере на строку, метка `worn` или `missing`
+    /// после размера исключает меру из набора, строки с `#` - комментарии.
+    pub fn parse(name: &str, content: &str) -> Result<Self, String> {
+        let mut blocks = Vec::new();
+        for (number, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let mut tokens = line.split_whitespace();
+            let size = match tokens.next() {
+                Some(size) => size,
+                None => continue,
+            };
+            let block: Decimal = replace_comma_with_dot(size)
+                .parse()
+                .ok()
+                .filter(|block: &Decimal| block.is_sign_positive() && !block.is_zero())
+                .ok_or(format!(
+                    "строка {}: некорректный размер меры '{}'",
+                    number + 1,
+                    size
+                ))?;
+            match tokens.next() {
+                None => blocks.push(block.normalize()),
+                Some("worn") | Some("missing") => {}
+                Some(mark) => {
+                    return Err(format!(
+                        "строка {}: неизвестная метка '{}'",
+                        number + 1,
+                        mark
+                    ))
+                }
+            }
+        }
+        Ok(GaugeBlockSet {
+            name: name.to_string(),
+            blocks,
+        })
+    }
+
+    pub fn load(set: &str) -> Result<Self, String> {
+        match GaugeBlockSet::standard(set) {
+            Some(block_set) => Ok(block_set),
+            None => {
+                let content = fs::read_to_string(set)
+                    .map_err(|e| format!("не удалось прочитать файл набора '{}': {}", set, e))?;
+                GaugeBlockSet::parse(set, &content)
+            }
+        }
+    }
+
+    /// Исключает по одному экземпляру каждой указанной меры.
+    /// Возвращает меры, которых в наборе не нашлось.
+    pub fn exclude(&mut self, blocks: &[Decimal]) -> Vec<Decimal> {
+        let mut not_found = Vec::new();
+        for block in blocks {
+            match self.blocks.iter().position(|b| b == block) {
+                Some(index) => {
+                    self.blocks.remove(index);
+                }
+                None => not_found.push(*block),
+            }
+        }
+        not_found
+    }
+}
+
+pub fn parse_blocks_input(input: &str) -> Option<BlocksQuery> {
+    let mut tokens = input.strip_prefix("blocks")?.split_whitespace().peekable();
+    let target_token = tokens.next()?;
+    let target = match parse_input(target_token) {
+        Some(class) => {
+            let upper = match tokens.next_if(|token| !token.contains('='))? {
+                "upper" => true,
+                "lower" => false,
+                _ => return None,
+            };
+            BlockTarget::Limit(class, upper)
+        }
+        None => BlockTarget::Size(replace_comma_with_dot(target_token).parse().ok()?),
+    };
+    let params = Params::parse(tokens, &["set", "exclude"])?;
+    let exclude = match params.text("exclude") {
+        Some(exclude) => exclude
+            .split(';')
+            .map(|block| replace_comma_with_dot(block).parse::<Decimal>().ok())
+            .map(|block| block.map(|block| block.normalize()))
+            .collect::<Option<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+    Some(BlocksQuery {
+        target,
+        set: params.text("set").unwrap_or("87").to_string(),
+        exclude,
+    })
+}
+
+/// Размер в единицах 0.0001 мм, если он задан не точнее.
+fn to_units(size: Decimal) -> Option<i64> {
+    let units = size * dec!(10000);
+    (units.fract().is_zero()).then(|| units.to_i64()).flatten()
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Блок из наименьшего числа мер набора, дающий размер `target`.
+/// Каждая мера набора используется не более одного раза (рюкзак 0/1 по числу мер).
+/// Err - таблица перебора для такого размера и набора слишком велика.
+pub fn build_stack(target: Decimal, blocks: &[Decimal]) -> Result<Option<Vec<Decimal>>, String> {
+    let target = match to_units(target).filter(|target| *target > 0) {
+        Some(target) => target,
+        None => return Ok(None),
+    };
+    let units: Vec<(i64, Decimal)> = blocks
+        .iter()
+        .filter_map(|block| to_units(*block).map(|units| (units, *block)))
+        .filter(|(units, _)| *units > 0 && *units <= target)
+        .collect();
+    if units.iter().map(|(units, _)| units).sum::<i64>() < target {
+        return Ok(None);
+    }
+    // Размер, не кратный шагу мер набора, не набирается ни при каком сочетании.
+    let step = units.iter().fold(0, |step, (units, _)| gcd(step, *units));
+    if target % step != 0 {
+        return Ok(None);
+    }
+    let target = (target / step) as usize;
+    let words = target / 64 + 1;
+    if units.len() * words * 64 > MAX_TABLE_BITS {
+        return Err(format!(
+            "размер слишком велик для подбора из {} мер с шагом {} мм",
+            units.len(),
+            (Decimal::from(step) / dec!(10000)).normalize()
+        ));
+    }
+    let mut counts = vec![u32::MAX; target + 1];
+    counts[0] = 0;
+    // Признак «мера index взята для суммы sum», по биту на сумму.
+    let mut taken = vec![0u64; units.len() * words];
+    for (index, (block, _)) in units.iter().enumerate() {
+        let block = (*block / step) as usize;
+        for sum in (block..=target).rev() {
+            let count = counts[sum - block].saturating_add(1);
+            if count < counts[sum] {
+                counts[sum] = count;
+                taken[index * words + sum / 64] |= 1 << (sum % 64);
+            }
+        }
+    }
+    if counts[target] == u32::MAX {
+        return Ok(None);
+    }
+    let mut stack = Vec::new();
+    let mut sum = target;
+    for (index, (block, size)) in units.iter().enumerate().rev() {
+        if sum > 0 && taken[index * words + sum / 64] & (1 << (sum % 64)) != 0 {
+            stack.push(*size);
+            sum -= (*block / step) as usize;
+        }
+    }
+    stack.sort_by(|a, b| b.cmp(a));
+    Ok(Some(stack))
+}
+
+pub fn handle_blocks(blocks_query: &BlocksQuery) {
+    let target = match &blocks_query.target {
+        BlockTarget::Size(size) => *size,
+        BlockTarget::Limit(class, upper) => match search_class_limits(class) {
+            Ok(Some(limits)) => {
+                if *upper {
+                    limits.0
+                } else {
+                    limits.1
+                }
+            }
+            Ok(None) => {
+                println!("Ничего не найдено для заданных параметров");
+                return;
+            }
+            Err(e) => {
+                println!("Ошибка при поиске в БД: {}", e);
+                return;
+            }
+        },
+    };
+    let mut block_set = match GaugeBlockSet::load(&blocks_query.set) {
+        Ok(block_set) => block_set,
+        Err(e) => {
+            println!("Ошибка: {}", e);
+            return;
+        }
+    };
+    let not_found = block_set.exclude(&blocks_query.exclude);
+    if !not_found.is_empty() {
+        let not_found: Vec<String> = not_found.iter().map(|block| block.to_string()).collect();
+        println!("В наборе нет мер: {}", not_found.join(", "));
+    }
+    match build_stack(target, &block_set.blocks) {
+        Ok(Some(stack)) => print_stack(target, &block_set, &stack),
+        Ok(None) => println!(
+            "Размер {} не набирается из мер ({})",
+            target.normalize(),
+            block_set.name
+        ),
+        Err(e) => println!("Ошибка: {}", e),
+    }
+}
+
+fn print_stack(target: Decimal, block_set: &GaugeBlockSet, stack: &[Decimal]) {
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new(format!("блок {}", target.normalize())),
+            Cell::new(&block_set.name),
+        ]);
+    let mut total = Decimal::ZERO;
+    for (number, block) in stack.iter().enumerate() {
+        total += block;
+        table_result.add_row(vec![
+            Cell::new(number + 1),
+            Cell::new(block).fg(Color::Cyan),
+        ]);
+    }
+    table_result.add_row(vec![
+        Cell::new(format!("итого, мер: {}", stack.len())),
+        Cell::new(total.normalize()).fg(Color::Green),
+    ]);
+    println!("{table_result}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_sets() {
+        for pieces in ["87", "103", "112", "47"] {
+            let block_set = GaugeBlockSet::standard(pieces).unwrap();
+            assert_eq!(block_set.blocks.len().to_string(), pieces);
+        }
+        assert_eq!(GaugeBlockSet::standard("88"), None);
+    }
+
+    #[test]
+    fn test_parse_block_set() {
+        let block_set =
+            GaugeBlockSet::parse("мой", "# набор\n1.005\n1,5 worn\n\n2 missing\n10 # новая\n")
+                .unwrap();
+        assert_eq!(block_set.blocks, vec![dec!(1.005), dec!(10)]);
+        assert!(GaugeBlockSet::parse("мой", "abc").is_err());
+        assert!(GaugeBlockSet::parse("мой", "1.5 broken").is_err());
+        assert!(GaugeBlockSet::parse("мой", "-1.5").is_err());
+    }
+
+    #[test]
+    fn test_parse_blocks_input() {
+        assert_eq!(
+            parse_blocks_input("blocks 25,013").unwrap(),
+            BlocksQuery {
+                target: BlockTarget::Size(dec!(25.013)),
+                set: "87".to_string(),
+                exclude: Vec::new(),
+            }
+        );
+        assert_eq!(
+            parse_blocks_input("blocks 25H6 upper set=112 exclude=1.003;1,5").unwrap(),
+            BlocksQuery {
+                target: BlockTarget::Limit(
+                    ("25".to_string(), "H".to_string(), "6".to_string()),
+                    true
+                ),
+                set: "112".to_string(),
+                exclude: vec![dec!(1.003), dec!(1.5)],
+            }
+        );
+        assert_eq!(parse_blocks_input("blocks 25H6"), None);
+        assert_eq!(parse_blocks_input("blocks 25H6 middle"), None);
+        assert_eq!(parse_blocks_input("blocks 25 mode=fast"), None);
+    }
+
+    #[test]
+    fn test_build_stack() {
+        let blocks = GaugeBlockSet::standard("87").unwrap().blocks;
+        let stack = build_stack(dec!(25.013), &blocks).unwrap().unwrap();
+        assert_eq!(stack.len(), 4);
+        assert_eq!(stack.iter().sum::<Decimal>(), dec!(25.013));
+
+        let stack = build_stack(dec!(58.975), &blocks).unwrap().unwrap();
+        assert_eq!(stack.iter().sum::<Decimal>(), dec!(58.975));
+        assert!(stack.len() <= 5);
+
+        assert_eq!(build_stack(dec!(10), &blocks), Ok(Some(vec![dec!(10)])));
+        assert_eq!(build_stack(dec!(25.0125), &blocks), Ok(None));
+        assert_eq!(build_stack(dec!(0.2), &blocks), Ok(None));
+        // Больше суммы всех мер набора.
+        assert_eq!(build_stack(dec!(1000), &blocks), Ok(None));
+
+        let blocks = GaugeBlockSet::standard("112").unwrap().blocks;
+        let stack = build_stack(dec!(58.9755), &blocks).unwrap().unwrap();
+        assert_eq!(stack.iter().sum::<Decimal>(), dec!(58.9755));
+        assert!(build_stack(dec!(900.0005), &blocks).is_err());
+        // Не кратно шагу мер набора 0.0005: отказ без построения таблицы.
+        assert_eq!(build_stack(dec!(900.0001), &blocks), Ok(None));
+    }
+
+    #[test]
+    fn test_exclude_blocks() {
+        let mut block_set = GaugeBlockSet::standard("87").unwrap();
+        let not_found = block_set.exclude(&[dec!(1.003), dec!(1.0005)]);
+        assert_eq!(not_found, vec![dec!(1.0005)]);
+        assert_eq!(block_set.blocks.len(), 86);
+        let stack = build_stack(dec!(25.013), &block_set.blocks)
+            .unwrap()
+            .unwrap();
+        assert!(!stack.contains(&dec!(1.003)));
+        assert_eq!(stack.iter().sum::<Decimal>(), dec!(25.013));
+    }
+}
@@ -0,0 +1,642 @@
+use core::f64::consts::FRAC_1_SQRT_2;
+use std::fs;
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::inspection::{search_class_limits, ToleranceClass};
+use crate::measurements::split_row;
+use crate::params::Params;
+use crate::parse_input;
+use crate::statistics::{d2, f_distribution_sf, mean, range};
+
+/// Ширина зоны рассеяния в σ (AIAG MSA, 4-е изд.).
+const STUDY_SPREAD: f64 = 6.0;
+/// Уровень значимости взаимодействия оператор × деталь, выше которого
+/// взаимодействие объединяется с повторяемостью.
+const INTERACTION_ALPHA: f64 = 0.25;
+/// Коэффициенты K2/K3 = 1/d2* для размаха 2-10 средних (одна подгруппа).
+#[rustfmt::skip]
+const K_SINGLE: [f64; 9] = [
+    FRAC_1_SQRT_2, 0.5231, 0.4467, 0.4030, 0.3742, 0.3534, 0.3375, 0.3249, 0.3146,
+];
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct GrrQuery {
+    pub class: ToleranceClass,
+    pub path: String,
+}
+
+/// Перекрёстное исследование: замеры `trials[оператор][деталь][повтор]`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Study {
+    pub operators: Vec<String>,
+    pub parts: Vec<String>,
+    pub trials: Vec<Vec<Vec<f64>>>,
+}
+
+impl Study {
+    pub fn trial_count(&self) -> usize {
+        self.trials[0][0].len()
+    }
+
+    fn values(&self) -> impl Iterator<Item = &f64> {
+        self.trials.iter().flatten().flatten()
+    }
+
+    fn operator_means(&self) -> Vec<f64> {
+        self.trials
+            .iter()
+            .map(|parts| mean(&parts.concat()))
+            .collect()
+    }
+
+    fn part_means(&self) -> Vec<f64> {
+        (0..self.parts.len())
+            .map(|part| {
+                let values: Vec<f64> = self
+                    .trials
+                    .iter()
+                    .flat_map(|parts| parts[part].iter().copied())
+                    .collect();
+                mean(&values)
+            })
+            .collect()
+    }
+}
+
+/// Составляющие изменчивости, σ.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Variation {
+    pub repeatability: f64,
+    pub reproducibility: f64,
+    pub grr: f64,
+    pub part: f64,
+    pub total: f64,
+}
+
+impl Variation {
+    fn from_variances(repeatability: f64, reproducibility: f64, part: f64) -> Self {
+        let grr = repeatability + reproducibility;
+        Variation {
+            repeatability: repeatability.sqrt(),
+            reproducibility: reproducibility.sqrt(),
+            grr: grr.sqrt(),
+            part: part.sqrt(),
+            total: (grr + part).sqrt(),
+        }
+    }
+
+    /// EV, AV, GRR, PV, TV.
+    pub fn components(&self) -> [f64; 5] {
+        [
+            self.repeatability,
+            self.reproducibility,
+            self.grr,
+            self.part,
+            self.total,
+        ]
+    }
+
+    /// Доля зоны рассеяния 6σ в ширине поля допуска, %.
+    pub fn percent_of_tolerance(sigma: f64, tolerance: f64) -> f64 {
+        STUDY_SPREAD * sigma / tolerance * 100.0
+    }
+
+    /// Число различимых категорий.
+    pub fn distinct_categories(&self) -> usize {
+        if self.grr > 0.0 {
+            (1.41 * self.part / self.grr).floor() as usize
+        } else {
+            usize::MAX
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct AnovaRow {
+    pub name: &'static str,
+    pub df: f64,
+    pub ss: f64,
+    pub ms: f64,
+    /// F-отношение и p-значение; None - для повторяемости и итога.
+    pub f: Option<(f64, f64)>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Anova {
+    pub rows: Vec<AnovaRow>,
+    /// Взаимодействие незначимо и объединено с повторяемостью.
+    pub pooled: bool,
+    pub variation: Variation,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Verdict {
+    Acceptable,
+    Conditional,
+    Unacceptable,
+}
+
+impl Verdict {
+    /// Критерии AIAG: %GRR до 10% - пригодна, 10-30% - условно, выше 30% - непригодна;
+    /// при числе различимых категорий меньше 5 система не более чем условно пригодна.
+    pub fn of(percent_of_tolerance: f64, distinct_categories: usize) -> Self {
+        if percent_of_tolerance > 30.0 {
+            Verdict::Unacceptable
+        } else if percent_of_tolerance > 10.0 || distinct_categories < 5 {
+            Verdict::Conditional
+        } else {
+            Verdict::Acceptable
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verdict::Acceptable => "система измерений пригодна",
+            Verdict::Conditional => {
+                "система измерений условно пригодна (в зависимости от назначения и затрат)"
+            }
+            Verdict::Unacceptable => "система измерений непригодна, требуется улучшение",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Verdict::Acceptable => Color::Green,
+            Verdict::Conditional => Color::Yellow,
+            Verdict::Unacceptable => Color::Red,
+        }
+    }
+}
+
+pub fn parse_grr_input(input: &str) -> Option<GrrQuery> {
+    let mut tokens = input.strip_prefix("grr")?.split_whitespace();
+    let class = parse_input(tokens.next()?)?;
+    let params = Params::parse(tokens, &["file"])?;
+    Some(GrrQuery {
+        class,
+        path: params.text("file")?.to_string(),
+    })
+}
+
+/// Строка CSV: оператор, деталь, замеры. Повторы для одной пары могут быть
+/// в одной строке или в нескольких. Строки без замеров (заголовок) пропускаются.
+pub fn parse_study(content: &str) -> Result<Study, String> {
+    let mut operators: Vec<String> = Vec::new();
+    let mut parts: Vec<String> = Vec::new();
+    let mut records: Vec<(usize, usize, Vec<f64>)> = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let cells: Vec<String> = split_row(line)
+            .iter()
+            .map(|cell| cell.trim().to_string())
+            .collect();
+        if cells.len() < 3 {
+            continue;
+        }
+        let values = cells[2..]
+            .iter()
+            .filter(|cell| !cell.is_empty())
+            .map(|cell| cell.parse().ok())
+            .collect::<Option<Vec<f64>>>();
+        let values = match values {
+            Some(values) if !values.is_empty() => values,
+            Some(_) => continue,
+            None if records.is_empty() => continue,
+            None => return Err(format!("строка {}: замеры должны быть числами", number + 1)),
+        };
+        let operator = position_or_push(&mut operators, &cells[0]);
+        let part = position_or_push(&mut parts, &cells[1]);
+        records.push((operator, part, values));
+    }
+    if operators.len() < 2 || parts.len() < 2 {
+        return Err("нужно не меньше двух операторов и двух деталей".to_string());
+    }
+    let mut trials = vec![vec![Vec::new(); parts.len()]; operators.len()];
+    for (operator, part, values) in records {
+        trials[operator][part].extend(values);
+    }
+    let trial_count = trials[0][0].len();
+    if trial_count < 2
+        || trials
+            .iter()
+            .flatten()
+            .any(|cell| cell.len() != trial_count)
+    {
+        return Err(
+            "каждый оператор должен измерить каждую деталь одинаковое число раз, не меньше двух"
+                .to_string(),
+        );
+    }
+    Ok(Study {
+        operators,
+        parts,
+        trials,
+    })
+}
+
+fn position_or_push(labels: &mut Vec<String>, label: &str) -> usize {
+    match labels.iter().position(|known| known == label) {
+        Some(index) => index,
+        None => {
+            labels.push(label.to_string());
+            labels.len() - 1
+        }
+    }
+}
+
+/// Двухфакторный дисперсионный анализ с взаимодействием (AIAG MSA).
+/// Отрицательные оценки дисперсий приравниваются к нулю.
+pub fn anova(study: &Study) -> Anova {
+    let operators = study.operators.len() as f64;
+    let parts = study.parts.len() as f64;
+    let trials = study.trial_count() as f64;
+    let values: Vec<f64> = study.values().copied().collect();
+    let grand_mean = mean(&values);
+    let squares =
+        |means: Vec<f64>| -> f64 { means.iter().map(|mean| (mean - grand_mean).powi(2)).sum() };
+    let ss_total: f64 = values
+        .iter()
+        .map(|value| (value - grand_mean).powi(2))
+        .sum();
+    let ss_operator = parts * trials * squares(study.operator_means());
+    let ss_part = operators * trials * squares(study.part_means());
+    let ss_repeatability: f64 = study
+        .trials
+        .iter()
+        .flatten()
+        .map(|cell| {
+            let cell_mean = mean(cell);
+            cell.iter()
+                .map(|value| (value - cell_mean).powi(2))
+                .sum::<f64>()
+        })
+        .sum();
+    let ss_interaction = (ss_total - ss_operator - ss_part - ss_repeatability).max(0.0);
+
+    let df_operator = operators - 1.0;
+    let df_part = parts - 1.0;
+    let df_interaction = df_operator * df_part;
+    let df_repeatability = operators * parts * (trials - 1.0);
+    let ms_operator = ss_operator / df_operator;
+    let ms_part = ss_part / df_part;
+    let ms_interaction = ss_interaction / df_interaction;
+    let ms_repeatability = ss_repeatability / df_repeatability;
+
+    let f_interaction = ms_interaction / ms_repeatability;
+    let p_interaction = f_distribution_sf(f_interaction, df_interaction, df_repeatability);
+    let pooled = p_interaction > INTERACTION_ALPHA;
+    let row = |name, df, ss, ms, f: Option<(f64, f64)>| AnovaRow {
+        name,
+        df,
+        ss,
+        ms,
+        f,
+    };
+    let total = row("итог", values.len() as f64 - 1.0, ss_total, 0.0, None);
+
+    if pooled {
+        let df_pooled = df_interaction + df_repeatability;
+        let ms_pooled = (ss_interaction + ss_repeatability) / df_pooled;
+        let test = |ms: f64, df: f64| {
+            let f = ms / ms_pooled;
+            (f, f_distribution_sf(f, df, df_pooled))
+        };
+        let variation = Variation::from_variances(
+            ms_pooled,
+            ((ms_operator - ms_pooled) / (parts * trials)).max(0.0),
+            ((ms_part - ms_pooled) / (operators * trials)).max(0.0),
+        );
+        return Anova {
+            rows: vec![
+                row(
+                    "детали",
+                    df_part,
+                    ss_part,
+                    ms_part,
+                    Some(test(ms_part, df_part)),
+                ),
+                row(
+                    "операторы",
+                    df_operator,
+                    ss_operator,
+                    ms_operator,
+                    Some(test(ms_operator, df_operator)),
+                ),
+                row(
+                    "повторяемость (с взаимодействием)",
+                    df_pooled,
+                    ss_interaction + ss_repeatability,
+                    ms_pooled,
+                    None,
+                ),
+                total,
+            ],
+            pooled,
+            variation,
+        };
+    }
+
+    let test = |ms: f64, df: f64| {
+        let f = ms / ms_interaction;
+        (f, f_distribution_sf(f, df, df_interaction))
+    };
+    let interaction = ((ms_interaction - ms_repeatability) / trials).max(0.0);
+    let operator = ((ms_operator - ms_interaction) / (parts * trials)).max(0.0);
+    let variation = Variation::from_variances(
+        ms_repeatability,
+        operator + interaction,
+        ((ms_part - ms_interaction) / (operators * trials)).max(0.0),
+    );
+    Anova {
+        rows: vec![
+            row(
+                "детали",
+                df_part,
+                ss_part,
+                ms_part,
+                Some(test(ms_part, df_part)),
+            ),
+            row(
+                "операторы",
+                df_operator,
+                ss_operator,
+                ms_operator,
+                Some(test(ms_operator, df_operator)),
+            ),
+            row(
+                "оператор × деталь",
+                df_interaction,
+                ss_interaction,
+                ms_interaction,
+                Some((f_interaction, p_interaction)),
+            ),
+            row(
+                "повторяемость",
+                df_repeatability,
+                ss_repeatability,
+                ms_repeatability,
+                None,
+            ),
+            total,
+        ],
+        pooled,
+        variation,
+    }
+}
+
+fn k_single(count: usize) -> Option<f64> {
+    K_SINGLE.get(count.checked_sub(2)?).copied()
+}
+
+/// Метод средних и размахов: EV = R̄·K1, AV = √((X̄diff·K2)² - EV²/(n·r)), PV = Rp·K3.
+/// None - число повторов, операторов или деталей вне 2-10.
+pub fn average_and_range(study: &Study) -> Option<Variation> {
+    let parts = study.parts.len();
+    let trials = study.trial_count();
+    let ranges: Vec<f64> = study
+        .trials
+        .iter()
+        .flatten()
+        .map(|cell| range(cell))
+        .collect();
+    let repeatability = mean(&ranges) / d2(trials)?;
+    let operator_spread = range(&study.operator_means()) * k_single(study.operators.len())?;
+    let reproducibility =
+        (operator_spread.powi(2) - repeatability.powi(2) / (parts * trials) as f64).max(0.0);
+    let part = range(&study.part_means()) * k_single(parts)?;
+    Some(Variation::from_variances(
+        repeatability.powi(2),
+        reproducibility,
+        part.powi(2),
+    ))
+}
+
+pub fn handle_grr(grr_query: &GrrQuery) {
+    let study = match fs::read_to_string(&grr_query.path) {
+        Ok(content) => match parse_study(&content) {
+            Ok(study) => study,
+            Err(e) => {
+                println!("Ошибка в файле '{}': {}", grr_query.path, e);
+                return;
+            }
+        },
+        Err(e) => {
+            println!(
+                "Ошибка: не удалось прочитать файл '{}': {}",
+                grr_query.path, e
+            );
+            return;
+        }
+    };
+    match search_class_limits(&grr_query.class) {
+        Ok(Some((upper, lower))) => {
+            let tolerance = (upper - lower).to_f64().unwrap_or_default();
+            print_grr(grr_query, tolerance, &study)
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_grr(grr_query: &GrrQuery, tolerance: f64, study: &Study) {
+    let anova = anova(study);
+    let mut table_anova = Table::new();
+    table_anova
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["источник", "df", "SS", "MS", "F", "p"]);
+    for row in &anova.rows {
+        let (f, p) = match row.f {
+            Some((f, p)) => (format!("{:.3}", f), format!("{:.4}", p)),
+            None => (String::new(), String::new()),
+        };
+        table_anova.add_row(vec![
+            Cell::new(row.name),
+            Cell::new(row.df),
+            Cell::new(format!("{:.4e}", row.ss)),
+            Cell::new(if row.ms > 0.0 {
+                format!("{:.4e}", row.ms)
+            } else {
+                String::new()
+            }),
+            Cell::new(f),
+            Cell::new(p),
+        ]);
+    }
+    println!("{table_anova}");
+    if anova.pooled {
+        println!(
+            "Взаимодействие оператор × деталь незначимо (p > {}), объединено с повторяемостью",
+            INTERACTION_ALPHA
+        );
+    }
+
+    let average_and_range = average_and_range(study);
+    let (size, field, accuracy) = &grr_query.class;
+    let mut header = vec![
+        Cell::new(format!("{}{}{}, T = {}", size, field, accuracy, tolerance)),
+        Cell::new("ANOVA, 6σ"),
+        Cell::new("% допуска"),
+    ];
+    if average_and_range.is_some() {
+        header.push(Cell::new("средних и размахов, 6σ"));
+        header.push(Cell::new("% допуска"));
+    }
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header);
+    let names = [
+        "повторяемость (EV)",
+        "воспроизводимость (AV)",
+        "GRR",
+        "изменчивость деталей (PV)",
+        "общая изменчивость (TV)",
+    ];
+    for (index, name) in names.into_iter().enumerate() {
+        let mut row = vec![Cell::new(name)];
+        for variation in Some(anova.variation).iter().chain(average_and_range.iter()) {
+            let sigma = variation.components()[index];
+            let percent = Variation::percent_of_tolerance(sigma, tolerance);
+            row.push(Cell::new(format!("{:.5}", STUDY_SPREAD * sigma)));
+            row.push(if name == "GRR" {
+                Cell::new(format!("{:.1}", percent)).fg(Verdict::of(percent, usize::MAX).color())
+            } else {
+                Cell::new(format!("{:.1}", percent))
+            });
+        }
+        table_result.add_row(row);
+    }
+    let mut row = vec![Cell::new("число различимых категорий (ndc)")];
+    for variation in Some(anova.variation).iter().chain(average_and_range.iter()) {
+        let ndc = variation.distinct_categories();
+        row.push(Cell::new(ndc).fg(if ndc >= 5 { Color::Green } else { Color::Red }));
+        row.push(Cell::new(""));
+    }
+    table_result.add_row(row);
+    println!("{table_result}");
+    println!(
+        "Операторов: {}, деталей: {}, повторов: {}",
+        study.operators.len(),
+        study.parts.len(),
+        study.trial_count()
+    );
+    if average_and_range.is_none() {
+        println!("Метод средних и размахов применим при 2-10 операторах, деталях и повторах");
+    }
+
+    let percent = Variation::percent_of_tolerance(anova.variation.grr, tolerance);
+    let verdict = Verdict::of(percent, anova.variation.distinct_categories());
+    let mut table_verdict = Table::new();
+    table_verdict
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .add_row(vec![
+            Cell::new(format!("%GRR = {:.1}% (ANOVA)", percent)),
+            Cell::new(verdict.name()).fg(verdict.color()),
+        ]);
+    println!("{table_verdict}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, epsilon: f64) {
+        assert!(
+            (actual - expected).abs() < epsilon,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    const STUDY: &str = "оператор;деталь;замер 1;замер 2\n\
+                         A;1;10,00;10,02\n\
+                         A;2;10,10;10,10\n\
+                         A;3;9,90;9,92\n\
+                         B;1;10,02;10,04\n\
+                         B;2;10,12;10,10\n\
+                         B;3;9,94;9,92\n";
+
+    #[test]
+    fn test_parse_grr_input() {
+        assert_eq!(
+            parse_grr_input("grr 10h9 file=grr.csv").unwrap(),
+            GrrQuery {
+                class: ("10".to_string(), "h".to_string(), "9".to_string()),
+                path: "grr.csv".to_string(),
+            }
+        );
+        assert_eq!(parse_grr_input("grr 10h9"), None);
+        assert_eq!(parse_grr_input("grr 10h9 file=grr.csv method=x"), None);
+    }
+
+    #[test]
+    fn test_parse_study() {
+        let study = parse_study(STUDY).unwrap();
+        assert_eq!(study.operators, vec!["A", "B"]);
+        assert_eq!(study.parts, vec!["1", "2", "3"]);
+        assert_eq!(study.trials[1][2], vec![9.94, 9.92]);
+
+        let study = parse_study("A,1,10\nA,1,11\nA,2,12,13\nB,1,10,10\nB,2,12\nB,2,12").unwrap();
+        assert_eq!(study.trial_count(), 2);
+        assert_eq!(study.trials[0][0], vec![10.0, 11.0]);
+
+        assert!(parse_study("A;1;10;10\nA;2;10\nB;1;10;10\nB;2;10;10").is_err());
+        assert!(parse_study("A;1;10;10\nA;2;10;10").is_err());
+        assert!(parse_study("A;1;10;10\nA;2;10;x\nB;1;10;10\nB;2;10;10").is_err());
+    }
+
+    #[test]
+    fn test_anova() {
+        let anova = anova(&parse_study(STUDY).unwrap());
+        assert_eq!(anova.rows.len(), 4);
+        assert!(anova.pooled);
+        assert_close(anova.rows[0].ss, 0.0686, 1e-9);
+        assert_close(anova.rows[1].ss, 0.0025 / 3.0, 1e-9);
+        assert_close(anova.rows[2].ss, 0.0032 / 3.0, 1e-9);
+        assert_close(anova.rows[3].ss, 0.0705, 1e-9);
+        let ms_pooled: f64 = 0.0032 / 3.0 / 8.0;
+        let variation = anova.variation;
+        assert_close(variation.repeatability, ms_pooled.sqrt(), 1e-9);
+        assert_close(
+            variation.reproducibility,
+            ((0.0025 / 3.0 - ms_pooled) / 6.0).sqrt(),
+            1e-9,
+        );
+        assert_close(variation.part, ((0.0343 - ms_pooled) / 4.0).sqrt(), 1e-9);
+        assert_close(variation.grr, 2.5e-4f64.sqrt(), 1e-9);
+        assert_eq!(variation.distinct_categories(), 8);
+        assert_close(
+            Variation::percent_of_tolerance(variation.grr, 0.1),
+            6.0 * variation.grr / 0.1 * 100.0,
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn test_average_and_range() {
+        let study = parse_study(STUDY).unwrap();
+        let variation = average_and_range(&study).unwrap();
+        let repeatability = 0.1 / 6.0 / 1.128;
+        assert_close(variation.repeatability, repeatability, 1e-9);
+        assert_close(
+            variation.reproducibility,
+            ((0.05 / 3.0 * FRAC_1_SQRT_2).powi(2) - repeatability.powi(2) / 6.0).sqrt(),
+            1e-9,
+        );
+        assert_close(variation.part, 0.185 * 0.5231, 1e-9);
+    }
+
+    #[test]
+    fn test_verdict() {
+        assert_eq!(Verdict::of(8.0, 6), Verdict::Acceptable);
+        assert_eq!(Verdict::of(8.0, 4), Verdict::Conditional);
+        assert_eq!(Verdict::of(25.0, 10), Verdict::Conditional);
+        assert_eq!(Verdict::of(31.0, 10), Verdict::Unacceptable);
+    }
+}
@@ -0,0 +1,396 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::inspection::{search_class_limits, ToleranceClass};
+use crate::measurement_error::size_range;
+use crate::{is_uppercase, parse_input, search_it_value};
+
+/// Отклонения и допуски калибров ГОСТ 24853-81 для квалитета детали, мкм, по интервалам
+/// размеров до 500 мм. Квалитеты допусков калибров: H (пробки), H1 (скобы, кольца),
+/// Hp (контрольные калибры).
+struct GaugeTable {
+    grade: u8,
+    z: [f64; 13],
+    y: [f64; 13],
+    alpha: [f64; 13],
+    z1: [f64; 13],
+    y1: [f64; 13],
+    alpha1: [f64; 13],
+    gauge_grades: (u8, u8, u8),
+}
+
+#[rustfmt::skip]
+const GAUGE_TABLES: [GaugeTable; 7] = [
+    GaugeTable {
+        grade: 6,
+        z: [1.0, 1.5, 1.5, 2.0, 2.0, 2.5, 2.5, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0],
+        y: [1.0, 1.0, 1.0, 1.5, 1.5, 2.0, 2.0, 3.0, 3.0, 4.0, 5.0, 6.0, 7.0],
+        alpha: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 2.0, 3.0, 4.0, 5.0],
+        z1: [1.5, 2.0, 2.0, 2.5, 3.0, 3.5, 4.0, 5.0, 6.0, 7.0, 8.0, 10.0, 11.0],
+        y1: [1.5, 1.5, 1.5, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 5.0, 6.0, 6.0, 7.0],
+        alpha1: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 3.0, 4.0, 6.0, 7.0],
+        gauge_grades: (2, 3, 1),
+    },
+    GaugeTable {
+        grade: 7,
+        z: [1.5, 2.0, 2.0, 2.5, 3.0, 3.5, 4.0, 5.0, 6.0, 7.0, 8.0, 10.0, 11.0],
+        y: [1.5, 1.5, 1.5, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 6.0, 7.0, 8.0, 9.0],
+        alpha: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 3.0, 4.0, 6.0, 7.0],
+        z1: [1.5, 2.0, 2.0, 2.5, 3.0, 3.5, 4.0, 5.0, 6.0, 7.0, 8.0, 10.0, 11.0],
+        y1: [1.5, 1.5, 1.5, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 6.0, 7.0, 8.0, 9.0],
+        alpha1: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 3.0, 4.0, 6.0, 7.0],
+        gauge_grades: (3, 4, 2),
+    },
+    GaugeTable {
+        grade: 8,
+        z: [2.0, 3.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 12.0, 14.0, 16.0, 18.0],
+        y: [3.0, 3.0, 3.0, 4.0, 4.0, 5.0, 5.0, 6.0, 6.0, 7.0, 9.0, 9.0, 11.0],
+        alpha: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 4.0, 6.0, 7.0, 9.0],
+        z1: [2.0, 3.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 12.0, 14.0, 16.0, 18.0],
+        y1: [3.0, 3.0, 3.0, 4.0, 4.0, 5.0, 5.0, 6.0, 6.0, 7.0, 9.0, 9.0, 11.0],
+        alpha1: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 4.0, 6.0, 7.0, 9.0],
+        gauge_grades: (4, 5, 2),
+    },
+    GaugeTable {
+        grade: 9,
+        z: [5.0, 6.0, 7.0, 8.0, 9.0, 11.0, 13.0, 15.0, 18.0, 21.0, 24.0, 28.0, 32.0],
+        y: [0.0; 13],
+        alpha: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 4.0, 6.0, 7.0, 9.0],
+        z1: [5.0, 6.0, 7.0, 8.0, 9.0, 11.0, 13.0, 15.0, 18.0, 21.0, 24.0, 28.0, 32.0],
+        y1: [0.0; 13],
+        alpha1: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 4.0, 6.0, 7.0, 9.0],
+        gauge_grades: (5, 5, 2),
+    },
+    GaugeTable {
+        grade: 10,
+        z: [5.0, 6.0, 7.0, 8.0, 9.0, 11.0, 13.0, 15.0, 18.0, 24.0, 27.0, 32.0, 37.0],
+        y: [0.0; 13],
+        alpha: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 4.0, 6.0, 7.0, 9.0],
+        z1: [5.0, 6.0, 7.0, 8.0, 9.0, 11.0, 13.0, 15.0, 18.0, 24.0, 27.0, 32.0, 37.0],
+        y1: [0.0; 13],
+        alpha1: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 4.0, 6.0, 7.0, 9.0],
+        gauge_grades: (5, 5, 2),
+    },
+    GaugeTable {
+        grade: 11,
+        z: [10.0, 12.0, 14.0, 16.0, 19.0, 22.0, 25.0, 28.0, 32.0, 40.0, 45.0, 50.0, 55.0],
+        y: [0.0; 13],
+        alpha: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 10.0, 15.0, 15.0, 20.0],
+        z1: [10.0, 12.0, 14.0, 16.0, 19.0, 22.0, 25.0, 28.0, 32.0, 40.0, 45.0, 50.0, 55.0],
+        y1: [0.0; 13],
+        alpha1: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 10.0, 15.0, 15.0, 20.0],
+        gauge_grades: (7, 7, 4),
+    },
+    GaugeTable {
+        grade: 12,
+        z: [10.0, 12.0, 14.0, 16.0, 19.0, 22.0, 25.0, 28.0, 32.0, 45.0, 50.0, 65.0, 70.0],
+        y: [0.0; 13],
+        alpha: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 10.0, 15.0, 15.0, 20.0],
+        z1: [10.0, 12.0, 14.0, 16.0, 19.0, 22.0, 25.0, 28.0, 32.0, 45.0, 50.0, 65.0, 70.0],
+        y1: [0.0; 13],
+        alpha1: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 10.0, 15.0, 15.0, 20.0],
+        gauge_grades: (7, 7, 4),
+    },
+];
+
+/// Смещение поля допуска проходной стороны (Z), выход износа за предел (Y)
+/// и сдвиг для размеров свыше 180 мм (α), мм.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct GaugeDeviations {
+    pub z: Decimal,
+    pub y: Decimal,
+    pub alpha: Decimal,
+}
+
+/// Калибр с пределами размера и границей износа проходной стороны.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Gauge {
+    pub name: &'static str,
+    pub upper: Decimal,
+    pub lower: Decimal,
+    pub wear_limit: Option<Decimal>,
+    /// Пробка (исполнительный размер от верхнего предела) или скоба/кольцо (от нижнего).
+    pub is_plug: bool,
+}
+
+impl Gauge {
+    fn new(
+        name: &'static str,
+        center: Decimal,
+        tolerance: Decimal,
+        wear_limit: Option<Decimal>,
+        is_plug: bool,
+    ) -> Self {
+        let half = tolerance * dec!(0.5);
+        Gauge {
+            name,
+            upper: (center + half).normalize(),
+            lower: (center - half).normalize(),
+            wear_limit: wear_limit.map(|wear_limit| wear_limit.normalize()),
+            is_plug,
+        }
+    }
+
+    pub fn tolerance(&self) -> Decimal {
+        (self.upper - self.lower).normalize()
+    }
+
+    /// Исполнительный размер для чертежа калибра: допуск направлен в тело калибра.
+    pub fn executive_size(&self) -> String {
+        if self.is_plug {
+            format!("{} -{}", self.upper, self.tolerance())
+        } else {
+            format!("{} +{}", self.lower, self.tolerance())
+        }
+    }
+}
+
+pub fn parse_gauge_input(input: &str) -> Option<ToleranceClass> {
+    parse_input(input.strip_prefix("gauge")?.trim_start())
+}
+
+fn table_value(values: &[f64; 13], range: usize) -> Decimal {
+    Decimal::from_f64(values[range]).unwrap_or_default() / dec!(1000)
+}
+
+/// Отклонения калибров для квалитета детали и размера. Для отверстий - Z, Y, α,
+/// для валов - Z1, Y1, α1. Вторым значением - квалитеты допусков H (H1) и Hp.
+pub fn gauge_deviations(
+    size: Decimal,
+    grade: u8,
+    is_hole: bool,
+) -> Option<(GaugeDeviations, (u8, u8))> {
+    let range = size_range(size)?;
+    let table = GAUGE_TABLES.iter().find(|table| table.grade == grade)?;
+    let (hole_grade, shaft_grade, check_grade) = table.gauge_grades;
+    if is_hole {
+        Some((
+            GaugeDeviations {
+                z: table_value(&table.z, range),
+                y: table_value(&table.y, range),
+                alpha: table_value(&table.alpha, range),
+            },
+            (hole_grade, check_grade),
+        ))
+    } else {
+        Some((
+            GaugeDeviations {
+                z: table_value(&table.z1, range),
+                y: table_value(&table.y1, range),
+                alpha: table_value(&table.alpha1, range),
+            },
+            (shaft_grade, check_grade),
+        ))
+    }
+}
+
+/// Пробки для отверстия с пределами `limits` (верхний, нижний) и допуском H.
+pub fn plug_gauges(
+    limits: (Decimal, Decimal),
+    deviations: GaugeDeviations,
+    tolerance: Decimal,
+) -> Vec<Gauge> {
+    let (upper, lower) = limits;
+    vec![
+        Gauge::new(
+            "ПР (проходная)",
+            lower + deviations.z,
+            tolerance,
+            Some(lower - deviations.y + deviations.alpha),
+            true,
+        ),
+        Gauge::new(
+            "НЕ (непроходная)",
+            upper - deviations.alpha,
+            tolerance,
+            None,
+            true,
+        ),
+    ]
+}
+
+/// Скобы (кольца) для вала с допуском H1 и контрольные калибры к ним с допуском Hp.
+pub fn ring_gauges(
+    limits: (Decimal, Decimal),
+    deviations: GaugeDeviations,
+    tolerance: Decimal,
+    check_tolerance: Decimal,
+) -> Vec<Gauge> {
+    let (upper, lower) = limits;
+    let go_center = upper - deviations.z;
+    let no_go_center = lower + deviations.alpha;
+    let wear_limit = upper + deviations.y - deviations.alpha;
+    vec![
+        Gauge::new(
+            "ПР (проходная)",
+            go_center,
+            tolerance,
+            Some(wear_limit),
+            false,
+        ),
+        Gauge::new("НЕ (непроходная)", no_go_center, tolerance, None, false),
+        Gauge::new("К-ПР (контрольный)", go_center, check_tolerance, None, true),
+        Gauge::new(
+            "К-НЕ (контрольный)",
+            no_go_center,
+            check_tolerance,
+            None,
+            true,
+        ),
+        Gauge::new(
+            "К-И (контроль износа)",
+            wear_limit,
+            check_tolerance,
+            None,
+            true,
+        ),
+    ]
+}
+
+pub fn handle_gauge(class: &ToleranceClass) {
+    let (size, field, accuracy) = class;
+    let is_hole = is_uppercase(field);
+    let deviations = accuracy
+        .parse()
+        .ok()
+        .and_then(|grade| gauge_deviations(size.parse().unwrap_or_default(), grade, is_hole));
+    let (deviations, (gauge_grade, check_grade)) = match deviations {
+        Some(deviations) => deviations,
+        None => {
+            println!(
+                "Таблицы калибров ГОСТ 24853-81 заданы для размеров до 500 мм и квалитетов 6-12"
+            );
+            return;
+        }
+    };
+    let gauges = search_class_limits(class).and_then(|limits| {
+        let tolerance = search_it_value(size, gauge_grade).map_err(|e| e.to_string())?;
+        let check_tolerance = search_it_value(size, check_grade).map_err(|e| e.to_string())?;
+        Ok(limits.zip(tolerance).zip(check_tolerance).map(
+            |((limits, tolerance), check_tolerance)| {
+                if is_hole {
+                    plug_gauges(limits, deviations, tolerance)
+                } else {
+                    ring_gauges(limits, deviations, tolerance, check_tolerance)
+                }
+            },
+        ))
+    });
+    match gauges {
+        Ok(Some(gauges)) => print_gauges(class, deviations, &gauges),
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_gauges(class: &ToleranceClass, deviations: GaugeDeviations, gauges: &[Gauge]) {
+    let (size, field, accuracy) = class;
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new(format!("{}{}{}", size, field, accuracy)),
+            Cell::new("верхний размер").fg(Color::Red),
+            Cell::new("нижний размер").fg(Color::Cyan),
+            Cell::new("исполнительный размер"),
+            Cell::new("предел износа"),
+        ]);
+    for gauge in gauges {
+        table_result.add_row(vec![
+            Cell::new(gauge.name),
+            Cell::new(gauge.upper).fg(Color::Red),
+            Cell::new(gauge.lower).fg(Color::Cyan),
+            Cell::new(gauge.executive_size()).fg(Color::Green),
+            Cell::new(
+                gauge
+                    .wear_limit
+                    .map(|wear_limit| wear_limit.to_string())
+                    .unwrap_or_default(),
+            )
+            .fg(Color::Yellow),
+        ]);
+    }
+    println!("{table_result}");
+    println!(
+        "Z = {}, Y = {}, α = {} (ГОСТ 24853-81)",
+        deviations.z.normalize(),
+        deviations.y.normalize(),
+        deviations.alpha.normalize()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gauge_input() {
+        assert_eq!(
+            parse_gauge_input("gauge 50H7").unwrap(),
+            ("50".to_string(), "H".to_string(), "7".to_string())
+        );
+        assert_eq!(parse_gauge_input("50H7"), None);
+    }
+
+    #[test]
+    fn test_gauge_deviations() {
+        let (deviations, grades) = gauge_deviations(dec!(50), 7, true).unwrap();
+        assert_eq!(deviations.z, dec!(0.0035));
+        assert_eq!(deviations.y, dec!(0.003));
+        assert_eq!(deviations.alpha, dec!(0));
+        assert_eq!(grades, (3, 2));
+
+        let (deviations, grades) = gauge_deviations(dec!(200), 6, false).unwrap();
+        assert_eq!(deviations.z, dec!(0.007));
+        assert_eq!(deviations.alpha, dec!(0.003));
+        assert_eq!(grades, (3, 1));
+
+        assert_eq!(gauge_deviations(dec!(50), 5, true), None);
+        assert_eq!(gauge_deviations(dec!(600), 7, true), None);
+    }
+
+    #[test]
+    fn test_plug_gauges() {
+        let deviations = GaugeDeviations {
+            z: dec!(0.0035),
+            y: dec!(0.003),
+            alpha: dec!(0),
+        };
+        let gauges = plug_gauges((dec!(50.025), dec!(50)), deviations, dec!(0.004));
+        assert_eq!(gauges[0].upper, dec!(50.0055));
+        assert_eq!(gauges[0].lower, dec!(50.0015));
+        assert_eq!(gauges[0].wear_limit, Some(dec!(49.997)));
+        assert_eq!(gauges[0].executive_size(), "50.0055 -0.004");
+        assert_eq!(gauges[1].upper, dec!(50.027));
+        assert_eq!(gauges[1].lower, dec!(50.023));
+    }
+
+    #[test]
+    fn test_ring_gauges() {
+        let deviations = GaugeDeviations {
+            z: dec!(0.0035),
+            y: dec!(0.003),
+            alpha: dec!(0),
+        };
+        let gauges = ring_gauges(
+            (dec!(50.018), dec!(50.002)),
+            deviations,
+            dec!(0.004),
+            dec!(0.0015),
+        );
+        assert_eq!(gauges[0].upper, dec!(50.0165));
+        assert_eq!(gauges[0].lower, dec!(50.0125));
+        assert_eq!(gauges[0].wear_limit, Some(dec!(50.021)));
+        assert_eq!(gauges[0].executive_size(), "50.0125 +0.004");
+        assert_eq!(gauges[1].upper, dec!(50.004));
+        assert_eq!(gauges[1].lower, dec!(50));
+        assert_eq!(gauges[2].upper, dec!(50.01525));
+        assert_eq!(gauges[2].lower, dec!(50.01375));
+        assert_eq!(gauges[4].upper, dec!(50.02175));
+        assert_eq!(gauges[4].executive_size(), "50.02175 -0.0015");
+    }
+}
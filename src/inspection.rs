@@ -0,0 +1,537 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use regex::Regex;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy::MidpointAwayFromZero;
+use rust_decimal_macros::dec;
+
+use crate::instruments::Instrument;
+use crate::materials::{Material, REFERENCE_TEMPERATURE};
+use crate::params::Params;
+use crate::{
+    calc_average_tol, calc_sizes_with_tols, parse_input, replace_comma_with_dot, search_in_tables,
+    size_tols_map_decimal, NUMBER_PATTERN, POOL,
+};
+
+/// Обозначение поля допуска: размер, основное отклонение, квалитет.
+pub type ToleranceClass = (String, String, String);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Conformity {
+    Within,
+    Oversize,
+    Undersize,
+}
+
+impl Conformity {
+    pub fn of(value: Decimal, limits: (Decimal, Decimal)) -> Self {
+        if value > limits.0 {
+            Conformity::Oversize
+        } else if value < limits.1 {
+            Conformity::Undersize
+        } else {
+            Conformity::Within
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Conformity::Within => "годен",
+            Conformity::Oversize => "больше верхнего предела",
+            Conformity::Undersize => "меньше нижнего предела",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Conformity::Within => Color::Green,
+            Conformity::Oversize => Color::Red,
+            Conformity::Undersize => Color::Cyan,
+        }
+    }
+}
+
+/// Решение о соответствии с учётом неопределённости измерения U по ISO 14253-1:
+/// зона соответствия сужается на U, зона несоответствия расширяется на U.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Decision {
+    Conforming,
+    NonConforming,
+    Undecidable,
+}
+
+impl Decision {
+    pub fn of(value: Decimal, limits: (Decimal, Decimal), uncertainty: Decimal) -> Self {
+        if value >= limits.1 + uncertainty && value <= limits.0 - uncertainty {
+            Decision::Conforming
+        } else if value < limits.1 - uncertainty || value > limits.0 + uncertainty {
+            Decision::NonConforming
+        } else {
+            Decision::Undecidable
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Decision::Conforming => "соответствует",
+            Decision::NonConforming => "не соответствует",
+            Decision::Undecidable => "не определено",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Decision::Conforming => Color::Green,
+            Decision::NonConforming => Color::Red,
+            Decision::Undecidable => Color::Yellow,
+        }
+    }
+}
+
+/// Результат проверки одного измеренного значения.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ValueCheck {
+    pub value: Decimal,
+    pub conformity: Conformity,
+    /// Запас до верхнего предела (отрицательный - превышение).
+    pub to_upper: Decimal,
+    /// Запас до нижнего предела (отрицательный - выход за предел).
+    pub to_lower: Decimal,
+    /// Положение значения в поле допуска: 0 % - нижний предел, 100 % - верхний.
+    pub zone_used: Decimal,
+    pub decision: Option<Decision>,
+}
+
+impl ValueCheck {
+    pub fn new(value: Decimal, limits: (Decimal, Decimal), uncertainty: Option<Decimal>) -> Self {
+        let zone = limits.0 - limits.1;
+        let zone_used = if zone.is_zero() {
+            Decimal::ZERO
+        } else {
+            Decimal::round_dp_with_strategy(
+                &((value - limits.1) / zone * dec!(100)),
+                1,
+                MidpointAwayFromZero,
+            )
+        };
+        ValueCheck {
+            value,
+            conformity: Conformity::of(value, limits),
+            to_upper: (limits.0 - value).normalize(),
+            to_lower: (value - limits.1).normalize(),
+            zone_used: zone_used.normalize(),
+            decision: uncertainty.map(|uncertainty| Decision::of(value, limits, uncertainty)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CheckQuery {
+    pub class: ToleranceClass,
+    pub values: Vec<Decimal>,
+    /// Расширенная неопределённость U, заданная числом или через средство измерения.
+    pub uncertainty: Option<Decimal>,
+    pub instrument: Option<&'static Instrument>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MeasureQuery {
+    pub class: ToleranceClass,
+    pub value: Decimal,
+    pub temperature: f64,
+    pub part: Material,
+    /// Материал шкалы средства измерения, находящегося при той же температуре.
+    pub instrument: Material,
+}
+
+pub fn parse_measure_input(input: &str) -> Option<MeasureQuery> {
+    let mut tokens = input.strip_prefix("measure")?.split_whitespace();
+    let class = parse_input(tokens.next()?)?;
+    let value: Decimal = replace_comma_with_dot(tokens.next()?).parse().ok()?;
+    let mut keys: Vec<String> = vec!["t".to_string()];
+    keys.extend(Material::param_keys("part", "p"));
+    keys.extend(Material::param_keys("instrument", "i"));
+    let allowed: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let params = Params::parse(tokens, &allowed)?;
+    Some(MeasureQuery {
+        class,
+        value,
+        temperature: params.required_number("t")?,
+        part: Material::from_params(&params, "part", "p")?,
+        instrument: Material::from_params(&params, "instrument", "i")?,
+    })
+}
+
+pub fn parse_check_input(input: &str) -> Option<CheckQuery> {
+    let regex = Regex::new(&format!(
+        r"^(check\s+)?(?P<class>[^\s=]+)(\s*=\s*|\s+)(?P<values>{0}(\s+{0})*)(\s+U=(?P<uncertainty>\S+))?$",
+        NUMBER_PATTERN
+    ))
+    .expect("Ошибка обработки RegEx");
+    let captures = regex.captures(input)?;
+    let values = captures["values"]
+        .split_whitespace()
+        .map(|value| replace_comma_with_dot(value).parse().ok())
+        .collect::<Option<Vec<Decimal>>>()?;
+    let (uncertainty, instrument) = match captures.name("uncertainty") {
+        Some(uncertainty) => match Instrument::find(uncertainty.as_str()) {
+            Some(instrument) => (Some(instrument.expanded_uncertainty()), Some(instrument)),
+            None => {
+                let uncertainty: Decimal =
+                    replace_comma_with_dot(uncertainty.as_str()).parse().ok()?;
+                if uncertainty.is_sign_negative() {
+                    return None;
+                }
+                (Some(uncertainty), None)
+            }
+        },
+        None => (None, None),
+    };
+    Some(CheckQuery {
+        class: parse_input(&captures["class"])?,
+        values,
+        uncertainty,
+        instrument,
+    })
+}
+
+/// Верхний и нижний предельные размеры поля допуска.
+pub fn search_class_limits(class: &ToleranceClass) -> Result<Option<(Decimal, Decimal)>, String> {
+    let (size, field, accuracy) = class;
+    let upper_lower_tol =
+        search_in_tables(&POOL, size, field, accuracy).map_err(|e| e.to_string())?;
+    Ok(upper_lower_tol.map(|(upper_tol, lower_tol)| {
+        let decimals = size_tols_map_decimal(size, (&upper_tol, &lower_tol));
+        let average_tol = calc_average_tol(&decimals.1, &decimals.2);
+        let sizes = calc_sizes_with_tols(decimals, average_tol);
+        (
+            sizes.1.parse().unwrap_or_default(),
+            sizes.2.parse().unwrap_or_default(),
+        )
+    }))
+}
+
+/// Приведение размера, измеренного при температуре `temperature`, к 20 °C
+/// с учётом расширения детали и шкалы средства измерения.
+pub fn correct_to_reference(
+    value: Decimal,
+    temperature: f64,
+    part_alpha: f64,
+    instrument_alpha: f64,
+) -> Decimal {
+    let delta = temperature - REFERENCE_TEMPERATURE;
+    let factor = Decimal::from_f64((1.0 + instrument_alpha * delta) / (1.0 + part_alpha * delta))
+        .unwrap_or(Decimal::ONE);
+    Decimal::round_dp_with_strategy(&(value * factor), 6, MidpointAwayFromZero).normalize()
+}
+
+pub fn handle_measure(measure_query: &MeasureQuery) {
+    match search_class_limits(&measure_query.class) {
+        Ok(Some(limits)) => print_measure(measure_query, limits),
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+pub fn handle_check(check_query: &CheckQuery) {
+    match search_class_limits(&check_query.class) {
+        Ok(Some(limits)) => {
+            let checks: Vec<ValueCheck> = check_query
+                .values
+                .iter()
+                .map(|value| ValueCheck::new(*value, limits, check_query.uncertainty))
+                .collect();
+            print_checks(check_query, limits, &checks)
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_checks(check_query: &CheckQuery, limits: (Decimal, Decimal), checks: &[ValueCheck]) {
+    let (size, field, accuracy) = &check_query.class;
+    let mut header = vec![
+        Cell::new(format!("{}{}{}", size, field, accuracy)),
+        Cell::new(format!("{} .. {}", limits.1, limits.0)),
+        Cell::new("до верхнего").fg(Color::Red),
+        Cell::new("до нижнего").fg(Color::Cyan),
+        Cell::new("% поля"),
+    ];
+    if let Some(uncertainty) = check_query.uncertainty {
+        header.push(Cell::new(format!("ISO 14253-1, U = {}", uncertainty)));
+    }
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header);
+    for check in checks {
+        let color = check.conformity.color();
+        let mut row = vec![
+            Cell::new(check.value.normalize()),
+            Cell::new(check.conformity.name()).fg(color),
+            Cell::new(check.to_upper).fg(Color::Red),
+            Cell::new(check.to_lower).fg(Color::Cyan),
+            Cell::new(check.zone_used).fg(color),
+        ];
+        if let Some(decision) = check.decision {
+            row.push(Cell::new(decision.name()).fg(decision.color()));
+        }
+        table_result.add_row(row);
+    }
+    println!("{table_result}");
+    if let Some(uncertainty) = check_query.uncertainty {
+        if let Some(instrument) = check_query.instrument {
+            println!(
+                "U получена из предельной погрешности ±{} ({}): U = 2·Δ/√3",
+                instrument.error, instrument.name
+            );
+        }
+        println!(
+            "Правило ISO 14253-1: соответствует при {} <= x <= {}, не соответствует при x < {} или x > {}",
+            (limits.1 + uncertainty).normalize(),
+            (limits.0 - uncertainty).normalize(),
+            (limits.1 - uncertainty).normalize(),
+            (limits.0 + uncertainty).normalize()
+        );
+    }
+    let within = checks
+        .iter()
+        .filter(|check| check.conformity == Conformity::Within)
+        .count();
+    println!("Годных: {} из {}", within, checks.len());
+}
+
+fn print_measure(measure_query: &MeasureQuery, limits: (Decimal, Decimal)) {
+    let (size, field, accuracy) = &measure_query.class;
+    let corrected = correct_to_reference(
+        measure_query.value,
+        measure_query.temperature,
+        measure_query.part.thermal_expansion,
+        measure_query.instrument.thermal_expansion,
+    );
+    let conformity = Conformity::of(corrected, limits);
+    let raw_conformity = Conformity::of(measure_query.value, limits);
+
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            format!("{}{}{}", size, field, accuracy),
+            String::new(),
+        ])
+        .add_row(vec![
+            Cell::new(format!(
+                "измерено при {} °C ({} / {})",
+                measure_query.temperature, measure_query.part.name, measure_query.instrument.name
+            )),
+            Cell::new(measure_query.value.normalize()),
+        ])
+        .add_row(vec![
+            Cell::new("поправка"),
+            Cell::new((corrected - measure_query.value).normalize()).fg(Color::Magenta),
+        ])
+        .add_row(vec![
+            Cell::new(format!("приведено к {} °C", REFERENCE_TEMPERATURE)),
+            Cell::new(corrected).fg(Color::Blue),
+        ])
+        .add_row(vec![
+            Cell::new("верхний размер"),
+            Cell::new(limits.0).fg(Color::Red),
+        ])
+        .add_row(vec![
+            Cell::new("нижний размер"),
+            Cell::new(limits.1).fg(Color::Cyan),
+        ])
+        .add_row(vec![
+            Cell::new("заключение"),
+            Cell::new(conformity.name()).fg(conformity.color()),
+        ]);
+    println!("{table_result}");
+    if raw_conformity != conformity {
+        println!(
+            "Внимание: без температурной поправки заключение было бы: {}",
+            raw_conformity.name()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_measure_input() {
+        let query = parse_measure_input("measure 50H7 50,012 t=12 part=aluminium").unwrap();
+        assert_eq!(
+            query.class,
+            ("50".to_string(), "H".to_string(), "7".to_string())
+        );
+        assert_eq!(query.value, dec!(50.012));
+        assert_eq!(query.temperature, 12.0);
+        assert_eq!(query.part.name, "aluminium");
+        assert_eq!(query.instrument.name, "steel");
+
+        assert_eq!(parse_measure_input("measure 50H7 50.012"), None);
+        assert_eq!(parse_measure_input("measure 50H7 t=12"), None);
+        assert_eq!(parse_measure_input("measure 50H7/g6 50.012 t=12"), None);
+    }
+
+    #[test]
+    fn test_parse_check_input() {
+        let class = ("50".to_string(), "H".to_string(), "7".to_string());
+        assert_eq!(
+            parse_check_input("50H7 = 50.012").unwrap(),
+            CheckQuery {
+                class: class.clone(),
+                values: vec![dec!(50.012)],
+                uncertainty: None,
+                instrument: None,
+            }
+        );
+        assert_eq!(
+            parse_check_input("50H7 50.012 50,030 49.998").unwrap(),
+            CheckQuery {
+                class: class.clone(),
+                values: vec![dec!(50.012), dec!(50.030), dec!(49.998)],
+                uncertainty: None,
+                instrument: None,
+            }
+        );
+        assert_eq!(
+            parse_check_input("check 50H7=50.012").unwrap(),
+            CheckQuery {
+                class: class.clone(),
+                values: vec![dec!(50.012)],
+                uncertainty: None,
+                instrument: None,
+            }
+        );
+        assert_eq!(
+            parse_check_input("50H7 50.012 U=0,002").unwrap(),
+            CheckQuery {
+                class: class.clone(),
+                values: vec![dec!(50.012)],
+                uncertainty: Some(dec!(0.002)),
+                instrument: None,
+            }
+        );
+        let query = parse_check_input("50H7 50.012 U=micrometer").unwrap();
+        assert_eq!(query.uncertainty, Some(dec!(0.0046)));
+        assert_eq!(query.instrument.unwrap().key, "micrometer");
+        assert_eq!(parse_check_input("50H7 50.012 U=-0.002"), None);
+        assert_eq!(parse_check_input("50H7 50.012 U=ruler"), None);
+        assert_eq!(parse_check_input("50H7"), None);
+        assert_eq!(parse_check_input("50H7 = "), None);
+        assert_eq!(parse_check_input("50H7/g6 50.012"), None);
+        assert_eq!(parse_check_input("50H7 50.012 abc"), None);
+        // Квалитет не должен отделяться от поля как замер: это обычный запрос поля допуска.
+        assert_eq!(parse_check_input("50H12"), None);
+        assert_eq!(parse_check_input("50h11"), None);
+        assert_eq!(
+            parse_input("50H12"),
+            Some(("50".to_string(), "H".to_string(), "12".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_value_check() {
+        let limits = (dec!(50.025), dec!(50));
+        let check = ValueCheck::new(dec!(50.012), limits, None);
+        assert_eq!(check.conformity, Conformity::Within);
+        assert_eq!(check.to_upper, dec!(0.013));
+        assert_eq!(check.to_lower, dec!(0.012));
+        assert_eq!(check.zone_used, dec!(48));
+
+        let check = ValueCheck::new(dec!(50.030), limits, None);
+        assert_eq!(check.conformity, Conformity::Oversize);
+        assert_eq!(check.to_upper, dec!(-0.005));
+        assert_eq!(check.zone_used, dec!(120));
+
+        let check = ValueCheck::new(dec!(49.998), limits, None);
+        assert_eq!(check.conformity, Conformity::Undersize);
+        assert_eq!(check.to_lower, dec!(-0.002));
+        assert_eq!(check.zone_used, dec!(-8));
+        assert_eq!(check.decision, None);
+
+        let check = ValueCheck::new(dec!(50.012), limits, Some(dec!(0.002)));
+        assert_eq!(check.decision, Some(Decision::Conforming));
+    }
+
+    #[test]
+    fn test_decision() {
+        let limits = (dec!(50.025), dec!(50));
+        let uncertainty = dec!(0.002);
+        assert_eq!(
+            Decision::of(dec!(50.002), limits, uncertainty),
+            Decision::Conforming
+        );
+        assert_eq!(
+            Decision::of(dec!(50.023), limits, uncertainty),
+            Decision::Conforming
+        );
+        assert_eq!(
+            Decision::of(dec!(50.001), limits, uncertainty),
+            Decision::Undecidable
+        );
+        assert_eq!(
+            Decision::of(dec!(50.024), limits, uncertainty),
+            Decision::Undecidable
+        );
+        assert_eq!(
+            Decision::of(dec!(50.027), limits, uncertainty),
+            Decision::Undecidable
+        );
+        assert_eq!(
+            Decision::of(dec!(49.998), limits, uncertainty),
+            Decision::Undecidable
+        );
+        assert_eq!(
+            Decision::of(dec!(50.0271), limits, uncertainty),
+            Decision::NonConforming
+        );
+        assert_eq!(
+            Decision::of(dec!(49.997), limits, uncertainty),
+            Decision::NonConforming
+        );
+        assert_eq!(
+            Decision::of(dec!(50.012), limits, dec!(0)),
+            Decision::Conforming
+        );
+    }
+
+    #[test]
+    fn test_correct_to_reference() {
+        assert_eq!(
+            correct_to_reference(dec!(50.012), 20.0, 23.5e-6, 11.5e-6),
+            dec!(50.012)
+        );
+        assert_eq!(
+            correct_to_reference(dec!(50.012), 12.0, 11.5e-6, 11.5e-6),
+            dec!(50.012)
+        );
+        // Алюминиевая деталь при 12 °C, стальной микрометр: +0.0048 мм
+        assert_eq!(
+            correct_to_reference(dec!(50), 12.0, 23.5e-6, 11.5e-6),
+            dec!(50.004801)
+        );
+        assert_eq!(
+            correct_to_reference(dec!(50), 12.0, 23.5e-6, 0.0),
+            dec!(50.009402)
+        );
+    }
+
+    #[test]
+    fn test_conformity() {
+        let limits = (dec!(50.025), dec!(50));
+        assert_eq!(Conformity::of(dec!(50.012), limits), Conformity::Within);
+        assert_eq!(Conformity::of(dec!(50.025), limits), Conformity::Within);
+        assert_eq!(Conformity::of(dec!(50), limits), Conformity::Within);
+        assert_eq!(Conformity::of(dec!(50.0251), limits), Conformity::Oversize);
+        assert_eq!(Conformity::of(dec!(49.998), limits), Conformity::Undersize);
+    }
+}
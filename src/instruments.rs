@@ -0,0 +1,123 @@
+use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy::MidpointAwayFromZero;
+use rust_decimal_macros::dec;
+
+/// Средство измерения с типичной предельной погрешностью.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Instrument {
+    pub key: &'static str,
+    pub name: &'static str,
+    /// Предельная погрешность, мм.
+    pub error: Decimal,
+    /// Диапазон измеряемых размеров, мм.
+    pub range: (Decimal, Decimal),
+    /// Применимость для наружных (валы) и внутренних (отверстия) размеров.
+    pub shaft: bool,
+    pub hole: bool,
+}
+
+pub const INSTRUMENTS: [Instrument; 6] = [
+    Instrument {
+        key: "caliper",
+        name: "штангенциркуль 0.01",
+        error: dec!(0.03),
+        range: (dec!(0), dec!(1000)),
+        shaft: true,
+        hole: true,
+    },
+    Instrument {
+        key: "micrometer",
+        name: "микрометр гладкий 0.01",
+        error: dec!(0.004),
+        range: (dec!(0), dec!(600)),
+        shaft: true,
+        hole: false,
+    },
+    Instrument {
+        key: "indicator",
+        name: "индикатор часового типа 0.01 на стойке",
+        error: dec!(0.01),
+        range: (dec!(0), dec!(250)),
+        shaft: true,
+        hole: false,
+    },
+    Instrument {
+        key: "head",
+        name: "измерительная головка 0.001 на стойке",
+        error: dec!(0.002),
+        range: (dec!(0), dec!(250)),
+        shaft: true,
+        hole: false,
+    },
+    Instrument {
+        key: "boregauge",
+        name: "индикаторный нутромер с головкой 0.001",
+        error: dec!(0.004),
+        range: (dec!(6), dec!(1000)),
+        shaft: false,
+        hole: true,
+    },
+    Instrument {
+        key: "cmm",
+        name: "координатно-измерительная машина",
+        error: dec!(0.003),
+        range: (dec!(0), dec!(1000)),
+        shaft: true,
+        hole: true,
+    },
+];
+
+impl Instrument {
+    pub fn find(key: &str) -> Option<&'static Instrument> {
+        INSTRUMENTS
+            .iter()
+            .find(|instrument| instrument.key.eq_ignore_ascii_case(key))
+    }
+
+    pub fn is_applicable(&self, size: Decimal, is_hole: bool) -> bool {
+        size >= self.range.0 && size <= self.range.1 && if is_hole { self.hole } else { self.shaft }
+    }
+
+    /// Расширенная неопределённость U (k = 2) при равномерном распределении
+    /// погрешности в пределах ±error: U = 2·error/√3.
+    pub fn expanded_uncertainty(&self) -> Decimal {
+        Decimal::round_dp_with_strategy(&(self.error * dec!(1.1547)), 4, MidpointAwayFromZero)
+            .normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_instrument() {
+        assert_eq!(Instrument::find("Micrometer").unwrap().error, dec!(0.004));
+        assert_eq!(Instrument::find("ruler"), None);
+    }
+
+    #[test]
+    fn test_is_applicable() {
+        let boregauge = Instrument::find("boregauge").unwrap();
+        assert!(boregauge.is_applicable(dec!(50), true));
+        assert!(!boregauge.is_applicable(dec!(50), false));
+        assert!(!boregauge.is_applicable(dec!(3), true));
+        let micrometer = Instrument::find("micrometer").unwrap();
+        assert!(micrometer.is_applicable(dec!(600), false));
+        assert!(!micrometer.is_applicable(dec!(601), false));
+    }
+
+    #[test]
+    fn test_expanded_uncertainty() {
+        assert_eq!(
+            Instrument::find("micrometer")
+                .unwrap()
+                .expanded_uncertainty(),
+            dec!(0.0046)
+        );
+        assert_eq!(
+            Instrument::find("caliper").unwrap().expanded_uncertainty(),
+            dec!(0.0346)
+        );
+    }
+}
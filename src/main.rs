@@ -20,9 +20,63 @@ use rust_decimal::Decimal;
 use rust_decimal::RoundingStrategy::MidpointAwayFromZero;
 use rust_decimal_macros::dec;
 
+use crate::capability::CapabilityQuery;
+use crate::compensation::ShimQuery;
+use crate::control_charts::SpcQuery;
+use crate::dimension_chain::ChainQuery;
+use crate::fit::FitQuery;
+use crate::fit_finder::FitWindowQuery;
+use crate::fit_matrix::MatrixQuery;
+use crate::fit_probability::ProbabilityQuery;
+use crate::fit_selection::LoadQuery;
+use crate::gauge_blocks::BlocksQuery;
+use crate::gauge_rr::GrrQuery;
+use crate::inspection::{CheckQuery, MeasureQuery, ToleranceClass};
+use crate::pin_gauges::PinsQuery;
+use crate::press_fit::PressFitQuery;
+use crate::sampling::SamplingQuery;
+use crate::selective_assembly::SelectQuery;
+use crate::stackup::StackQuery;
+use crate::thermal_fit::ThermalQuery;
+use crate::tolerance_allocation::AllocQuery;
 use crate::tolerance_table::ToleranceTable;
-
+use crate::vector_loop::LoopQuery;
+
+mod capability;
+mod compensation;
+mod control_charts;
+mod dimension_chain;
+mod fit;
+mod fit_conversion;
+mod fit_finder;
+mod fit_matrix;
+mod fit_probability;
+mod fit_selection;
+mod gauge_blocks;
+mod gauge_rr;
+mod gauges;
+mod inspection;
+mod instruments;
+mod materials;
+mod measurement_error;
+mod measurements;
+mod params;
+mod pin_gauges;
+mod preferred_fits;
+mod press_fit;
+mod sampling;
+mod selective_assembly;
+mod stackup;
+mod statistics;
+mod thermal_fit;
+mod tolerance_allocation;
 mod tolerance_table;
+mod vector_loop;
+
+const SIZE_PATTERN: &str =
+    r"([0-9]|[1-9][0-9]{1,2}|[12][0-9]{3}|30[0-9]{2}|31[0-4][0-9]|3150)([.,]\d{1,3})?";
+const ACCURACY_PATTERN: &str = r"[1-9]|1[0-8]";
+const NUMBER_PATTERN: &str = r"-?\d+([.,]\d+)?";
 
 lazy_static! {
     static ref POOL: Arc<Pool<SqliteConnectionManager>> = Arc::new(ToleranceTable::new().pool);
@@ -46,14 +100,69 @@ lazy_static! {
     ];
 }
 
+type SearchResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+enum Query {
+    Tolerance(String, String, String),
+    Fit(FitQuery),
+    FitWindow(FitWindowQuery),
+    PreferredFits(String),
+    Matrix(MatrixQuery),
+    Conversion(FitQuery),
+    Probability(ProbabilityQuery),
+    PressFit(PressFitQuery),
+    Load(LoadQuery),
+    Thermal(ThermalQuery),
+    Measure(MeasureQuery),
+    Check(CheckQuery),
+    Instruments(ToleranceClass),
+    Gauge(ToleranceClass),
+    Blocks(BlocksQuery),
+    Pins(PinsQuery),
+    Capability(CapabilityQuery),
+    Spc(SpcQuery),
+    Grr(GrrQuery),
+    Sampling(SamplingQuery),
+    Stack(StackQuery),
+    Chain(ChainQuery),
+    Shim(ShimQuery),
+    Select(SelectQuery),
+    Alloc(AllocQuery),
+    Loop(LoopQuery),
+}
+
 fn main() {
     loop {
-        let size_field_accuracy = get_input_values();
-        handle_search(
-            &size_field_accuracy.0,
-            &size_field_accuracy.1,
-            &size_field_accuracy.2,
-        )
+        match get_input_values() {
+            Query::Tolerance(size, field, accuracy) => handle_search(&size, &field, &accuracy),
+            Query::Fit(fit_query) => fit::handle_fit_search(&fit_query),
+            Query::FitWindow(window_query) => fit_finder::handle_fit_window_search(&window_query),
+            Query::PreferredFits(size) => preferred_fits::handle_preferred_fits(&size),
+            Query::Matrix(matrix_query) => fit_matrix::handle_matrix(&matrix_query),
+            Query::Conversion(fit_query) => fit_conversion::handle_conversion(&fit_query),
+            Query::Probability(probability_query) => {
+                fit_probability::handle_probability(&probability_query)
+            }
+            Query::PressFit(press_fit_query) => press_fit::handle_press_fit(&press_fit_query),
+            Query::Load(load_query) => fit_selection::handle_load(&load_query),
+            Query::Thermal(thermal_query) => thermal_fit::handle_thermal(&thermal_query),
+            Query::Measure(measure_query) => inspection::handle_measure(&measure_query),
+            Query::Check(check_query) => inspection::handle_check(&check_query),
+            Query::Instruments(class) => measurement_error::handle_instruments(&class),
+            Query::Gauge(class) => gauges::handle_gauge(&class),
+            Query::Blocks(blocks_query) => gauge_blocks::handle_blocks(&blocks_query),
+            Query::Pins(pins_query) => pin_gauges::handle_pins(&pins_query),
+            Query::Capability(capability_query) => capability::handle_capability(&capability_query),
+            Query::Spc(spc_query) => control_charts::handle_spc(&spc_query),
+            Query::Grr(grr_query) => gauge_rr::handle_grr(&grr_query),
+            Query::Sampling(sampling_query) => sampling::handle_sampling(&sampling_query),
+            Query::Stack(stack_query) => stackup::handle_stack(&stack_query),
+            Query::Chain(chain_query) => dimension_chain::handle_chain(&chain_query),
+            Query::Shim(shim_query) => compensation::handle_shim(&shim_query),
+            Query::Select(select_query) => selective_assembly::handle_select(&select_query),
+            Query::Alloc(alloc_query) => tolerance_allocation::handle_alloc(&alloc_query),
+            Query::Loop(loop_query) => vector_loop::handle_loop(&loop_query),
+        }
     }
 }
 
@@ -99,6 +208,89 @@ fn search_in_tables(
     result.unwrap_or(Ok(None))
 }
 
+fn search_tols_decimal(
+    size: &str,
+    field: &str,
+    accuracy: &str,
+) -> Result<Option<(Decimal, Decimal)>, Box<dyn Error + Send + Sync>> {
+    let upper_lower_tol = search_in_tables(&POOL, size, field, accuracy)?;
+    Ok(upper_lower_tol.map(|(upper_tol, lower_tol)| {
+        let decimals = size_tols_map_decimal(size, (&upper_tol, &lower_tol));
+        (decimals.1, decimals.2)
+    }))
+}
+
+/// Величина допуска квалитета IT как ширина поля H.
+fn search_it_value(size: &str, grade: u8) -> SearchResult<Option<Decimal>> {
+    let tols = search_tols_decimal(size, "H", &grade.to_string())?;
+    Ok(tols.map(|(upper, lower)| (upper - lower).normalize()))
+}
+
+fn search_all_in_tables(
+    pool: &POOL,
+    size: &str,
+    tables: &[Cow<'static, str>],
+) -> SearchResult<Vec<(String, Decimal, Decimal)>> {
+    let results = tables
+        .par_iter()
+        .map(|table_name| search_all_in_table(pool, size, table_name))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(results
+        .into_iter()
+        .flatten()
+        .map(|(class, upper_tol, lower_tol)| {
+            let decimals = size_tols_map_decimal(size, (&upper_tol, &lower_tol));
+            (class, decimals.1, decimals.2)
+        })
+        .collect())
+}
+
+fn search_all_in_table(
+    pool: &POOL,
+    size: &str,
+    table_name: &str,
+) -> SearchResult<Vec<(String, String, String)>> {
+    let query = format!(
+        "SELECT * FROM {1} WHERE MIN_DIA <= {0} AND MAX_DIA >= {0}",
+        size, table_name
+    );
+    let connection = pool.get().expect("Не удалось получить пул соединения БД");
+    let mut stmt = connection.prepare(&query)?;
+    let classes: Vec<(usize, String)> = stmt
+        .column_names()
+        .into_iter()
+        .enumerate()
+        .filter(|(_, name)| *name != "MIN_DIA" && *name != "MAX_DIA")
+        .map(|(index, name)| (index, name.to_string()))
+        .collect();
+
+    let rows = stmt
+        .query_map([], |row| {
+            classes
+                .iter()
+                .map(|(index, _)| row.get::<_, Option<String>>(*index))
+                .collect::<Result<Vec<_>, _>>()
+        })?
+        .take(2)
+        .collect::<Result<Vec<_>, _>>()?;
+    if rows.len() < 2 {
+        return Ok(Vec::new());
+    }
+
+    Ok(classes
+        .into_iter()
+        .zip(rows[0].iter().zip(rows[1].iter()))
+        .filter_map(|((_, class), tols)| match tols {
+            (Some(upper_tol), Some(lower_tol))
+                if !upper_tol.trim().is_empty() && !lower_tol.trim().is_empty() =>
+            {
+                Some((class, upper_tol.clone(), lower_tol.clone()))
+            }
+            _ => None,
+        })
+        .collect())
+}
+
 fn search_in_table(
     pool: &POOL,
     size: &str,
@@ -137,7 +329,7 @@ fn search_in_table(
     Ok(Some((upper_tol, lower_tol)))
 }
 
-fn get_input_values() -> (String, String, String) {
+fn get_input_values() -> Query {
     loop {
         print!("(Для справки введите -h или help) Введите данные: ");
         io::stdout().flush().expect("Ошибка обработки вызова flush");
@@ -147,8 +339,83 @@ fn get_input_values() -> (String, String, String) {
             print_help_info();
             continue;
         }
+        if let Some(loop_query) = vector_loop::parse_loop_input(input) {
+            return Query::Loop(loop_query);
+        }
+        if let Some(alloc_query) = tolerance_allocation::parse_alloc_input(input) {
+            return Query::Alloc(alloc_query);
+        }
+        if let Some(select_query) = selective_assembly::parse_select_input(input) {
+            return Query::Select(select_query);
+        }
+        if let Some(shim_query) = compensation::parse_shim_input(input) {
+            return Query::Shim(shim_query);
+        }
+        if let Some(chain_query) = dimension_chain::parse_chain_input(input) {
+            return Query::Chain(chain_query);
+        }
+        if let Some(stack_query) = stackup::parse_stack_input(input) {
+            return Query::Stack(stack_query);
+        }
+        if let Some(sampling_query) = sampling::parse_sampling_input(input) {
+            return Query::Sampling(sampling_query);
+        }
+        if let Some(grr_query) = gauge_rr::parse_grr_input(input) {
+            return Query::Grr(grr_query);
+        }
+        if let Some(spc_query) = control_charts::parse_spc_input(input) {
+            return Query::Spc(spc_query);
+        }
+        if let Some(capability_query) = capability::parse_capability_input(input) {
+            return Query::Capability(capability_query);
+        }
+        if let Some(pins_query) = pin_gauges::parse_pins_input(input) {
+            return Query::Pins(pins_query);
+        }
+        if let Some(blocks_query) = gauge_blocks::parse_blocks_input(input) {
+            return Query::Blocks(blocks_query);
+        }
+        if let Some(class) = gauges::parse_gauge_input(input) {
+            return Query::Gauge(class);
+        }
+        if let Some(class) = measurement_error::parse_instruments_input(input) {
+            return Query::Instruments(class);
+        }
+        if let Some(check_query) = inspection::parse_check_input(input) {
+            return Query::Check(check_query);
+        }
+        if let Some(measure_query) = inspection::parse_measure_input(input) {
+            return Query::Measure(measure_query);
+        }
+        if let Some(thermal_query) = thermal_fit::parse_thermal_input(input) {
+            return Query::Thermal(thermal_query);
+        }
+        if let Some(load_query) = fit_selection::parse_load_input(input) {
+            return Query::Load(load_query);
+        }
+        if let Some(press_fit_query) = press_fit::parse_press_fit_input(input) {
+            return Query::PressFit(press_fit_query);
+        }
+        if let Some(probability_query) = fit_probability::parse_probability_input(input) {
+            return Query::Probability(probability_query);
+        }
+        if let Some(fit_query) = fit_conversion::parse_conversion_input(input) {
+            return Query::Conversion(fit_query);
+        }
+        if let Some(matrix_query) = fit_matrix::parse_matrix_input(input) {
+            return Query::Matrix(matrix_query);
+        }
+        if let Some(size) = preferred_fits::parse_preferred_fits_input(input) {
+            return Query::PreferredFits(size);
+        }
+        if let Some(window_query) = fit_finder::parse_fit_window_input(input) {
+            return Query::FitWindow(window_query);
+        }
+        if let Some(fit_query) = fit::parse_fit_input(input) {
+            return Query::Fit(fit_query);
+        }
         if let Some((size, field, accuracy)) = parse_input(input) {
-            return (size, field, accuracy);
+            return Query::Tolerance(size, field, accuracy);
         } else {
             println!("Некорректный ввод. Пожалуйста, попробуйте снова");
         }
@@ -164,7 +431,11 @@ fn read_input() -> String {
 }
 
 fn parse_input(input: &str) -> Option<(String, String, String)> {
-    let regex = Regex::new(r"^(?P<size>([0-9]|[1-9][0-9]{1,2}|[12][0-9]{3}|30[0-9]{2}|31[0-4][0-9]|3150)([.,]\d{1,3})?)(?P<field>[a-z]+|[A-Z]+)(?P<accuracy>[1-9]|1[0-8])$").expect("Ошибка обработки RegEx");
+    let regex = Regex::new(&format!(
+        r"^(?P<size>{})(?P<field>[a-z]+|[A-Z]+)(?P<accuracy>{})$",
+        SIZE_PATTERN, ACCURACY_PATTERN
+    ))
+    .expect("Ошибка обработки RegEx");
     if let Some(captures) = regex.captures(input) {
         let size = replace_comma_with_dot(&captures["size"]).to_string();
         let field = captures["field"].to_string();
@@ -183,6 +454,16 @@ fn replace_comma_with_dot(size: &str) -> String {
     }
 }
 
+fn split_class(class: &str) -> (String, String) {
+    let accuracy_start = class
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(class.len());
+    (
+        class[..accuracy_start].to_string(),
+        class[accuracy_start..].to_string(),
+    )
+}
+
 fn is_uppercase(field: &str) -> bool {
     field.chars().all(|c| c.is_uppercase())
 }
@@ -262,7 +543,173 @@ fn print_help_info() {
             Cell::new("верхний допуск").fg(Color::Magenta),
             Cell::new("нижний допуск").fg(Color::Yellow),
         ]);
-    println!("{help_info}")
+    println!("{help_info}");
+
+    let mut formats_info = Table::new();
+    formats_info
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["формат ввода", "пример", "описание"])
+        .add_row(vec![
+            "<размер><поле><квалитет>",
+            "50H7",
+            "допуски одного поля",
+        ])
+        .add_row(vec![
+            "<размер><отверстие>/<вал>",
+            "50H7/g6",
+            "расчёт посадки: зазоры, натяги, тип посадки",
+        ])
+        .add_row(vec![
+            "find <размер> <Smin> <Smax> [basis=all|main] [limit=10]",
+            "find 40 0.02 0.06",
+            "подбор посадок под требуемый диапазон зазора (натяг - отрицательный зазор): \
+             все сочетания полей, basis=main - только системы отверстия и вала; limit= - \
+             сколько показать",
+        ])
+        .add_row(vec![
+            "fits <размер>",
+            "fits 40",
+            "предпочтительные посадки ISO 286 с областями применения",
+        ])
+        .add_row(vec![
+            "matrix <размер> <квалитеты> [отверстия] [валы]",
+            "matrix 40 6-8 H,G f,g,h,k",
+            "таблица посадок: все поля отверстий на все поля валов",
+        ])
+        .add_row(vec![
+            "convert <посадка>",
+            "convert 50H7/g6",
+            "пересчёт посадки из системы отверстия в систему вала и обратно",
+        ])
+        .add_row(vec![
+            "prob <посадка> [закон отв.] [закон вала]",
+            "prob 50H7/k6 normal uniform",
+            "вероятность зазора и натяга; законы: normal, normal:<k>, uniform, triangular \
+             (Симпсона), shifted:<α>:<σ/T> (смещение центра на α·T/2, σ в долях допуска)",
+        ])
+        .add_row(vec![
+            "press <посадка> D= L= [mu=] [di=] [hub=] [shaft=]",
+            "press 60H7/s6 D=100 L=50 mu=0.12 hub=castiron",
+            "прочность соединения с натягом (Ламе, DIN 7190); материалы: steel, castiron, \
+             bronze, brass, aluminium, titanium; уточнение: Eh= nuh= Reh= alphah= (Es= ...), \
+             шероховатость rzh= rzs= в мкм",
+        ])
+        .add_row(vec![
+            "load <размер> T= | F= D= L= [S=] [Sf=] [hole=]",
+            "load 60 T=800 D=100 L=50 hole=7",
+            "подбор посадки с натягом по моменту (Н·м) или осевой силе (Н); S - запас \
+             сцепления, Sf - запас по Re, параметры соединения как у press",
+        ])
+        .add_row(vec![
+            "thermal <посадка> [hole=] [shaft=] [th=] [ts=] [from= to= step=]",
+            "thermal 50H7/g6 hole=aluminium th=-40",
+            "посадка при рабочих температурах отверстия (th) и вала (ts) и таблица \
+             зазоров по диапазону температур (не более 200 строк); alphah= alphas= в 1e-6/°C",
+        ])
+        .add_row(vec![
+            "measure <поле> <размер> t= [part=] [instrument=]",
+            "measure 50H7 50.012 t=12 part=aluminium",
+            "приведение измеренного размера к 20 °C и проверка по пределам поля",
+        ])
+        .add_row(vec![
+            "<поле> [=] <размер> [размер ...] [U=]",
+            "50H7 = 50.012 | 50H7 50.012 50.030 49.998 U=micrometer",
+            "проверка измеренных размеров: годен, больше или меньше предела; U - расширенная \
+             неопределённость (мм или средство: caliper, micrometer, indicator, head, \
+             boregauge, cmm) для решения по ISO 14253-1",
+        ])
+        .add_row(vec![
+            "instruments <поле>",
+            "instruments 50H7",
+            "допускаемая погрешность измерения по ГОСТ 8.051 и подбор средств измерения",
+        ])
+        .add_row(vec![
+            "gauge <поле>",
+            "gauge 50H7 | gauge 50k6",
+            "размеры калибров ПР и НЕ по ГОСТ 24853 (ISO 1938): пробки для отверстий, \
+             скобы и контрольные калибры для валов, квалитеты 6-12",
+        ])
+        .add_row(vec![
+            "blocks <размер> | <поле> upper|lower [set=] [exclude=]",
+            "blocks 25H6 upper set=112 exclude=1.003;1.5",
+            "блок концевых мер из наименьшего числа мер; set: 87, 103, 112, 47 или файл \
+             (размер на строку, метки worn/missing), exclude - недоступные меры через ;",
+        ])
+        .add_row(vec![
+            "pins <отверстие> [step=0.01|0.001] [class=ZZ|Z|X]",
+            "pins 6H7 step=0.001 class=X",
+            "подбор штифтов ПР (набор «плюс») и НЕ (набор «минус») с учётом их допуска",
+        ])
+        .add_row(vec![
+            "cpk <поле> <размер> [размер ...] | file= [subgroup=]",
+            "cpk 30h6 file=shafts.csv subgroup=5",
+            "воспроизводимость процесса: Cp, Cpk, Pp, Ppk, прогноз ppm за пределами и \
+             гистограмма; CSV с разделителем ; , или табуляцией",
+        ])
+        .add_row(vec![
+            "spc <поле> <размер> [размер ...] | file= [chart=xr|imr] [subgroup=] [svg=]",
+            "spc 30h6 file=shafts.csv chart=xr svg=chart.svg",
+            "контрольные карты X̄-R (строка файла - подгруппа) или I-MR с проверкой правил \
+             Western Electric/Nelson, пределами поля допуска и экспортом в SVG",
+        ])
+        .add_row(vec![
+            "grr <поле> file=",
+            "grr 10h9 file=grr.csv",
+            "сходимость и воспроизводимость (Gauge R&R) методами ANOVA и средних и размахов: \
+             %GRR от допуска, ndc и заключение; строка CSV - оператор, деталь, замеры",
+        ])
+        .add_row(vec![
+            "sampling <поле> lot= aql= [level=S-1..S-4|I|II|III] [defects=] \
+             [размер ...] | [file=]",
+            "sampling 20h9 lot=500 aql=1 file=lot.csv",
+            "выборочный приёмочный контроль партии: план ISO 2859-1 (Ac/Re) и решение по \
+             партии по замерам или числу дефектов",
+        ])
+        .add_row(vec![
+            "stack <±звено> [±звено ...] [dist=] [n=] [seed=] [min=] [max=]",
+            "stack +60h9 -25±0.05 -30js8@uniform -4.5+0.1-0.05 min=0 max=0.6",
+            "размерная цепь: замыкающее звено методами максимума-минимума, RSS и Монте-Карло, \
+             чувствительность и вклад звеньев; звено - поле допуска или размер с отклонениями, \
+             @закон - распределение звена",
+        ])
+        .add_row(vec![
+            "chain <±звено> [±звено ...] [closing=] [method=tolerance|precision] [calc=max|prob] \
+             [risk=0.27] [dist=] [adjust=N]",
+            "chain +100H -40h -58 closing=2+0.6-0.2 method=precision calc=prob",
+            "размерная цепь по ГОСТ 16320: без closing= - прямая задача (замыкающее звено \
+             на максимум-минимум и вероятностно), с closing= - обратная задача способом равных \
+             допусков или одного квалитета с увязочным звеном; звено - номинал с h|H|js",
+        ])
+        .add_row(vec![
+            "shim <±звено> [±звено ...] closing= [comp=N]",
+            "shim +120±0.1 -50h9 -68±0.05 -2±0.01 closing=0+0.2-0",
+            "метод регулирования: наибольшая компенсация, число ступеней и толщины прокладок \
+             неподвижного компенсатора (звено comp=, по умолчанию последнее) по замыкающему \
+             размеру без прокладки",
+        ])
+        .add_row(vec![
+            "select <посадка> groups=N | tol=",
+            "select 20H6/h6 tol=0.005",
+            "селективная сборка: число сортировочных групп и границы групп отверстия и вала, \
+             предельные зазоры (натяги) в группах",
+        ])
+        .add_row(vec![
+            "alloc <±звено[@обработка|@a+b/T^k]> [...] closing= [calc=max|prob] [risk=] [dist=]",
+            "alloc +100H@bore -40h@grind -58@2+40/T closing=2+0.6-0.2 calc=prob",
+            "назначение допусков по минимуму стоимости: квалитеты звеньев, при которых \
+             замыкающее звено укладывается в closing=; обработка - turn, bore, mill, drill, \
+             ream, grind, hone (по умолчанию turn) или модель стоимости a + b/T^k, T в мкм",
+        ])
+        .add_row(vec![
+            "loop <длина@угол[±допуск]|float:<посадка>|pos:<⌀>> [...] [dir=] [dist=] [n=] [seed=] \
+             [min=] [max=]",
+            "loop 50±0.1@30±0.5 40h9@-90 float:10H7/g6 pos:0.1 dir=90 min=0",
+            "плоский векторный контур: смещение конечной точки и зазор по направлению dir= \
+             линеаризованно (якобиан) и методом Монте-Карло; float: - плавание пальца \
+             в отверстии, pos: - позиционный допуск",
+        ]);
+    println!("{formats_info}")
 }
 
 #[cfg(test)]
@@ -495,6 +942,14 @@ mod tests {
         assert_eq!(replace_comma_with_dot("10.01"), "10.01")
     }
 
+    #[test]
+    fn test_split_class() {
+        assert_eq!(split_class("H7"), ("H".to_string(), "7".to_string()));
+        assert_eq!(split_class("js18"), ("js".to_string(), "18".to_string()));
+        assert_eq!(split_class("CD10"), ("CD".to_string(), "10".to_string()));
+        assert_eq!(split_class("zc"), ("zc".to_string(), "".to_string()));
+    }
+
     #[test]
     fn test_is_uppercase() {
         assert!(is_uppercase("A"));
@@ -0,0 +1,141 @@
+use crate::params::Params;
+
+/// Нормальная температура, к которой отнесены размеры и допуски (ISO 1), °C.
+pub const REFERENCE_TEMPERATURE: f64 = 20.0;
+
+/// Механические и теплофизические свойства материала детали.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Material {
+    pub name: &'static str,
+    /// Модуль упругости E, МПа.
+    pub elasticity: f64,
+    /// Коэффициент Пуассона ν.
+    pub poisson: f64,
+    /// Предел текучести Re (для чугуна - предел прочности), МПа.
+    pub yield_strength: f64,
+    /// Коэффициент линейного расширения α, 1/°C.
+    pub thermal_expansion: f64,
+}
+
+pub const MATERIALS: [Material; 6] = [
+    Material {
+        name: "steel",
+        elasticity: 210000.0,
+        poisson: 0.3,
+        yield_strength: 355.0,
+        thermal_expansion: 11.5e-6,
+    },
+    Material {
+        name: "castiron",
+        elasticity: 110000.0,
+        poisson: 0.25,
+        yield_strength: 250.0,
+        thermal_expansion: 10.5e-6,
+    },
+    Material {
+        name: "bronze",
+        elasticity: 110000.0,
+        poisson: 0.35,
+        yield_strength: 200.0,
+        thermal_expansion: 18e-6,
+    },
+    Material {
+        name: "brass",
+        elasticity: 100000.0,
+        poisson: 0.34,
+        yield_strength: 250.0,
+        thermal_expansion: 19e-6,
+    },
+    Material {
+        name: "aluminium",
+        elasticity: 70000.0,
+        poisson: 0.33,
+        yield_strength: 200.0,
+        thermal_expansion: 23.5e-6,
+    },
+    Material {
+        name: "titanium",
+        elasticity: 110000.0,
+        poisson: 0.33,
+        yield_strength: 830.0,
+        thermal_expansion: 8.9e-6,
+    },
+];
+
+impl Material {
+    pub fn find(name: &str) -> Option<Material> {
+        MATERIALS
+            .iter()
+            .find(|material| material.name.eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    /// Материал из параметра `key` (по умолчанию сталь) с уточнением свойств
+    /// параметрами `E<suffix>`, `nu<suffix>`, `Re<suffix>`, `alpha<suffix>`.
+    pub fn from_params(params: &Params, key: &str, suffix: &str) -> Option<Material> {
+        let material = match params.text(key) {
+            Some(name) => Material::find(name)?,
+            None => MATERIALS[0],
+        };
+        Some(Material {
+            elasticity: params.number_or(&format!("E{}", suffix), material.elasticity)?,
+            poisson: params.number_or(&format!("nu{}", suffix), material.poisson)?,
+            yield_strength: params.number_or(&format!("Re{}", suffix), material.yield_strength)?,
+            thermal_expansion: params
+                .number(&format!("alpha{}", suffix))?
+                .map(|alpha| alpha * 1e-6)
+                .unwrap_or(material.thermal_expansion),
+            ..material
+        })
+    }
+
+    pub fn param_keys(key: &'static str, suffix: &'static str) -> [String; 5] {
+        [
+            key.to_string(),
+            format!("E{}", suffix),
+            format!("nu{}", suffix),
+            format!("Re{}", suffix),
+            format!("alpha{}", suffix),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_material() {
+        assert_eq!(Material::find("Steel").unwrap().elasticity, 210000.0);
+        assert_eq!(
+            Material::find("aluminium").unwrap().thermal_expansion,
+            23.5e-6
+        );
+        assert_eq!(Material::find("wood"), None);
+    }
+
+    #[test]
+    fn test_material_from_params() {
+        let keys = Material::param_keys("hub", "h");
+        let allowed: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let params = Params::parse(
+            "hub=aluminium Reh=280 alphah=23".split_whitespace(),
+            &allowed,
+        )
+        .unwrap();
+        let material = Material::from_params(&params, "hub", "h").unwrap();
+        assert_eq!(material.name, "aluminium");
+        assert_eq!(material.elasticity, 70000.0);
+        assert_eq!(material.yield_strength, 280.0);
+        assert_eq!(material.thermal_expansion, 23e-6);
+
+        let params = Params::parse("".split_whitespace(), &allowed).unwrap();
+        assert_eq!(
+            Material::from_params(&params, "hub", "h").unwrap(),
+            MATERIALS[0]
+        );
+
+        let params = Params::parse("hub=wood".split_whitespace(), &allowed).unwrap();
+        assert_eq!(Material::from_params(&params, "hub", "h"), None);
+    }
+}
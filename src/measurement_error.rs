@@ -0,0 +1,215 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::inspection::{search_class_limits, ToleranceClass};
+use crate::instruments::{Instrument, INSTRUMENTS};
+use crate::{is_uppercase, parse_input};
+
+/// Верхние границы интервалов размеров до 500 мм (ГОСТ 8.051-81, ГОСТ 24853-81), мм.
+const SIZE_RANGES: [Decimal; 13] = [
+    dec!(3),
+    dec!(6),
+    dec!(10),
+    dec!(18),
+    dec!(30),
+    dec!(50),
+    dec!(80),
+    dec!(120),
+    dec!(180),
+    dec!(250),
+    dec!(315),
+    dec!(400),
+    dec!(500),
+];
+
+/// Допускаемые погрешности измерения δ, мкм, для квалитетов 2-17 по интервалам размеров.
+#[rustfmt::skip]
+const PERMISSIBLE_ERRORS: [(u8, [f64; 13]); 16] = [
+    (2, [0.4, 0.6, 0.6, 0.8, 1.0, 1.0, 1.2, 1.6, 2.0, 2.8, 3.0, 3.0, 4.0]),
+    (3, [0.8, 1.0, 1.0, 1.2, 1.4, 1.4, 1.8, 2.0, 2.8, 4.0, 4.0, 4.0, 5.0]),
+    (4, [1.0, 1.4, 1.4, 1.6, 2.0, 2.4, 2.8, 3.0, 4.0, 5.0, 5.0, 6.0, 6.0]),
+    (5, [1.4, 1.6, 2.0, 2.8, 3.0, 4.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 9.0]),
+    (6, [1.8, 2.0, 2.0, 3.0, 4.0, 5.0, 5.0, 6.0, 7.0, 8.0, 10.0, 10.0, 12.0]),
+    (7, [3.0, 3.0, 4.0, 5.0, 6.0, 7.0, 9.0, 10.0, 12.0, 12.0, 14.0, 16.0, 18.0]),
+    (8, [3.0, 4.0, 5.0, 7.0, 8.0, 10.0, 12.0, 12.0, 16.0, 18.0, 20.0, 24.0, 26.0]),
+    (9, [6.0, 8.0, 9.0, 10.0, 12.0, 16.0, 18.0, 20.0, 30.0, 30.0, 30.0, 40.0, 40.0]),
+    (10, [8.0, 10.0, 12.0, 14.0, 18.0, 20.0, 30.0, 30.0, 40.0, 40.0, 50.0, 50.0, 50.0]),
+    (11, [12.0, 16.0, 18.0, 30.0, 30.0, 40.0, 40.0, 50.0, 50.0, 60.0, 70.0, 80.0, 80.0]),
+    (12, [20.0, 30.0, 30.0, 40.0, 50.0, 50.0, 60.0, 70.0, 80.0, 100.0, 120.0, 120.0, 140.0]),
+    (13, [30.0, 40.0, 50.0, 60.0, 70.0, 80.0, 100.0, 120.0, 140.0, 160.0, 180.0, 180.0, 200.0]),
+    (14, [50.0, 60.0, 80.0, 90.0, 120.0, 140.0, 160.0, 180.0, 200.0, 240.0, 260.0, 280.0, 320.0]),
+    (15, [80.0, 100.0, 120.0, 140.0, 180.0, 200.0, 240.0, 280.0, 320.0, 380.0, 440.0, 460.0, 500.0]),
+    (16, [120.0, 160.0, 200.0, 240.0, 280.0, 320.0, 400.0, 440.0, 500.0, 600.0, 640.0, 720.0, 800.0]),
+    (17, [200.0, 240.0, 300.0, 380.0, 440.0, 500.0, 600.0, 700.0, 800.0, 1000.0, 1100.0, 1200.0, 1400.0]),
+];
+
+/// Номер интервала размеров до 500 мм.
+pub fn size_range(size: Decimal) -> Option<usize> {
+    SIZE_RANGES.iter().position(|upper| size <= *upper)
+}
+
+pub fn parse_instruments_input(input: &str) -> Option<ToleranceClass> {
+    parse_input(input.strip_prefix("instruments")?.trim_start())
+}
+
+/// Допускаемая погрешность измерения по ГОСТ 8.051-81, мм.
+/// Стандарт распространяется на размеры до 500 мм и квалитеты 2-17.
+pub fn permissible_error(size: Decimal, grade: u8) -> Option<Decimal> {
+    let range = size_range(size)?;
+    let (_, errors) = PERMISSIBLE_ERRORS.iter().find(|(g, _)| *g == grade)?;
+    Decimal::from_f64(errors[range]).map(|error| (error / dec!(1000)).normalize())
+}
+
+/// Применимые к размеру средства измерения: сначала пригодные по точности
+/// в порядке убывания погрешности (самые простые), затем остальные.
+pub fn recommend_instruments(
+    size: Decimal,
+    is_hole: bool,
+    permissible_error: Decimal,
+) -> Vec<(&'static Instrument, bool)> {
+    let mut instruments: Vec<(&'static Instrument, bool)> = INSTRUMENTS
+        .iter()
+        .filter(|instrument| instrument.is_applicable(size, is_hole))
+        .map(|instrument| (instrument, instrument.error <= permissible_error))
+        .collect();
+    instruments.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.error.cmp(&a.0.error)));
+    instruments
+}
+
+pub fn handle_instruments(class: &ToleranceClass) {
+    let (size, field, accuracy) = class;
+    let size_decimal: Decimal = size.parse().unwrap_or_default();
+    let error = match accuracy
+        .parse()
+        .ok()
+        .and_then(|grade| permissible_error(size_decimal, grade))
+    {
+        Some(error) => error,
+        None => {
+            println!(
+                "ГОСТ 8.051-81 устанавливает погрешности для размеров до 500 мм и квалитетов 2-17"
+            );
+            return;
+        }
+    };
+    match search_class_limits(class) {
+        Ok(Some(limits)) => {
+            let instruments = recommend_instruments(size_decimal, is_uppercase(field), error);
+            print_instruments(class, limits, error, &instruments)
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_instruments(
+    class: &ToleranceClass,
+    limits: (Decimal, Decimal),
+    error: Decimal,
+    instruments: &[(&'static Instrument, bool)],
+) {
+    let (size, field, accuracy) = class;
+    let tolerance = (limits.0 - limits.1).normalize();
+    let mut table_class = Table::new();
+    table_class
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new(format!("{}{}{}", size, field, accuracy)),
+            Cell::new(format!("{} .. {}", limits.1, limits.0)),
+        ])
+        .add_row(vec![Cell::new("допуск (IT)"), Cell::new(tolerance)])
+        .add_row(vec![
+            Cell::new("допускаемая погрешность δ (ГОСТ 8.051)"),
+            Cell::new(error).fg(Color::Yellow),
+        ]);
+    if !tolerance.is_zero() {
+        table_class.add_row(vec![
+            Cell::new("δ / IT, %"),
+            Cell::new((error / tolerance * dec!(100)).round_dp(1).normalize()),
+        ]);
+    }
+    println!("{table_class}");
+
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            "средство измерения",
+            "предельная погрешность",
+            "вывод",
+        ]);
+    for (instrument, suitable) in instruments {
+        let (conclusion, color) = if *suitable {
+            ("пригодно", Color::Green)
+        } else {
+            ("точность недостаточна", Color::Red)
+        };
+        table_result.add_row(vec![
+            Cell::new(instrument.name),
+            Cell::new(instrument.error).fg(color),
+            Cell::new(conclusion).fg(color),
+        ]);
+    }
+    println!("{table_result}");
+    if !instruments.iter().any(|(_, suitable)| *suitable) {
+        println!(
+            "Ни одно средство из списка не обеспечивает погрешность δ = {}",
+            error
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_instruments_input() {
+        assert_eq!(
+            parse_instruments_input("instruments 50H7").unwrap(),
+            ("50".to_string(), "H".to_string(), "7".to_string())
+        );
+        assert_eq!(parse_instruments_input("50H7"), None);
+        assert_eq!(parse_instruments_input("instruments 50H7/g6"), None);
+    }
+
+    #[test]
+    fn test_permissible_error() {
+        assert_eq!(permissible_error(dec!(50), 7), Some(dec!(0.007)));
+        assert_eq!(permissible_error(dec!(3), 6), Some(dec!(0.0018)));
+        assert_eq!(permissible_error(dec!(3.5), 6), Some(dec!(0.002)));
+        assert_eq!(permissible_error(dec!(500), 17), Some(dec!(1.4)));
+        assert_eq!(permissible_error(dec!(501), 7), None);
+        assert_eq!(permissible_error(dec!(50), 1), None);
+        assert_eq!(permissible_error(dec!(50), 18), None);
+    }
+
+    #[test]
+    fn test_recommend_instruments() {
+        let keys = |instruments: Vec<(&'static Instrument, bool)>| {
+            instruments
+                .iter()
+                .map(|(instrument, suitable)| (instrument.key, *suitable))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            keys(recommend_instruments(dec!(50), true, dec!(0.007))),
+            vec![("boregauge", true), ("cmm", true), ("caliper", false)]
+        );
+        assert_eq!(
+            keys(recommend_instruments(dec!(50), false, dec!(0.03))),
+            vec![
+                ("caliper", true),
+                ("indicator", true),
+                ("micrometer", true),
+                ("cmm", true),
+                ("head", true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use std::fs;
+
+use crate::params::Params;
+
+/// Источник измеренных значений: перечень в строке ввода или CSV-файл.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Samples {
+    Values(Vec<f64>),
+    File(String),
+}
+
+impl Samples {
+    /// Значения по строкам: строка файла - подгруппа, перечень в строке ввода - одна строка.
+    pub fn load(&self) -> Result<Vec<Vec<f64>>, String> {
+        match self {
+            Samples::Values(values) => Ok(vec![values.clone()]),
+            Samples::File(path) => fs::read_to_string(path)
+                .map(|content| parse_csv(&content))
+                .map_err(|e| format!("не удалось прочитать файл '{}': {}", path, e)),
+        }
+    }
+}
+
+/// Разбирает токены после команды: числа - значения, `ключ=значение` - параметры.
+/// Значения задаются либо числами, либо параметром `file=`, но не тем и другим сразу.
+pub fn parse_samples<'a>(
+    tokens: impl Iterator<Item = &'a str>,
+    allowed: &[&str],
+) -> Option<(Samples, Params)> {
+    let (params, values): (Vec<&str>, Vec<&str>) = tokens.partition(|token| token.contains('='));
+    let mut keys = allowed.to_vec();
+    keys.push("file");
+    let params = Params::parse(params.into_iter(), &keys)?;
+    let values = values
+        .iter()
+        .map(|value| value.replace(',', ".").parse().ok())
+        .collect::<Option<Vec<f64>>>()?;
+    let samples = match (params.text("file"), values.is_empty()) {
+        (Some(path), true) => Samples::File(path.to_string()),
+        (None, false) => Samples::Values(values),
+        _ => return None,
+    };
+    Some((samples, params))
+}
+
+/// Ячейки строки CSV. Разделитель `;` (тогда `,` - десятичная запятая),
+/// табуляция или `,`.
+pub fn split_row(line: &str) -> Vec<String> {
+    if line.contains(';') {
+        line.split(';').map(|cell| cell.replace(',', ".")).collect()
+    } else if line.contains('\t') {
+        line.split('\t')
+            .map(|cell| cell.replace(',', "."))
+            .collect()
+    } else {
+        line.split(',').map(str::to_string).collect()
+    }
+}
+
+/// Числа из CSV по строкам. Нечисловые ячейки (заголовки, пустые) пропускаются.
+pub fn parse_csv(content: &str) -> Vec<Vec<f64>> {
+    content
+        .lines()
+        .map(|line| {
+            split_row(line)
+                .iter()
+                .filter_map(|cell| cell.trim().parse().ok())
+                .collect::<Vec<f64>>()
+        })
+        .filter(|row| !row.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_samples() {
+        let (samples, params) =
+            parse_samples("30,01 29.995 subgroup=5".split_whitespace(), &["subgroup"]).unwrap();
+        assert_eq!(samples, Samples::Values(vec![30.01, 29.995]));
+        assert_eq!(params.required_number("subgroup"), Some(5.0));
+
+        let (samples, _) = parse_samples("file=data.csv".split_whitespace(), &[]).unwrap();
+        assert_eq!(samples, Samples::File("data.csv".to_string()));
+
+        assert_eq!(parse_samples("".split_whitespace(), &[]), None);
+        assert_eq!(
+            parse_samples("30 file=data.csv".split_whitespace(), &[]),
+            None
+        );
+        assert_eq!(parse_samples("30 abc".split_whitespace(), &[]), None);
+        assert_eq!(parse_samples("30 mode=x".split_whitespace(), &[]), None);
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        assert_eq!(
+            parse_csv("x1;x2;x3\n30,01;29,99;\n\n30;30,02;30\n"),
+            vec![vec![30.01, 29.99], vec![30.0, 30.02, 30.0]]
+        );
+        assert_eq!(
+            parse_csv("x1,x2\n30.01,29.99\n30\t30.02"),
+            vec![vec![30.01, 29.99], vec![30.0, 30.02]]
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+/// Именованные параметры вида `ключ=значение`, разделённые пробелами.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Params {
+    values: HashMap<String, String>,
+}
+
+impl Params {
+    /// Возвращает None, если встречен токен без `=` или ключ не из списка `allowed`.
+    pub fn parse<'a>(tokens: impl Iterator<Item = &'a str>, allowed: &[&str]) -> Option<Self> {
+        let mut values = HashMap::new();
+        for token in tokens {
+            let (key, value) = token.split_once('=')?;
+            if !allowed.contains(&key) || value.is_empty() {
+                return None;
+            }
+            values.insert(key.to_string(), value.to_string());
+        }
+        Some(Params { values })
+    }
+
+    pub fn text(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Some(None) - параметр не задан, None - задан, но не является числом.
+    pub fn number(&self, key: &str) -> Option<Option<f64>> {
+        match self.values.get(key) {
+            Some(value) => value.replace(',', ".").parse().ok().map(Some),
+            None => Some(None),
+        }
+    }
+
+    pub fn number_or(&self, key: &str, default: f64) -> Option<f64> {
+        self.number(key).map(|value| value.unwrap_or(default))
+    }
+
+    pub fn required_number(&self, key: &str) -> Option<f64> {
+        self.number(key).flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_params() {
+        let params = Params::parse(
+            "D=100 L=50,5 hub=steel".split_whitespace(),
+            &["D", "L", "hub", "mu"],
+        )
+        .unwrap();
+        assert_eq!(params.required_number("D"), Some(100.0));
+        assert_eq!(params.required_number("L"), Some(50.5));
+        assert_eq!(params.text("hub"), Some("steel"));
+        assert_eq!(params.number("mu"), Some(None));
+        assert_eq!(params.number_or("mu", 0.12), Some(0.12));
+        assert_eq!(params.required_number("mu"), None);
+        assert_eq!(params.number("hub"), None);
+
+        assert_eq!(Params::parse("D=100 X=1".split_whitespace(), &["D"]), None);
+        assert_eq!(Params::parse("D".split_whitespace(), &["D"]), None);
+        assert_eq!(Params::parse("D=".split_whitespace(), &["D"]), None);
+    }
+}
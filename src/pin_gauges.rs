@@ -0,0 +1,251 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::inspection::{search_class_limits, ToleranceClass};
+use crate::params::Params;
+use crate::{is_uppercase, parse_input, replace_comma_with_dot};
+
+/// Наибольший диаметр штифтов в стандартных наборах, мм.
+const MAX_PIN_DIAMETER: Decimal = dec!(25);
+
+/// Класс точности калибров-штифтов (ASME B89.1.5).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PinClass {
+    ZZ,
+    Z,
+    X,
+}
+
+impl PinClass {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.to_uppercase().as_str() {
+            "ZZ" => Some(PinClass::ZZ),
+            "Z" => Some(PinClass::Z),
+            "X" => Some(PinClass::X),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PinClass::ZZ => "ZZ",
+            PinClass::Z => "Z",
+            PinClass::X => "X",
+        }
+    }
+
+    /// Допуск штифта, мм: в наборах «плюс» направлен вверх от номинала, в наборах «минус» - вниз.
+    pub fn tolerance(&self) -> Decimal {
+        match self {
+            PinClass::ZZ => dec!(0.005),
+            PinClass::Z => dec!(0.0025),
+            PinClass::X => dec!(0.001),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PinsQuery {
+    pub class: ToleranceClass,
+    pub step: Decimal,
+    pub pin_class: PinClass,
+}
+
+/// Штифт набора: номинал и действительные пределы диаметра.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Pin {
+    pub nominal: Decimal,
+    pub upper: Decimal,
+    pub lower: Decimal,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PinSelection {
+    pub go: Pin,
+    pub no_go: Pin,
+}
+
+impl PinSelection {
+    /// Диапазон отверстий, которые гарантированно принимаются при любом
+    /// действительном размере штифтов. None - зоны штифтов перекрываются.
+    pub fn guaranteed_range(&self) -> Option<(Decimal, Decimal)> {
+        (self.go.upper < self.no_go.lower).then_some((self.go.upper, self.no_go.lower))
+    }
+}
+
+pub fn parse_pins_input(input: &str) -> Option<PinsQuery> {
+    let mut tokens = input.strip_prefix("pins")?.split_whitespace();
+    let class = parse_input(tokens.next()?)?;
+    if !is_uppercase(&class.1) {
+        return None;
+    }
+    let params = Params::parse(tokens, &["step", "class"])?;
+    let step = match params.text("step").map(replace_comma_with_dot).as_deref() {
+        None | Some("0.01") => dec!(0.01),
+        Some("0.001") => dec!(0.001),
+        Some(_) => return None,
+    };
+    let pin_class = match params.text("class") {
+        Some(pin_class) => PinClass::parse(pin_class)?,
+        None => PinClass::ZZ,
+    };
+    Some(PinsQuery {
+        class,
+        step,
+        pin_class,
+    })
+}
+
+/// Проходной штифт из набора «плюс» с номиналом не меньше нижнего предела отверстия,
+/// непроходной - из набора «минус» с номиналом не больше верхнего предела.
+/// Так годным признаётся только отверстие, действительно лежащее в пределах.
+pub fn select_pins(limits: (Decimal, Decimal), step: Decimal, tolerance: Decimal) -> PinSelection {
+    let (upper, lower) = limits;
+    let go = (lower / step).ceil() * step;
+    let no_go = (upper / step).floor() * step;
+    PinSelection {
+        go: Pin {
+            nominal: go.normalize(),
+            upper: (go + tolerance).normalize(),
+            lower: go.normalize(),
+        },
+        no_go: Pin {
+            nominal: no_go.normalize(),
+            upper: no_go.normalize(),
+            lower: (no_go - tolerance).normalize(),
+        },
+    }
+}
+
+pub fn handle_pins(pins_query: &PinsQuery) {
+    match search_class_limits(&pins_query.class) {
+        Ok(Some(limits)) => {
+            if limits.0 > MAX_PIN_DIAMETER {
+                println!(
+                    "Наборы штифтов выпускаются до {} мм, используйте калибр-пробку (gauge)",
+                    MAX_PIN_DIAMETER
+                );
+                return;
+            }
+            let selection = select_pins(limits, pins_query.step, pins_query.pin_class.tolerance());
+            print_pins(pins_query, limits, &selection)
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_pins(pins_query: &PinsQuery, limits: (Decimal, Decimal), selection: &PinSelection) {
+    let (size, field, accuracy) = &pins_query.class;
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new(format!("{}{}{}", size, field, accuracy)),
+            Cell::new("номинал штифта"),
+            Cell::new("наибольший диаметр").fg(Color::Red),
+            Cell::new("наименьший диаметр").fg(Color::Cyan),
+        ])
+        .add_row(vec![
+            Cell::new(format!("ПР, набор «плюс» (>= {})", limits.1)),
+            Cell::new(selection.go.nominal).fg(Color::Green),
+            Cell::new(selection.go.upper).fg(Color::Red),
+            Cell::new(selection.go.lower).fg(Color::Cyan),
+        ])
+        .add_row(vec![
+            Cell::new(format!("НЕ, набор «минус» (<= {})", limits.0)),
+            Cell::new(selection.no_go.nominal).fg(Color::Green),
+            Cell::new(selection.no_go.upper).fg(Color::Red),
+            Cell::new(selection.no_go.lower).fg(Color::Cyan),
+        ]);
+    println!("{table_result}");
+    println!(
+        "Штифты класса {} (допуск {}), шаг набора {}",
+        pins_query.pin_class.name(),
+        pins_query.pin_class.tolerance(),
+        pins_query.step
+    );
+    match selection.guaranteed_range() {
+        Some((from, to)) => println!(
+            "Гарантированно принимаются отверстия {} .. {} ({}% допуска)",
+            from,
+            to,
+            ((to - from) / (limits.0 - limits.1) * dec!(100))
+                .round_dp(1)
+                .normalize()
+        ),
+        None => println!(
+            "Зоны штифтов ПР и НЕ перекрываются: набор не обеспечивает контроль, \
+             выберите шаг 0.001 или более точный класс штифтов"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pins_input() {
+        let class = ("6".to_string(), "H".to_string(), "7".to_string());
+        assert_eq!(
+            parse_pins_input("pins 6H7").unwrap(),
+            PinsQuery {
+                class: class.clone(),
+                step: dec!(0.01),
+                pin_class: PinClass::ZZ,
+            }
+        );
+        assert_eq!(
+            parse_pins_input("pins 6H7 step=0,001 class=x").unwrap(),
+            PinsQuery {
+                class,
+                step: dec!(0.001),
+                pin_class: PinClass::X,
+            }
+        );
+        assert_eq!(parse_pins_input("pins 6h7"), None);
+        assert_eq!(parse_pins_input("pins 6H7 step=0.005"), None);
+        assert_eq!(parse_pins_input("pins 6H7 class=XX"), None);
+    }
+
+    #[test]
+    fn test_select_pins() {
+        let limits = (dec!(6.012), dec!(6));
+        let selection = select_pins(limits, dec!(0.001), PinClass::X.tolerance());
+        assert_eq!(
+            selection.go,
+            Pin {
+                nominal: dec!(6),
+                upper: dec!(6.001),
+                lower: dec!(6),
+            }
+        );
+        assert_eq!(
+            selection.no_go,
+            Pin {
+                nominal: dec!(6.012),
+                upper: dec!(6.012),
+                lower: dec!(6.011),
+            }
+        );
+        assert_eq!(
+            selection.guaranteed_range(),
+            Some((dec!(6.001), dec!(6.011)))
+        );
+
+        let selection = select_pins(limits, dec!(0.01), PinClass::ZZ.tolerance());
+        assert_eq!(selection.go.nominal, dec!(6));
+        assert_eq!(selection.no_go.nominal, dec!(6.01));
+        assert_eq!(selection.guaranteed_range(), None);
+
+        let limits = (dec!(10.028), dec!(10.013));
+        let selection = select_pins(limits, dec!(0.01), PinClass::Z.tolerance());
+        assert_eq!(selection.go.nominal, dec!(10.02));
+        assert_eq!(selection.no_go.nominal, dec!(10.02));
+        assert_eq!(selection.guaranteed_range(), None);
+    }
+}
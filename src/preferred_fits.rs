@@ -0,0 +1,223 @@
+use alloc::string::String;
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, ContentArrangement, Table};
+use regex::Regex;
+
+use crate::fit::{calc_fit, search_fit, FitBasis, FitQuery};
+use crate::{replace_comma_with_dot, split_class, SIZE_PATTERN};
+
+pub struct PreferredFit {
+    pub hole_class: &'static str,
+    pub shaft_class: &'static str,
+    pub description: &'static str,
+}
+
+/// Предпочтительные посадки ISO 286-2 / ANSI B4.2 в системе отверстия и системе вала.
+pub const PREFERRED_FITS: [PreferredFit; 19] = [
+    PreferredFit {
+        hole_class: "H11",
+        shaft_class: "c11",
+        description: "свободная ходовая: грубые допуски, наружные детали с большим зазором",
+    },
+    PreferredFit {
+        hole_class: "H9",
+        shaft_class: "d9",
+        description: "свободного вращения: неточная работа, большие скорости, перепады температуры",
+    },
+    PreferredFit {
+        hole_class: "H8",
+        shaft_class: "f7",
+        description: "ходовая точная: вращение в точных механизмах при умеренных скоростях",
+    },
+    PreferredFit {
+        hole_class: "H7",
+        shaft_class: "g6",
+        description: "скользящая: детали перемещаются и поворачиваются, точное центрирование",
+    },
+    PreferredFit {
+        hole_class: "H7",
+        shaft_class: "h6",
+        description: "центрирующая с зазором: неподвижные детали, свободная сборка и разборка",
+    },
+    PreferredFit {
+        hole_class: "H7",
+        shaft_class: "k6",
+        description: "переходная: точное центрирование, компромисс между зазором и натягом",
+    },
+    PreferredFit {
+        hole_class: "H7",
+        shaft_class: "n6",
+        description: "переходная плотная: точное центрирование при допустимом большем натяге",
+    },
+    PreferredFit {
+        hole_class: "H7",
+        shaft_class: "p6",
+        description: "центрирующая с натягом: жёсткость и соосность без передачи нагрузки натягом",
+    },
+    PreferredFit {
+        hole_class: "H7",
+        shaft_class: "s6",
+        description: "прессовая средняя: стальные детали, лёгкие горячие посадки, чугун",
+    },
+    PreferredFit {
+        hole_class: "H7",
+        shaft_class: "u6",
+        description: "прессовая тяжёлая: сильно нагруженные детали, горячие посадки",
+    },
+    PreferredFit {
+        hole_class: "C11",
+        shaft_class: "h11",
+        description: "свободная ходовая: грубые допуски, наружные детали с большим зазором",
+    },
+    PreferredFit {
+        hole_class: "D9",
+        shaft_class: "h9",
+        description: "свободного вращения: неточная работа, большие скорости, перепады температуры",
+    },
+    PreferredFit {
+        hole_class: "F8",
+        shaft_class: "h7",
+        description: "ходовая точная: вращение в точных механизмах при умеренных скоростях",
+    },
+    PreferredFit {
+        hole_class: "G7",
+        shaft_class: "h6",
+        description: "скользящая: детали перемещаются и поворачиваются, точное центрирование",
+    },
+    PreferredFit {
+        hole_class: "K7",
+        shaft_class: "h6",
+        description: "переходная: точное центрирование, компромисс между зазором и натягом",
+    },
+    PreferredFit {
+        hole_class: "N7",
+        shaft_class: "h6",
+        description: "переходная плотная: точное центрирование при допустимом большем натяге",
+    },
+    PreferredFit {
+        hole_class: "P7",
+        shaft_class: "h6",
+        description: "центрирующая с натягом: жёсткость и соосность без передачи нагрузки натягом",
+    },
+    PreferredFit {
+        hole_class: "S7",
+        shaft_class: "h6",
+        description: "прессовая средняя: стальные детали, лёгкие горячие посадки, чугун",
+    },
+    PreferredFit {
+        hole_class: "U7",
+        shaft_class: "h6",
+        description: "прессовая тяжёлая: сильно нагруженные детали, горячие посадки",
+    },
+];
+
+impl PreferredFit {
+    pub fn fit_query(&self, size: &str) -> FitQuery {
+        let (hole_field, hole_accuracy) = split_class(self.hole_class);
+        let (shaft_field, shaft_accuracy) = split_class(self.shaft_class);
+        FitQuery {
+            size: size.to_string(),
+            hole_field,
+            hole_accuracy,
+            shaft_field,
+            shaft_accuracy,
+        }
+    }
+}
+
+pub fn parse_preferred_fits_input(input: &str) -> Option<String> {
+    let regex = Regex::new(&format!(r"^fits\s+(?P<size>{})$", SIZE_PATTERN))
+        .expect("Ошибка обработки RegEx");
+    let captures = regex.captures(input)?;
+    Some(replace_comma_with_dot(&captures["size"]))
+}
+
+pub fn handle_preferred_fits(size: &str) {
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            format!("{} мм", size).as_str(),
+            "система",
+            "отверстие",
+            "вал",
+            "Smax",
+            "Smin",
+            "тип",
+            "применение",
+        ]);
+    let mut found = 0;
+    for preferred_fit in PREFERRED_FITS.iter() {
+        let fit_query = preferred_fit.fit_query(size);
+        match search_fit(&fit_query) {
+            Ok(Some((hole_tols, shaft_tols))) => {
+                let fit_limits = calc_fit(hole_tols, shaft_tols);
+                let color = fit_limits.fit_type.color();
+                table_result.add_row(vec![
+                    Cell::new(format!(
+                        "{}/{}",
+                        preferred_fit.hole_class, preferred_fit.shaft_class
+                    )),
+                    Cell::new(FitBasis::of(&fit_query.hole_field, &fit_query.shaft_field).name()),
+                    Cell::new(format!("{} / {}", hole_tols.0, hole_tols.1)),
+                    Cell::new(format!("{} / {}", shaft_tols.0, shaft_tols.1)),
+                    Cell::new(fit_limits.max_clearance).fg(color),
+                    Cell::new(fit_limits.min_clearance).fg(color),
+                    Cell::new(fit_limits.fit_type.name()).fg(color),
+                    Cell::new(preferred_fit.description),
+                ]);
+                found += 1;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                println!("Ошибка при поиске в БД: {}", e);
+                return;
+            }
+        }
+    }
+    if found == 0 {
+        println!("Ничего не найдено для заданных параметров");
+    } else {
+        println!("{table_result}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fit::parse_fit_input;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_preferred_fits_input() {
+        assert_eq!(parse_preferred_fits_input("fits 40").unwrap(), "40");
+        assert_eq!(parse_preferred_fits_input("fits 12,5").unwrap(), "12.5");
+        assert_eq!(parse_preferred_fits_input("fits"), None);
+        assert_eq!(parse_preferred_fits_input("fits 40H7"), None);
+    }
+
+    #[test]
+    fn test_preferred_fits_catalog() {
+        for preferred_fit in PREFERRED_FITS.iter() {
+            let input = format!(
+                "40{}/{}",
+                preferred_fit.hole_class, preferred_fit.shaft_class
+            );
+            assert_eq!(
+                parse_fit_input(&input).unwrap(),
+                preferred_fit.fit_query("40")
+            );
+        }
+        let basis_counts = PREFERRED_FITS.iter().fold((0, 0), |counts, preferred_fit| {
+            let fit_query = preferred_fit.fit_query("40");
+            match FitBasis::of(&fit_query.hole_field, &fit_query.shaft_field) {
+                FitBasis::Hole => (counts.0 + 1, counts.1),
+                FitBasis::Shaft => (counts.0, counts.1 + 1),
+                FitBasis::Combined => counts,
+            }
+        });
+        assert_eq!(basis_counts, (10, 9));
+    }
+}
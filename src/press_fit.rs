@@ -0,0 +1,381 @@
+use core::f64::consts::PI;
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::fit::{calc_fit, parse_fit_input, search_fit, FitQuery};
+use crate::materials::{Material, REFERENCE_TEMPERATURE};
+use crate::params::Params;
+
+/// Монтажный зазор при сборке с нагревом или охлаждением, доля диаметра.
+const ASSEMBLY_CLEARANCE: f64 = 0.001;
+
+/// Геометрия и материалы соединения с натягом. Размеры в мм, напряжения в МПа.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PressFitJoint {
+    pub diameter: f64,
+    pub hub_diameter: f64,
+    pub shaft_bore: f64,
+    pub length: f64,
+    pub friction: f64,
+    /// Сглаживание микронеровностей 0.8·(RzA + RzI), мм.
+    pub smoothing: f64,
+    pub hub: Material,
+    pub shaft: Material,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PressFitQuery {
+    pub fit_query: FitQuery,
+    pub joint: PressFitJoint,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PressFitResult {
+    pub interference: f64,
+    pub pressure: f64,
+    /// Передаваемый момент, Н·м.
+    pub torque: f64,
+    /// Передаваемая осевая сила, Н.
+    pub axial_force: f64,
+    pub hub_stress: f64,
+    pub shaft_stress: f64,
+    pub hub_safety: f64,
+    pub shaft_safety: f64,
+}
+
+pub fn joint_param_keys() -> Vec<String> {
+    let mut keys: Vec<String> = ["D", "L", "mu", "di", "rzh", "rzs"]
+        .iter()
+        .map(|key| key.to_string())
+        .collect();
+    keys.extend(Material::param_keys("hub", "h"));
+    keys.extend(Material::param_keys("shaft", "s"));
+    keys
+}
+
+/// Соединение диаметра `diameter` из параметров D, L, mu, di, rzh, rzs и материалов.
+pub fn parse_joint(diameter: f64, params: &Params) -> Option<PressFitJoint> {
+    let joint = PressFitJoint {
+        diameter,
+        hub_diameter: params.required_number("D")?,
+        shaft_bore: params.number_or("di", 0.0)?,
+        length: params.required_number("L")?,
+        friction: params.number_or("mu", 0.12)?,
+        smoothing: 0.8 * (params.number_or("rzh", 0.0)? + params.number_or("rzs", 0.0)?) / 1000.0,
+        hub: Material::from_params(params, "hub", "h")?,
+        shaft: Material::from_params(params, "shaft", "s")?,
+    };
+    let valid = diameter > 0.0
+        && joint.hub_diameter > diameter
+        && joint.shaft_bore >= 0.0
+        && joint.shaft_bore < diameter
+        && joint.length > 0.0
+        && joint.friction > 0.0;
+    valid.then_some(joint)
+}
+
+pub fn parse_press_fit_input(input: &str) -> Option<PressFitQuery> {
+    let mut tokens = input.strip_prefix("press")?.split_whitespace();
+    let fit_query = parse_fit_input(tokens.next()?)?;
+    let keys = joint_param_keys();
+    let allowed: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let params = Params::parse(tokens, &allowed)?;
+    let joint = parse_joint(fit_query.size.parse().ok()?, &params)?;
+    Some(PressFitQuery { fit_query, joint })
+}
+
+impl PressFitJoint {
+    fn hub_ratio(&self) -> f64 {
+        self.diameter / self.hub_diameter
+    }
+
+    fn shaft_ratio(&self) -> f64 {
+        self.shaft_bore / self.diameter
+    }
+
+    /// Давление на посадочной поверхности по Ламе, МПа.
+    pub fn pressure(&self, interference: f64) -> f64 {
+        let effective = (interference - self.smoothing).max(0.0);
+        let q_a = self.hub_ratio();
+        let q_i = self.shaft_ratio();
+        let k_a = (1.0 + q_a * q_a) / (1.0 - q_a * q_a) + self.hub.poisson;
+        let k_i = (1.0 + q_i * q_i) / (1.0 - q_i * q_i) - self.shaft.poisson;
+        effective / (self.diameter * (k_a / self.hub.elasticity + k_i / self.shaft.elasticity))
+    }
+
+    /// Натяг, создающий давление `pressure` (обратная задача к `pressure`).
+    pub fn interference_for_pressure(&self, pressure: f64) -> f64 {
+        pressure / self.pressure(1.0 + self.smoothing) + self.smoothing
+    }
+
+    /// Эквивалентные напряжения (по DIN 7190) на посадочной поверхности ступицы и вала.
+    pub fn stresses(&self, pressure: f64) -> (f64, f64) {
+        let q_a = self.hub_ratio();
+        let q_i = self.shaft_ratio();
+        let hub_stress = 2.0 * pressure / (1.0 - q_a * q_a);
+        let shaft_stress = if self.shaft_bore > 0.0 {
+            2.0 * pressure / (1.0 - q_i * q_i)
+        } else {
+            pressure
+        };
+        (hub_stress, shaft_stress)
+    }
+
+    /// Давление, при котором одна из деталей достигает предела текучести.
+    pub fn yield_pressure(&self) -> f64 {
+        let (hub_stress, shaft_stress) = self.stresses(1.0);
+        (self.hub.yield_strength / hub_stress).min(self.shaft.yield_strength / shaft_stress)
+    }
+
+    /// Осевая сила, передаваемая давлением `pressure`, Н.
+    pub fn axial_force(&self, pressure: f64) -> f64 {
+        pressure * PI * self.diameter * self.length * self.friction
+    }
+
+    /// Передаваемый момент, Н·м.
+    pub fn torque(&self, pressure: f64) -> f64 {
+        self.axial_force(pressure) * self.diameter * 0.5 / 1000.0
+    }
+
+    pub fn analyse(&self, interference: f64) -> PressFitResult {
+        let pressure = self.pressure(interference);
+        let (hub_stress, shaft_stress) = self.stresses(pressure);
+        PressFitResult {
+            interference,
+            pressure,
+            torque: self.torque(pressure),
+            axial_force: self.axial_force(pressure),
+            hub_stress,
+            shaft_stress,
+            hub_safety: self.hub.yield_strength / hub_stress,
+            shaft_safety: self.shaft.yield_strength / shaft_stress,
+        }
+    }
+
+    /// Температуры нагрева ступицы и охлаждения вала для сборки без усилия.
+    pub fn assembly_temperatures(&self, max_interference: f64) -> (f64, f64) {
+        let expansion = max_interference + ASSEMBLY_CLEARANCE * self.diameter;
+        (
+            REFERENCE_TEMPERATURE + expansion / (self.hub.thermal_expansion * self.diameter),
+            REFERENCE_TEMPERATURE - expansion / (self.shaft.thermal_expansion * self.diameter),
+        )
+    }
+
+    /// Уменьшение отверстия запрессованной втулки, мм.
+    pub fn bore_closure(&self, pressure: f64) -> f64 {
+        let q_i = self.shaft_ratio();
+        2.0 * pressure * self.shaft_bore / (self.shaft.elasticity * (1.0 - q_i * q_i))
+    }
+}
+
+pub fn handle_press_fit(press_fit_query: &PressFitQuery) {
+    match search_fit(&press_fit_query.fit_query) {
+        Ok(Some((hole_tols, shaft_tols))) => {
+            let fit_limits = calc_fit(hole_tols, shaft_tols);
+            let min_interference = (-fit_limits.max_clearance).to_f64().unwrap_or_default();
+            let max_interference = (-fit_limits.min_clearance).to_f64().unwrap_or_default();
+            if max_interference <= 0.0 {
+                println!("Посадка не создаёт натяга, расчёт соединения невозможен");
+                return;
+            }
+            if min_interference <= 0.0 {
+                println!(
+                    "Внимание: посадка переходная, при наименьшем натяге соединение не нагружено"
+                );
+            }
+            print_press_fit(press_fit_query, min_interference.max(0.0), max_interference)
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn safety_cell(safety: f64) -> Cell {
+    let color = if safety >= 1.0 {
+        Color::Green
+    } else {
+        Color::Red
+    };
+    Cell::new(format!("{:.2}", safety)).fg(color)
+}
+
+fn print_press_fit(press_fit_query: &PressFitQuery, min_interference: f64, max_interference: f64) {
+    let joint = &press_fit_query.joint;
+    let fit_query = &press_fit_query.fit_query;
+    let min_result = joint.analyse(min_interference);
+    let max_result = joint.analyse(max_interference);
+    let row = |name: &str, value: fn(&PressFitResult) -> String| {
+        vec![
+            Cell::new(name),
+            Cell::new(value(&min_result)),
+            Cell::new(value(&max_result)),
+        ]
+    };
+
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            format!(
+                "{}{}/{}",
+                fit_query.size,
+                fit_query.hole_class(),
+                fit_query.shaft_class()
+            ),
+            "при Nmin".to_string(),
+            "при Nmax".to_string(),
+        ])
+        .add_row(row("натяг, мм", |result| {
+            format!("{:.4}", result.interference)
+        }))
+        .add_row(row("давление p, МПа", |result| {
+            format!("{:.1}", result.pressure)
+        }))
+        .add_row(row("момент T, Н·м", |result| {
+            format!("{:.1}", result.torque)
+        }))
+        .add_row(row("осевая сила F, Н", |result| {
+            format!("{:.0}", result.axial_force)
+        }))
+        .add_row(row(
+            "напряжение ступицы, МПа",
+            |result| format!("{:.1}", result.hub_stress),
+        ))
+        .add_row(row("напряжение вала, МПа", |result| {
+            format!("{:.1}", result.shaft_stress)
+        }))
+        .add_row(vec![
+            Cell::new("запас ступицы по Re"),
+            safety_cell(min_result.hub_safety),
+            safety_cell(max_result.hub_safety),
+        ])
+        .add_row(vec![
+            Cell::new("запас вала по Re"),
+            safety_cell(min_result.shaft_safety),
+            safety_cell(max_result.shaft_safety),
+        ]);
+    println!("{table_result}");
+
+    let (hub_temperature, shaft_temperature) = joint.assembly_temperatures(max_interference);
+    let mut table_assembly = Table::new();
+    table_assembly
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .add_row(vec![
+            Cell::new("усилие запрессовки при Nmax, Н"),
+            Cell::new(format!("{:.0}", max_result.axial_force)).fg(Color::Magenta),
+        ])
+        .add_row(vec![
+            Cell::new("нагрев ступицы до, °C"),
+            Cell::new(format!("{:.0}", hub_temperature)).fg(Color::Red),
+        ])
+        .add_row(vec![
+            Cell::new("или охлаждение вала до, °C"),
+            Cell::new(format!("{:.0}", shaft_temperature)).fg(Color::Cyan),
+        ]);
+    if joint.shaft_bore > 0.0 {
+        table_assembly.add_row(vec![
+            Cell::new("усадка отверстия втулки, мм"),
+            Cell::new(format!(
+                "{:.4} .. {:.4}",
+                joint.bore_closure(min_result.pressure),
+                joint.bore_closure(max_result.pressure)
+            ))
+            .fg(Color::Yellow),
+        ]);
+    }
+    println!("{table_assembly}");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::materials::MATERIALS;
+
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, epsilon: f64) {
+        assert!(
+            (actual - expected).abs() < epsilon,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    fn steel_joint() -> PressFitJoint {
+        PressFitJoint {
+            diameter: 60.0,
+            hub_diameter: 100.0,
+            shaft_bore: 0.0,
+            length: 50.0,
+            friction: 0.12,
+            smoothing: 0.0,
+            hub: MATERIALS[0],
+            shaft: MATERIALS[0],
+        }
+    }
+
+    #[test]
+    fn test_parse_press_fit_input() {
+        let query = parse_press_fit_input("press 60H7/s6 D=100 L=50 hub=castiron mu=0,1").unwrap();
+        assert_eq!(query.fit_query.shaft_class(), "s6");
+        assert_eq!(query.joint.diameter, 60.0);
+        assert_eq!(query.joint.hub_diameter, 100.0);
+        assert_eq!(query.joint.friction, 0.1);
+        assert_eq!(query.joint.hub.name, "castiron");
+        assert_eq!(query.joint.shaft.name, "steel");
+
+        assert_eq!(parse_press_fit_input("press 60H7/s6 L=50"), None);
+        assert_eq!(parse_press_fit_input("press 60H7/s6 D=50 L=50"), None);
+        assert_eq!(
+            parse_press_fit_input("press 60H7/s6 D=100 L=50 di=60"),
+            None
+        );
+        assert_eq!(parse_press_fit_input("press 60H7/s6 D=100 L=50 x=1"), None);
+    }
+
+    #[test]
+    fn test_pressure_and_loads() {
+        let joint = steel_joint();
+        let result = joint.analyse(0.04);
+        assert_close(result.pressure, 44.8, 1e-9);
+        assert_close(result.axial_force, 50667.6, 0.1);
+        assert_close(result.torque, 1520.03, 0.01);
+        assert_close(result.hub_stress, 140.0, 1e-9);
+        assert_close(result.shaft_stress, 44.8, 1e-9);
+        assert_close(joint.interference_for_pressure(44.8), 0.04, 1e-12);
+        assert_close(joint.yield_pressure(), 355.0 * 0.64 / 2.0, 1e-9);
+    }
+
+    #[test]
+    fn test_smoothing_and_bushing() {
+        let joint = PressFitJoint {
+            smoothing: 0.01,
+            ..steel_joint()
+        };
+        assert_close(joint.pressure(0.05), 44.8, 1e-9);
+        assert_close(joint.pressure(0.005), 0.0, 1e-12);
+        assert_close(joint.interference_for_pressure(44.8), 0.05, 1e-12);
+
+        let bushing = PressFitJoint {
+            shaft_bore: 30.0,
+            ..steel_joint()
+        };
+        let pressure = bushing.pressure(0.04);
+        assert_close(
+            bushing.bore_closure(pressure),
+            8.0 * pressure * 30.0 / (3.0 * 210000.0),
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn test_assembly_temperatures() {
+        let (hub_temperature, shaft_temperature) = steel_joint().assembly_temperatures(0.04);
+        assert_close(hub_temperature, 20.0 + 0.1 / (11.5e-6 * 60.0), 1e-9);
+        assert_close(shaft_temperature, 20.0 - 0.1 / (11.5e-6 * 60.0), 1e-9);
+    }
+}
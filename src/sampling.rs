@@ -0,0 +1,407 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::inspection::{search_class_limits, ToleranceClass};
+use crate::measurements::{parse_samples, Samples};
+use crate::params::Params;
+use crate::parse_input;
+
+/// Кодовые буквы объёма выборки и объёмы выборки (ISO 2859-1, таблица 2-A).
+const CODE_LETTERS: [char; 16] = [
+    'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'J', 'K', 'L', 'M', 'N', 'P', 'Q', 'R',
+];
+#[rustfmt::skip]
+const SAMPLE_SIZES: [u32; 16] = [
+    2, 3, 5, 8, 13, 20, 32, 50, 80, 125, 200, 315, 500, 800, 1250, 2000,
+];
+/// Верхние границы диапазонов объёма партии (ISO 2859-1, таблица 1).
+#[rustfmt::skip]
+const LOT_SIZES: [u32; 14] = [
+    8, 15, 25, 50, 90, 150, 280, 500, 1200, 3200, 10000, 35000, 150000, 500000,
+];
+/// Индексы кодовых букв по диапазонам объёма партии для уровней S-1..S-4, I, II, III.
+#[rustfmt::skip]
+const LEVEL_LETTERS: [[u8; 15]; 7] = [
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3],
+    [0, 0, 0, 1, 1, 1, 2, 2, 2, 3, 3, 3, 4, 4, 4],
+    [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7],
+    [0, 0, 1, 2, 2, 3, 4, 4, 5, 6, 6, 7, 8, 8, 9],
+    [0, 0, 1, 2, 2, 3, 4, 5, 6, 6, 7, 8, 9, 10, 11],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14],
+    [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+];
+/// Предпочтительные значения AQL, % несоответствующих единиц продукции.
+#[rustfmt::skip]
+const AQL_VALUES: [f64; 16] = [
+    0.010, 0.015, 0.025, 0.040, 0.065, 0.10, 0.15, 0.25, 0.40, 0.65, 1.0, 1.5, 2.5, 4.0, 6.5, 10.0,
+];
+/// Приёмочные числа таблицы 2-A вдоль диагоналей «кодовая буква + AQL», начиная с суммы 14.
+/// None - стрелка: 15 - вверх, 16 - вниз.
+#[rustfmt::skip]
+const ACCEPTANCE_NUMBERS: [Option<u32>; 11] = [
+    Some(0), None, None, Some(1), Some(2), Some(3), Some(5), Some(7), Some(10), Some(14), Some(21),
+];
+const DIAGONAL_START: usize = 14;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum InspectionLevel {
+    S1,
+    S2,
+    S3,
+    S4,
+    General1,
+    General2,
+    General3,
+}
+
+impl InspectionLevel {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.to_uppercase().as_str() {
+            "S-1" | "S1" => Some(InspectionLevel::S1),
+            "S-2" | "S2" => Some(InspectionLevel::S2),
+            "S-3" | "S3" => Some(InspectionLevel::S3),
+            "S-4" | "S4" => Some(InspectionLevel::S4),
+            "I" => Some(InspectionLevel::General1),
+            "II" => Some(InspectionLevel::General2),
+            "III" => Some(InspectionLevel::General3),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InspectionLevel::S1 => "S-1",
+            InspectionLevel::S2 => "S-2",
+            InspectionLevel::S3 => "S-3",
+            InspectionLevel::S4 => "S-4",
+            InspectionLevel::General1 => "I",
+            InspectionLevel::General2 => "II",
+            InspectionLevel::General3 => "III",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SamplingQuery {
+    pub class: ToleranceClass,
+    pub lot: u32,
+    /// Индекс в `AQL_VALUES`.
+    pub aql: usize,
+    pub level: InspectionLevel,
+    /// Число несоответствующих единиц в выборке по результатам контроля калибрами.
+    pub defects: Option<u32>,
+    pub samples: Option<Samples>,
+}
+
+/// Одноступенчатый план нормального контроля по альтернативному признаку.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AttributesPlan {
+    pub code: char,
+    pub sample: u32,
+    pub accept: u32,
+    pub reject: u32,
+}
+
+pub fn parse_sampling_input(input: &str) -> Option<SamplingQuery> {
+    let mut tokens = input.strip_prefix("sampling")?.split_whitespace();
+    let class = parse_input(tokens.next()?)?;
+    let tokens: Vec<&str> = tokens.collect();
+    let allowed = ["lot", "aql", "level", "defects"];
+    let (samples, params) = if tokens
+        .iter()
+        .any(|token| !token.contains('=') || token.starts_with("file="))
+    {
+        let (samples, params) = parse_samples(tokens.into_iter(), &allowed)?;
+        (Some(samples), params)
+    } else {
+        (None, Params::parse(tokens.into_iter(), &allowed)?)
+    };
+    let lot = params.required_number("lot")?;
+    if lot.fract() != 0.0 || lot < 2.0 || lot > u32::MAX as f64 {
+        return None;
+    }
+    let aql = params.required_number("aql")?;
+    let aql = AQL_VALUES
+        .iter()
+        .position(|value| (value - aql).abs() < 1e-9)?;
+    let level = match params.text("level") {
+        Some(level) => InspectionLevel::parse(level)?,
+        None => InspectionLevel::General2,
+    };
+    let defects = match params.number("defects")? {
+        Some(defects) if defects.fract() == 0.0 && defects >= 0.0 => Some(defects as u32),
+        Some(_) => return None,
+        None => None,
+    };
+    if defects.is_some() && samples.is_some() {
+        return None;
+    }
+    Some(SamplingQuery {
+        class,
+        lot: lot as u32,
+        aql,
+        level,
+        defects,
+        samples,
+    })
+}
+
+/// Индекс кодовой буквы объёма выборки по объёму партии и уровню контроля.
+pub fn code_letter(lot: u32, level: InspectionLevel) -> usize {
+    let range = LOT_SIZES
+        .iter()
+        .position(|upper| lot <= *upper)
+        .unwrap_or(LOT_SIZES.len());
+    LEVEL_LETTERS[level.index()][range] as usize
+}
+
+/// План по таблице 2-A: по стрелкам переходит к первому плану ниже или выше;
+/// за крайними строками таблицы - в обратную сторону.
+/// Если объём выборки не меньше объёма партии, контролируется вся партия.
+pub fn attributes_plan(lot: u32, letter: usize, aql: usize) -> AttributesPlan {
+    let mut letter = letter;
+    loop {
+        let diagonal = letter + aql;
+        let step_down = diagonal < DIAGONAL_START || diagonal == DIAGONAL_START + 2;
+        let step_up =
+            diagonal == DIAGONAL_START + 1 || diagonal >= DIAGONAL_START + ACCEPTANCE_NUMBERS.len();
+        if (step_down && letter + 1 < CODE_LETTERS.len()) || (step_up && letter == 0) {
+            letter += 1;
+        } else if step_up || step_down {
+            letter -= 1;
+        } else if let Some(accept) = ACCEPTANCE_NUMBERS[diagonal - DIAGONAL_START] {
+            return AttributesPlan {
+                code: CODE_LETTERS[letter],
+                sample: SAMPLE_SIZES[letter].min(lot),
+                accept,
+                reject: accept + 1,
+            };
+        }
+    }
+}
+
+pub fn handle_sampling(sampling_query: &SamplingQuery) {
+    let letter = code_letter(sampling_query.lot, sampling_query.level);
+    let plan = attributes_plan(sampling_query.lot, letter, sampling_query.aql);
+    let values = match &sampling_query.samples {
+        Some(samples) => match samples.load() {
+            Ok(rows) => Some(rows.into_iter().flatten().collect::<Vec<f64>>()),
+            Err(e) => {
+                println!("Ошибка: {}", e);
+                return;
+            }
+        },
+        None => None,
+    };
+    match search_class_limits(&sampling_query.class) {
+        Ok(Some((upper, lower))) => {
+            let limits = (
+                upper.to_f64().unwrap_or_default(),
+                lower.to_f64().unwrap_or_default(),
+            );
+            print_sampling(sampling_query, CODE_LETTERS[letter], &plan, limits, values)
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_sampling(
+    sampling_query: &SamplingQuery,
+    letter: char,
+    plan: &AttributesPlan,
+    limits: (f64, f64),
+    values: Option<Vec<f64>>,
+) {
+    let (size, field, accuracy) = &sampling_query.class;
+    let mut table_plan = Table::new();
+    table_plan
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new(format!("{}{}{}", size, field, accuracy)),
+            Cell::new(format!("{} .. {}", limits.1, limits.0)),
+        ])
+        .add_row(vec![
+            Cell::new("объём партии"),
+            Cell::new(sampling_query.lot),
+        ])
+        .add_row(vec![
+            Cell::new("уровень контроля, кодовая буква"),
+            Cell::new(format!("{}, {}", sampling_query.level.name(), letter)),
+        ])
+        .add_row(vec![
+            Cell::new("AQL, %"),
+            Cell::new(AQL_VALUES[sampling_query.aql]),
+        ]);
+    table_plan
+        .add_row(vec![
+            Cell::new("план ISO 2859-1, нормальный контроль"),
+            Cell::new(format!("кодовая буква {}", plan.code)),
+        ])
+        .add_row(vec![
+            Cell::new("объём выборки n"),
+            Cell::new(plan.sample).fg(Color::Green),
+        ])
+        .add_row(vec![
+            Cell::new("приёмочное / браковочное число"),
+            Cell::new(format!("Ac = {}, Re = {}", plan.accept, plan.reject)).fg(Color::Green),
+        ]);
+    let sample = plan.sample;
+    if sample >= sampling_query.lot {
+        table_plan.add_row(vec![
+            Cell::new("выборка не меньше партии"),
+            Cell::new("сплошной контроль"),
+        ]);
+    }
+    println!("{table_plan}");
+
+    let decision = match (sampling_query.defects, values) {
+        (Some(defects), _) => Some((
+            format!("несоответствующих в выборке: {}", defects),
+            defects <= plan.accept,
+        )),
+        (_, Some(values)) if values.len() < sample as usize => {
+            println!(
+                "Для решения по партии нужно не меньше {} значений, задано {}",
+                sample,
+                values.len()
+            );
+            None
+        }
+        (None, Some(values)) => {
+            let defects = values[..sample as usize]
+                .iter()
+                .filter(|value| **value > limits.0 || **value < limits.1)
+                .count() as u32;
+            Some((
+                format!("несоответствующих в выборке: {}", defects),
+                defects <= plan.accept,
+            ))
+        }
+        _ => None,
+    };
+    if let Some((basis, accepted)) = decision {
+        let mut table_decision = Table::new();
+        table_decision
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .add_row(vec![
+                Cell::new(basis),
+                if accepted {
+                    Cell::new("партия принимается").fg(Color::Green)
+                } else {
+                    Cell::new("партия бракуется").fg(Color::Red)
+                },
+            ]);
+        println!("{table_decision}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(lot: u32, level: InspectionLevel, aql: f64) -> AttributesPlan {
+        let aql = AQL_VALUES.iter().position(|value| *value == aql).unwrap();
+        attributes_plan(lot, code_letter(lot, level), aql)
+    }
+
+    #[test]
+    fn test_parse_sampling_input() {
+        let query = parse_sampling_input("sampling 20h9 lot=500 aql=1").unwrap();
+        assert_eq!(query.lot, 500);
+        assert_eq!(AQL_VALUES[query.aql], 1.0);
+        assert_eq!(query.level, InspectionLevel::General2);
+        assert_eq!(query.samples, None);
+
+        let query =
+            parse_sampling_input("sampling 20h9 lot=500 aql=0,65 level=s-3 file=lot.csv").unwrap();
+        assert_eq!(query.level, InspectionLevel::S3);
+        assert_eq!(query.samples, Some(Samples::File("lot.csv".to_string())));
+
+        let query = parse_sampling_input("sampling 20h9 lot=500 aql=1 defects=2").unwrap();
+        assert_eq!(query.defects, Some(2));
+
+        assert_eq!(parse_sampling_input("sampling 20h9 lot=500 aql=0.7"), None);
+        assert_eq!(parse_sampling_input("sampling 20h9 aql=1"), None);
+        assert_eq!(
+            parse_sampling_input("sampling 20h9 lot=500 aql=1 level=IV"),
+            None
+        );
+        assert_eq!(
+            parse_sampling_input("sampling 20h9 lot=500 aql=1 defects=1 method=var"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_code_letter() {
+        assert_eq!(
+            CODE_LETTERS[code_letter(1000, InspectionLevel::General2)],
+            'J'
+        );
+        assert_eq!(CODE_LETTERS[code_letter(8, InspectionLevel::General2)], 'A');
+        assert_eq!(CODE_LETTERS[code_letter(3201, InspectionLevel::S4)], 'G');
+        assert_eq!(
+            CODE_LETTERS[code_letter(1_000_000, InspectionLevel::General3)],
+            'R'
+        );
+        assert_eq!(
+            CODE_LETTERS[code_letter(50, InspectionLevel::General1)],
+            'C'
+        );
+    }
+
+    #[test]
+    fn test_attributes_plan() {
+        let expected = |code, sample, accept| AttributesPlan {
+            code,
+            sample,
+            accept,
+            reject: accept + 1,
+        };
+        assert_eq!(
+            plan(1000, InspectionLevel::General2, 1.0),
+            expected('J', 80, 2)
+        );
+        assert_eq!(
+            plan(2000, InspectionLevel::General2, 2.5),
+            expected('K', 125, 7)
+        );
+        assert_eq!(
+            plan(8, InspectionLevel::General2, 0.65),
+            expected('F', 8, 0)
+        );
+        assert_eq!(plan(500, InspectionLevel::S1, 6.5), expected('A', 2, 0));
+        assert_eq!(
+            plan(50, InspectionLevel::General2, 6.5),
+            expected('D', 8, 1)
+        );
+        assert_eq!(
+            plan(50, InspectionLevel::General2, 4.0),
+            expected('E', 13, 1)
+        );
+        assert_eq!(
+            plan(8, InspectionLevel::General2, 10.0),
+            expected('C', 5, 1)
+        );
+        assert_eq!(
+            plan(1000, InspectionLevel::General2, 0.010),
+            expected('Q', 1000, 0)
+        );
+        assert_eq!(
+            plan(1000, InspectionLevel::General2, 10.0),
+            expected('J', 80, 14)
+        );
+        assert_eq!(
+            plan(1_000_000, InspectionLevel::General3, 10.0),
+            expected('K', 125, 21)
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, ContentArrangement, Table};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+
+use crate::fit::{calc_fit, parse_fit_input, search_fit, FitLimits, FitQuery, FitTols};
+use crate::params::Params;
+
+const MAX_GROUPS: usize = 20;
+/// Точность границ групп, мм.
+const GROUP_DECIMALS: u32 = 4;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum GroupCount {
+    Groups(usize),
+    /// Допуск посадки в группе, мм: число групп подбирается по нему.
+    GroupTolerance(Decimal),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SelectQuery {
+    pub fit_query: FitQuery,
+    pub count: GroupCount,
+}
+
+/// Сортировочная группа: отклонения отверстия и вала и предельные зазоры в группе.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SortGroup {
+    pub tols: FitTols,
+    pub limits: FitLimits,
+}
+
+pub fn parse_select_input(input: &str) -> Option<SelectQuery> {
+    let mut tokens = input.strip_prefix("select ")?.split_whitespace();
+    let fit_query = parse_fit_input(tokens.next()?)?;
+    let params = Params::parse(tokens, &["groups", "tol"])?;
+    let count = match (params.number("groups")?, params.number("tol")?) {
+        (Some(groups), None) => {
+            if groups.fract() != 0.0 || !(2.0..=MAX_GROUPS as f64).contains(&groups) {
+                return None;
+            }
+            GroupCount::Groups(groups as usize)
+        }
+        (None, Some(tolerance)) if tolerance > 0.0 => {
+            GroupCount::GroupTolerance(Decimal::from_f64(tolerance)?)
+        }
+        _ => return None,
+    };
+    Some(SelectQuery { fit_query, count })
+}
+
+/// Число групп, при котором допуск посадки в группе (TD + Td) / n не больше заданного.
+pub fn group_count(tols: FitTols, count: &GroupCount) -> usize {
+    match count {
+        GroupCount::Groups(groups) => *groups,
+        GroupCount::GroupTolerance(tolerance) => {
+            let fit_tolerance = (tols.0 .0 - tols.0 .1) + (tols.1 .0 - tols.1 .1);
+            (fit_tolerance / tolerance)
+                .ceil()
+                .to_usize()
+                .unwrap_or(usize::MAX)
+                .max(1)
+        }
+    }
+}
+
+/// Поля допусков отверстия и вала делятся на `groups` равных частей; детали
+/// одноимённых групп собираются между собой.
+pub fn sort_groups(tols: FitTols, groups: usize) -> Vec<SortGroup> {
+    let split = |(upper, lower): (Decimal, Decimal), index: usize| {
+        let step = (upper - lower) / Decimal::from(groups);
+        let bound = |index: usize| {
+            if index == groups {
+                upper
+            } else {
+                (lower + step * Decimal::from(index)).round_dp(GROUP_DECIMALS)
+            }
+        };
+        (bound(index + 1).normalize(), bound(index).normalize())
+    };
+    (0..groups)
+        .map(|index| {
+            let hole = split(tols.0, index);
+            let shaft = split(tols.1, index);
+            SortGroup {
+                tols: (hole, shaft),
+                limits: calc_fit(hole, shaft),
+            }
+        })
+        .collect()
+}
+
+pub fn handle_select(select_query: &SelectQuery) {
+    match search_fit(&select_query.fit_query) {
+        Ok(Some(tols)) => {
+            let groups = group_count(tols, &select_query.count);
+            if groups > MAX_GROUPS {
+                println!(
+                    "Требуется {} групп: больше {} на практике не сортируют",
+                    groups, MAX_GROUPS
+                );
+                return;
+            }
+            print_select(select_query, tols, &sort_groups(tols, groups))
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_select(select_query: &SelectQuery, tols: FitTols, groups: &[SortGroup]) {
+    let fit_query = &select_query.fit_query;
+    let fit_limits = calc_fit(tols.0, tols.1);
+    let hole_tolerance = (tols.0 .0 - tols.0 .1).normalize();
+    let shaft_tolerance = (tols.1 .0 - tols.1 .1).normalize();
+    let mut table_groups = Table::new();
+    table_groups
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("группа"),
+            Cell::new(format!("отверстие {}", fit_query.hole_class())),
+            Cell::new(format!("вал {}", fit_query.shaft_class())),
+            Cell::new("Smin / Nmax"),
+            Cell::new("Smax / Nmin"),
+            Cell::new("посадка"),
+        ]);
+    for (index, group) in groups.iter().enumerate() {
+        let ((hole_upper, hole_lower), (shaft_upper, shaft_lower)) = group.tols;
+        table_groups.add_row(vec![
+            Cell::new(index + 1),
+            Cell::new(format!("{:+} … {:+}", hole_lower, hole_upper)),
+            Cell::new(format!("{:+} … {:+}", shaft_lower, shaft_upper)),
+            Cell::new(group.limits.min_clearance),
+            Cell::new(group.limits.max_clearance),
+            Cell::new(group.limits.fit_type.name()).fg(group.limits.fit_type.color()),
+        ]);
+    }
+    println!(
+        "{}{}/{}: TD = {}, Td = {}, Smin = {}, Smax = {}, допуск посадки {}",
+        fit_query.size,
+        fit_query.hole_class(),
+        fit_query.shaft_class(),
+        hole_tolerance,
+        shaft_tolerance,
+        fit_limits.min_clearance,
+        fit_limits.max_clearance,
+        fit_limits.fit_tolerance
+    );
+    println!("{table_groups}");
+    println!(
+        "Допуск посадки в группе {} вместо {}",
+        groups[0].limits.fit_tolerance, fit_limits.fit_tolerance
+    );
+    if hole_tolerance != shaft_tolerance {
+        println!(
+            "TD ≠ Td: предельные зазоры меняются от группы к группе, \
+             однородность посадки не сохраняется"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_select_input() {
+        let select_query = parse_select_input("select 20H7/g6 groups=3").unwrap();
+        assert_eq!(select_query.fit_query.size, "20");
+        assert_eq!(select_query.count, GroupCount::Groups(3));
+        assert_eq!(
+            parse_select_input("select 20H7/g6 tol=0,01").unwrap().count,
+            GroupCount::GroupTolerance(dec!(0.01))
+        );
+        assert_eq!(parse_select_input("select 20H7/g6"), None);
+        assert_eq!(parse_select_input("select 20H7/g6 groups=1"), None);
+        assert_eq!(parse_select_input("select 20H7/g6 groups=3 tol=0.01"), None);
+        assert_eq!(parse_select_input("select 20H7 groups=3"), None);
+    }
+
+    #[test]
+    fn test_sort_groups() {
+        // 20H6/h6: TD = Td = 0.013.
+        let tols = ((dec!(0.013), dec!(0)), (dec!(0), dec!(-0.013)));
+        assert_eq!(
+            group_count(tols, &GroupCount::GroupTolerance(dec!(0.01))),
+            3
+        );
+        assert_eq!(group_count(tols, &GroupCount::Groups(4)), 4);
+        let groups = sort_groups(tols, 3);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(
+            groups[0].tols,
+            ((dec!(0.0043), dec!(0)), (dec!(-0.0087), dec!(-0.013)))
+        );
+        assert_eq!(
+            groups[2].tols,
+            ((dec!(0.013), dec!(0.0087)), (dec!(0), dec!(-0.0043)))
+        );
+        // Зазоры во всех группах одинаковы с точностью до округления границ.
+        for group in &groups {
+            assert!((group.limits.min_clearance - dec!(0.0087)).abs() <= dec!(0.0001));
+            assert!((group.limits.max_clearance - dec!(0.0173)).abs() <= dec!(0.0001));
+        }
+        assert_eq!(groups[0].limits.fit_tolerance, dec!(0.0086));
+    }
+}
@@ -0,0 +1,592 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use regex::Regex;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::inspection::{search_class_limits, ToleranceClass};
+use crate::params::Params;
+use crate::statistics::{Distribution, Rng};
+use crate::{parse_input, replace_comma_with_dot};
+
+pub const DEFAULT_TRIALS: f64 = 100_000.0;
+pub const MAX_TRIALS: f64 = 10_000_000.0;
+pub const DEFAULT_SEED: f64 = 1.0;
+/// Доля результатов моделирования за каждой из границ поля рассеяния (±3σ).
+pub const TAIL_FRACTION: f64 = 0.00135;
+
+/// Размер звена: поле допуска из таблиц или отклонения, заданные явно.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Source {
+    Class(ToleranceClass),
+    Deviations {
+        nominal: Decimal,
+        upper: Decimal,
+        lower: Decimal,
+    },
+}
+
+impl Source {
+    pub fn name(&self) -> String {
+        match self {
+            Source::Class((size, field, accuracy)) => format!("{}{}{}", size, field, accuracy),
+            Source::Deviations {
+                nominal,
+                upper,
+                lower,
+            } => {
+                if *upper == -*lower && !upper.is_zero() {
+                    format!("{}±{}", nominal, upper)
+                } else {
+                    format!("{} {:+} {:+}", nominal, upper, lower)
+                }
+            }
+        }
+    }
+
+    /// Предельные размеры звена (наибольший, наименьший).
+    pub fn limits(&self) -> Result<Option<(Decimal, Decimal)>, String> {
+        match self {
+            Source::Class(class) => search_class_limits(class),
+            Source::Deviations {
+                nominal,
+                upper,
+                lower,
+            } => Ok(Some((nominal + upper, nominal + lower))),
+        }
+    }
+
+    pub fn nominal(&self) -> Decimal {
+        match self {
+            Source::Class((size, _, _)) => size.parse().unwrap_or_default(),
+            Source::Deviations { nominal, .. } => *nominal,
+        }
+    }
+}
+
+/// Составляющее звено размерной цепи.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Contributor {
+    /// Увеличивающее (+) или уменьшающее (-) звено.
+    pub increasing: bool,
+    pub source: Source,
+    /// None - закон распределения, общий для всей цепи.
+    pub distribution: Option<Distribution>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct StackQuery {
+    pub contributors: Vec<Contributor>,
+    pub distribution: Distribution,
+    pub trials: usize,
+    pub seed: u64,
+    /// Требуемые пределы замыкающего звена (наименьший, наибольший).
+    pub required: (Option<f64>, Option<f64>),
+}
+
+/// Звено с найденными предельными размерами.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Link {
+    pub sign: f64,
+    pub nominal: f64,
+    pub upper: f64,
+    pub lower: f64,
+    pub distribution: Distribution,
+}
+
+impl Link {
+    pub fn tolerance(&self) -> f64 {
+        self.upper - self.lower
+    }
+
+    pub fn sigma(&self) -> f64 {
+        self.distribution.sigma(self.lower, self.upper)
+    }
+}
+
+/// Замыкающее звено: (наименьший, наибольший) размер.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StackResult {
+    pub nominal: f64,
+    pub worst_case: (f64, f64),
+    pub mean: f64,
+    pub sigma: f64,
+    /// Поле рассеяния ±3σ по методу RSS.
+    pub rss: (f64, f64),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MonteCarlo {
+    pub mean: f64,
+    pub sigma: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Квантили 0.135% и 99.865%.
+    pub spread: (f64, f64),
+    /// Доля результатов ниже и выше требуемых пределов.
+    pub below: f64,
+    pub above: f64,
+}
+
+/// Звено вида `+30h7`, `-20g6@uniform`, `+10.5+0.1-0.05`, `-8±0.02` или `+12`.
+pub fn parse_contributor(input: &str) -> Option<Contributor> {
+    let (link, distribution) = match input.split_once('@') {
+        Some((link, distribution)) => (link, Some(Distribution::parse(distribution)?)),
+        None => (input, None),
+    };
+    let increasing = match link.chars().next()? {
+        '+' => true,
+        '-' => false,
+        _ => return None,
+    };
+    Some(Contributor {
+        increasing,
+        source: parse_source(&link[1..])?,
+        distribution,
+    })
+}
+
+/// Поле допуска `30h7` или размер с отклонениями.
+pub fn parse_source(input: &str) -> Option<Source> {
+    if let Some(class) = parse_input(input) {
+        return Some(Source::Class(class));
+    }
+    let (nominal, upper, lower) = parse_dimension(input)?;
+    Some(Source::Deviations {
+        nominal,
+        upper,
+        lower,
+    })
+}
+
+/// Размер с отклонениями `10.5+0.1-0.05`, `8±0.02` или без них `12`:
+/// (номинал, верхнее, нижнее отклонение).
+pub fn parse_dimension(input: &str) -> Option<(Decimal, Decimal, Decimal)> {
+    let number = r"\d+(?:[.,]\d+)?";
+    let regex = Regex::new(&format!(
+        r"^(?P<nominal>{0})(?:(?P<upper>[+-]{0})(?P<lower>[+-]{0})|±(?P<symmetric>{0}))?$",
+        number
+    ))
+    .expect("Ошибка обработки RegEx");
+    let captures = regex.captures(input)?;
+    let decimal = |name: &str| -> Option<Decimal> {
+        match captures.name(name) {
+            Some(value) => replace_comma_with_dot(value.as_str()).parse().ok(),
+            None => Some(Decimal::ZERO),
+        }
+    };
+    let nominal = decimal("nominal")?;
+    let (upper, lower) = match captures.name("symmetric") {
+        Some(_) => (decimal("symmetric")?, -decimal("symmetric")?),
+        None => (decimal("upper")?, decimal("lower")?),
+    };
+    if upper < lower {
+        return None;
+    }
+    Some((nominal, upper, lower))
+}
+
+pub fn parse_stack_input(input: &str) -> Option<StackQuery> {
+    let tokens = input.strip_prefix("stack ")?.split_whitespace();
+    let (params, links): (Vec<&str>, Vec<&str>) = tokens.partition(|token| token.contains('='));
+    let params = Params::parse(params.into_iter(), &["dist", "n", "seed", "min", "max"])?;
+    let contributors = links
+        .into_iter()
+        .map(parse_contributor)
+        .collect::<Option<Vec<Contributor>>>()?;
+    if contributors.is_empty() {
+        return None;
+    }
+    let distribution = match params.text("dist") {
+        Some(distribution) => Distribution::parse(distribution)?,
+        None => Distribution::Normal(3.0),
+    };
+    let trials = params.number_or("n", DEFAULT_TRIALS)?;
+    let seed = params.number_or("seed", DEFAULT_SEED)?;
+    if trials.fract() != 0.0 || !(1000.0..=MAX_TRIALS).contains(&trials) || seed < 0.0 {
+        return None;
+    }
+    Some(StackQuery {
+        contributors,
+        distribution,
+        trials: trials as usize,
+        seed: seed as u64,
+        required: (params.number("min")?, params.number("max")?),
+    })
+}
+
+/// Предельные размеры звеньев. Ok(None) - поле допуска звена не найдено.
+pub fn resolve_links(
+    contributors: &[Contributor],
+    distribution: Distribution,
+) -> Result<Option<Vec<Link>>, String> {
+    let mut links = Vec::new();
+    for contributor in contributors {
+        let (upper, lower) = match contributor.source.limits()? {
+            Some(limits) => limits,
+            None => return Ok(None),
+        };
+        links.push(Link {
+            sign: if contributor.increasing { 1.0 } else { -1.0 },
+            nominal: contributor.source.nominal().to_f64().unwrap_or_default(),
+            upper: upper.to_f64().unwrap_or_default(),
+            lower: lower.to_f64().unwrap_or_default(),
+            distribution: contributor.distribution.unwrap_or(distribution),
+        });
+    }
+    Ok(Some(links))
+}
+
+/// Замыкающее звено методом максимума-минимума и вероятностным методом (RSS):
+/// центры распределений складываются с учётом направления, σ - квадратично.
+pub fn calc_stack(links: &[Link]) -> StackResult {
+    let nominal = links.iter().map(|link| link.sign * link.nominal).sum();
+    let max = links
+        .iter()
+        .map(|link| {
+            if link.sign > 0.0 {
+                link.upper
+            } else {
+                -link.lower
+            }
+        })
+        .sum();
+    let min = links
+        .iter()
+        .map(|link| {
+            if link.sign > 0.0 {
+                link.lower
+            } else {
+                -link.upper
+            }
+        })
+        .sum();
+    let mean: f64 = links
+        .iter()
+        .map(|link| link.sign * link.distribution.mean(link.lower, link.upper))
+        .sum();
+    let sigma = links
+        .iter()
+        .map(|link| link.sigma().powi(2))
+        .sum::<f64>()
+        .sqrt();
+    StackResult {
+        nominal,
+        worst_case: (min, max),
+        mean,
+        sigma,
+        rss: (mean - 3.0 * sigma, mean + 3.0 * sigma),
+    }
+}
+
+/// Вклад звеньев, %: в допуск замыкающего звена (максимум-минимум) и в его дисперсию.
+pub fn contributions(links: &[Link]) -> Vec<(f64, f64)> {
+    let tolerance: f64 = links.iter().map(Link::tolerance).sum();
+    let variance: f64 = links.iter().map(|link| link.sigma().powi(2)).sum();
+    let percent = |value: f64, total: f64| {
+        if total > 0.0 {
+            value / total * 100.0
+        } else {
+            0.0
+        }
+    };
+    links
+        .iter()
+        .map(|link| {
+            (
+                percent(link.tolerance(), tolerance),
+                percent(link.sigma().powi(2), variance),
+            )
+        })
+        .collect()
+}
+
+/// Моделирование методом Монте-Карло: размеры звеньев независимы.
+pub fn monte_carlo(
+    links: &[Link],
+    trials: usize,
+    seed: u64,
+    required: (Option<f64>, Option<f64>),
+) -> MonteCarlo {
+    let mut rng = Rng::new(seed);
+    let results: Vec<f64> = (0..trials)
+        .map(|_| {
+            links
+                .iter()
+                .map(|link| link.sign * link.distribution.sample(&mut rng, link.lower, link.upper))
+                .sum()
+        })
+        .collect();
+    summarize(results, required)
+}
+
+/// Статистика результатов моделирования и доли выхода за требуемые пределы.
+pub fn summarize(mut results: Vec<f64>, required: (Option<f64>, Option<f64>)) -> MonteCarlo {
+    results.sort_by(|a, b| a.total_cmp(b));
+    let count = results.len() as f64;
+    let mean = results.iter().sum::<f64>() / count;
+    let sigma = (results
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / (count - 1.0))
+        .sqrt();
+    let quantile = |fraction: f64| results[((count - 1.0) * fraction).round() as usize];
+    let share = |outside: &dyn Fn(f64) -> bool| {
+        results.iter().filter(|value| outside(**value)).count() as f64 / count
+    };
+    MonteCarlo {
+        mean,
+        sigma,
+        min: results[0],
+        max: results[results.len() - 1],
+        spread: (quantile(TAIL_FRACTION), quantile(1.0 - TAIL_FRACTION)),
+        below: required.0.map_or(0.0, |min| share(&|value| value < min)),
+        above: required.1.map_or(0.0, |max| share(&|value| value > max)),
+    }
+}
+
+pub fn handle_stack(stack_query: &StackQuery) {
+    match resolve_links(&stack_query.contributors, stack_query.distribution) {
+        Ok(Some(links)) => {
+            let result = calc_stack(&links);
+            let simulation = monte_carlo(
+                &links,
+                stack_query.trials,
+                stack_query.seed,
+                stack_query.required,
+            );
+            print_stack(stack_query, &links, &result, &simulation)
+        }
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_stack(
+    stack_query: &StackQuery,
+    links: &[Link],
+    result: &StackResult,
+    simulation: &MonteCarlo,
+) {
+    let mut table_links = Table::new();
+    table_links
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("звено"),
+            Cell::new("наибольший").fg(Color::Red),
+            Cell::new("наименьший").fg(Color::Cyan),
+            Cell::new("допуск").fg(Color::Blue),
+            Cell::new("закон"),
+            Cell::new("чувствит."),
+            Cell::new("вклад max-min, %"),
+            Cell::new("вклад RSS, %"),
+        ]);
+    for ((contributor, link), (worst_case, rss)) in stack_query
+        .contributors
+        .iter()
+        .zip(links)
+        .zip(contributions(links))
+    {
+        table_links.add_row(vec![
+            Cell::new(format!(
+                "{}{}",
+                if contributor.increasing { "+" } else { "-" },
+                contributor.source.name()
+            )),
+            Cell::new(link.upper).fg(Color::Red),
+            Cell::new(link.lower).fg(Color::Cyan),
+            Cell::new(format!("{:.4}", link.tolerance())).fg(Color::Blue),
+            Cell::new(link.distribution.name()),
+            Cell::new(format!("{:+}", link.sign)),
+            Cell::new(format!("{:.1}", worst_case)),
+            Cell::new(format!("{:.1}", rss)),
+        ]);
+    }
+    println!("{table_links}");
+
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new(format!("замыкающее звено, номинал {:.4}", result.nominal)),
+            Cell::new("наименьший").fg(Color::Cyan),
+            Cell::new("наибольший").fg(Color::Red),
+            Cell::new("допуск").fg(Color::Blue),
+        ]);
+    for (name, (min, max)) in [
+        ("максимум-минимум", result.worst_case),
+        ("RSS (±3σ)", result.rss),
+        ("Монте-Карло (0.135% .. 99.865%)", simulation.spread),
+        ("Монте-Карло (min .. max)", (simulation.min, simulation.max)),
+    ] {
+        table_result.add_row(vec![
+            Cell::new(name),
+            Cell::new(format!("{:.4}", min)).fg(Color::Cyan),
+            Cell::new(format!("{:.4}", max)).fg(Color::Red),
+            Cell::new(format!("{:.4}", max - min)).fg(Color::Blue),
+        ]);
+    }
+    println!("{table_result}");
+    println!(
+        "Середина поля {:.4}, σ = {:.5}; Монте-Карло ({} испытаний): среднее {:.4}, σ = {:.5}",
+        result.mean, result.sigma, stack_query.trials, simulation.mean, simulation.sigma
+    );
+
+    let (min, max) = stack_query.required;
+    if min.is_some() || max.is_some() {
+        let worst_ok = min.is_none_or(|min| result.worst_case.0 >= min)
+            && max.is_none_or(|max| result.worst_case.1 <= max);
+        let mut table_required = Table::new();
+        table_required
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![
+                format!(
+                    "требование {} .. {}",
+                    min.map_or("-".to_string(), |min| min.to_string()),
+                    max.map_or("-".to_string(), |max| max.to_string())
+                ),
+                "".to_string(),
+            ])
+            .add_row(vec![
+                Cell::new("максимум-минимум"),
+                if worst_ok {
+                    Cell::new("выполняется при любых размерах звеньев").fg(Color::Green)
+                } else {
+                    Cell::new("не гарантируется").fg(Color::Red)
+                },
+            ])
+            .add_row(vec![
+                Cell::new("Монте-Карло: ниже / выше, ppm"),
+                Cell::new(format!(
+                    "{:.0} / {:.0}",
+                    simulation.below * 1e6,
+                    simulation.above * 1e6
+                ))
+                .fg(if simulation.below + simulation.above > 0.0 {
+                    Color::Red
+                } else {
+                    Color::Green
+                }),
+            ]);
+        println!("{table_required}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, epsilon: f64) {
+        assert!(
+            (actual - expected).abs() < epsilon,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    fn link(sign: f64, nominal: f64, upper: f64, lower: f64) -> Link {
+        Link {
+            sign,
+            nominal,
+            upper: nominal + upper,
+            lower: nominal + lower,
+            distribution: Distribution::Normal(3.0),
+        }
+    }
+
+    #[test]
+    fn test_parse_contributor() {
+        assert_eq!(
+            parse_contributor("-20g6@uniform").unwrap(),
+            Contributor {
+                increasing: false,
+                source: Source::Class(("20".to_string(), "g".to_string(), "6".to_string())),
+                distribution: Some(Distribution::Uniform),
+            }
+        );
+        let contributor = parse_contributor("+10,5+0.1-0,05").unwrap();
+        assert_eq!(
+            contributor.source,
+            Source::Deviations {
+                nominal: dec!(10.5),
+                upper: dec!(0.1),
+                lower: dec!(-0.05),
+            }
+        );
+        assert_eq!(contributor.source.name(), "10.5 +0.1 -0.05");
+        let contributor = parse_contributor("-8±0.02@normal:4").unwrap();
+        assert_eq!(contributor.source.name(), "8±0.02");
+        assert_eq!(contributor.distribution, Some(Distribution::Normal(4.0)));
+        assert_eq!(
+            parse_contributor("+12").unwrap().source.limits(),
+            Ok(Some((dec!(12), dec!(12))))
+        );
+        assert_eq!(parse_contributor("30h7"), None);
+        assert_eq!(parse_contributor("+10-0.1+0.1"), None);
+        assert_eq!(parse_contributor("+30h7@gauss"), None);
+    }
+
+    #[test]
+    fn test_parse_stack_input() {
+        let query = parse_stack_input("stack +30h7 -10±0.1 dist=uniform n=5000 max=20.2").unwrap();
+        assert_eq!(query.contributors.len(), 2);
+        assert_eq!(query.distribution, Distribution::Uniform);
+        assert_eq!(query.trials, 5000);
+        assert_eq!(query.seed, 1);
+        assert_eq!(query.required, (None, Some(20.2)));
+        assert_eq!(parse_stack_input("stack dist=uniform"), None);
+        assert_eq!(parse_stack_input("stack +30h7 n=10"), None);
+        assert_eq!(parse_stack_input("stack +30h7 30h7"), None);
+    }
+
+    #[test]
+    fn test_calc_stack() {
+        let links = [
+            link(1.0, 50.0, 0.1, -0.1),
+            link(-1.0, 20.0, 0.05, -0.05),
+            link(-1.0, 10.0, 0.0, -0.1),
+        ];
+        let result = calc_stack(&links);
+        assert_close(result.nominal, 20.0, 1e-12);
+        assert_close(result.worst_case.0, 49.9 - 20.05 - 10.0, 1e-9);
+        assert_close(result.worst_case.1, 50.1 - 19.95 - 9.9, 1e-9);
+        assert_close(result.mean, 20.05, 1e-9);
+        let sigma = (0.2f64.powi(2) + 0.1f64.powi(2) + 0.1f64.powi(2)).sqrt() / 6.0;
+        assert_close(result.sigma, sigma, 1e-12);
+        assert_close(result.rss.1 - result.rss.0, 6.0 * sigma, 1e-12);
+
+        let contributions = contributions(&links);
+        assert_close(contributions[0].0, 50.0, 1e-9);
+        assert_close(contributions[0].1, 200.0 / 3.0, 1e-9);
+        assert_close(contributions[2].1, 100.0 / 6.0, 1e-9);
+    }
+
+    #[test]
+    fn test_monte_carlo() {
+        let links = [link(1.0, 50.0, 0.1, -0.1), link(-1.0, 20.0, 0.05, -0.05)];
+        let result = calc_stack(&links);
+        let simulation = monte_carlo(&links, 200_000, 7, (Some(29.9), None));
+        assert_close(simulation.mean, result.mean, 1e-3);
+        assert_close(simulation.sigma, result.sigma, 1e-3);
+        assert_close(simulation.spread.0, result.rss.0, 5e-3);
+        assert_close(simulation.spread.1, result.rss.1, 5e-3);
+        let sigma_below = 0.1 / result.sigma;
+        assert_close(
+            simulation.below,
+            crate::statistics::normal_cdf(-sigma_below),
+            5e-4,
+        );
+        assert_eq!(simulation.above, 0.0);
+        assert_eq!(
+            monte_carlo(&links, 1000, 7, (None, None)),
+            monte_carlo(&links, 1000, 7, (None, None))
+        );
+    }
+}
@@ -0,0 +1,536 @@
+use core::f64::consts::{FRAC_1_SQRT_2, PI};
+
+/// Закон распределения действительных размеров в пределах поля допуска.
+/// Центр распределения совпадает с серединой поля, кроме смещённого закона.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Distribution {
+    /// Нормальный закон, границы поля допуска лежат на ±k·σ от середины.
+    Normal(f64),
+    Uniform,
+    /// Закон Симпсона: треугольная плотность с вершиной в середине поля.
+    Triangular,
+    /// Нормальный закон, заданный пользователем: центр смещён от середины поля
+    /// на `asymmetry`·T/2 (коэффициент относительной асимметрии по ГОСТ 16320),
+    /// σ = `spread`·T.
+    Shifted {
+        asymmetry: f64,
+        spread: f64,
+    },
+}
+
+impl Distribution {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input {
+            "normal" => Some(Distribution::Normal(3.0)),
+            "uniform" => Some(Distribution::Uniform),
+            "triangular" => Some(Distribution::Triangular),
+            _ if input.starts_with("shifted:") => {
+                let mut numbers = input["shifted:".len()..]
+                    .split(':')
+                    .map(|number| number.replace(',', ".").parse::<f64>().ok());
+                let asymmetry = numbers.next()??;
+                let spread = numbers.next()??;
+                let valid = numbers.next().is_none()
+                    && (-1.0..=1.0).contains(&asymmetry)
+                    && spread > 0.0
+                    && spread <= 1.0;
+                valid.then_some(Distribution::Shifted { asymmetry, spread })
+            }
+            _ => {
+                let sigmas: f64 = input
+                    .strip_prefix("normal:")?
+                    .replace(',', ".")
+                    .parse()
+                    .ok()?;
+                if sigmas > 0.0 {
+                    Some(Distribution::Normal(sigmas))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Distribution::Normal(sigmas) => format!("нормальный (±{}σ)", sigmas),
+            Distribution::Uniform => "равномерный".to_string(),
+            Distribution::Triangular => "треугольный (Симпсона)".to_string(),
+            Distribution::Shifted { asymmetry, spread } => {
+                format!("смещённый (α = {}, σ = {}·T)", asymmetry, spread)
+            }
+        }
+    }
+
+    pub fn sigma(&self, lower: f64, upper: f64) -> f64 {
+        match self {
+            Distribution::Normal(sigmas) => (upper - lower) / (2.0 * sigmas),
+            Distribution::Uniform => (upper - lower) / 12f64.sqrt(),
+            Distribution::Triangular => (upper - lower) / 24f64.sqrt(),
+            Distribution::Shifted { spread, .. } => (upper - lower) * spread,
+        }
+    }
+
+    /// Смещение центра распределения от середины поля.
+    pub fn mean_shift(&self, lower: f64, upper: f64) -> f64 {
+        match self {
+            Distribution::Shifted { asymmetry, .. } => asymmetry * (upper - lower) * 0.5,
+            _ => 0.0,
+        }
+    }
+
+    /// Центр распределения (математическое ожидание размера).
+    pub fn mean(&self, lower: f64, upper: f64) -> f64 {
+        (upper + lower) * 0.5 + self.mean_shift(lower, upper)
+    }
+
+    /// Интервал, за пределами которого плотность пренебрежимо мала.
+    pub fn support(&self, lower: f64, upper: f64) -> (f64, f64) {
+        match self {
+            Distribution::Normal(_) | Distribution::Shifted { .. } => {
+                let mean = self.mean(lower, upper);
+                let sigma = self.sigma(lower, upper);
+                (mean - 8.0 * sigma, mean + 8.0 * sigma)
+            }
+            Distribution::Uniform | Distribution::Triangular => (lower, upper),
+        }
+    }
+
+    pub fn pdf(&self, x: f64, lower: f64, upper: f64) -> f64 {
+        match self {
+            Distribution::Normal(_) | Distribution::Shifted { .. } => {
+                let sigma = self.sigma(lower, upper);
+                if sigma == 0.0 {
+                    return 0.0;
+                }
+                let z = (x - self.mean(lower, upper)) / sigma;
+                (-0.5 * z * z).exp() / (sigma * (2.0 * PI).sqrt())
+            }
+            Distribution::Uniform => {
+                if x < lower || x > upper || upper == lower {
+                    0.0
+                } else {
+                    1.0 / (upper - lower)
+                }
+            }
+            Distribution::Triangular => {
+                let half = (upper - lower) * 0.5;
+                if x < lower || x > upper || half == 0.0 {
+                    0.0
+                } else {
+                    (half - (x - (upper + lower) * 0.5).abs()) / (half * half)
+                }
+            }
+        }
+    }
+
+    pub fn cdf(&self, x: f64, lower: f64, upper: f64) -> f64 {
+        match self {
+            Distribution::Normal(_) | Distribution::Shifted { .. } => {
+                let sigma = self.sigma(lower, upper);
+                let mean = self.mean(lower, upper);
+                if sigma == 0.0 {
+                    return if x < mean { 0.0 } else { 1.0 };
+                }
+                normal_cdf((x - mean) / sigma)
+            }
+            Distribution::Uniform => {
+                if x <= lower {
+                    0.0
+                } else if x >= upper {
+                    1.0
+                } else {
+                    (x - lower) / (upper - lower)
+                }
+            }
+            Distribution::Triangular => {
+                let width = upper - lower;
+                if x <= lower {
+                    0.0
+                } else if x >= upper {
+                    1.0
+                } else if x < (upper + lower) * 0.5 {
+                    2.0 * ((x - lower) / width).powi(2)
+                } else {
+                    1.0 - 2.0 * ((upper - x) / width).powi(2)
+                }
+            }
+        }
+    }
+
+    /// Случайное значение размера с центром распределения в середине поля.
+    pub fn sample(&self, rng: &mut Rng, lower: f64, upper: f64) -> f64 {
+        match self {
+            Distribution::Normal(_) | Distribution::Shifted { .. } => {
+                self.mean(lower, upper) + self.sigma(lower, upper) * rng.next_normal()
+            }
+            Distribution::Uniform => lower + (upper - lower) * rng.next_f64(),
+            // Сумма двух равномерно распределённых величин.
+            Distribution::Triangular => {
+                lower + (upper - lower) * (rng.next_f64() + rng.next_f64()) * 0.5
+            }
+        }
+    }
+}
+
+/// Генератор псевдослучайных чисел SplitMix64: воспроизводимые результаты
+/// моделирования при одинаковом зерне.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Равномерно распределённое число в [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Стандартное нормальное число, преобразование Бокса-Мюллера.
+    pub fn next_normal(&mut self) -> f64 {
+        let radius = (-2.0 * (1.0 - self.next_f64()).ln()).sqrt();
+        radius * (2.0 * PI * self.next_f64()).cos()
+    }
+}
+
+/// Функция ошибок, приближение Абрамовица-Стиган 7.1.26 (погрешность < 1.5e-7).
+pub fn erf(x: f64) -> f64 {
+    let sign = if x < 0.0 { -1.0 } else { 1.0 };
+    let x = x.abs();
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    sign * (1.0 - poly * (-x * x).exp())
+}
+
+pub fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z * FRAC_1_SQRT_2))
+}
+
+/// Квантиль стандартного нормального распределения (обращение `normal_cdf` делением пополам).
+pub fn normal_quantile(probability: f64) -> f64 {
+    let (mut low, mut high) = (-10.0, 10.0);
+    for _ in 0..100 {
+        let middle = (low + high) * 0.5;
+        if normal_cdf(middle) < probability {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    (low + high) * 0.5
+}
+
+/// Логарифм гамма-функции, приближение Ланцоша (g = 7, 9 коэффициентов).
+pub fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Регуляризованная неполная бета-функция I_x(a, b), цепная дробь по Ленцу.
+pub fn incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    if x > (a + 1.0) / (a + b + 2.0) {
+        return 1.0 - incomplete_beta(1.0 - x, b, a);
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp() / a;
+    let tiny = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    d = 1.0 / if d.abs() < tiny { tiny } else { d };
+    let mut fraction = d;
+    for m in 1..300 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            d = 1.0 / if d.abs() < tiny { tiny } else { d };
+            c = 1.0 + numerator / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            fraction *= c * d;
+        }
+        if (c * d - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+    front * fraction
+}
+
+/// Вероятность превышения значения `f` для распределения Фишера с `df1`, `df2` степенями свободы.
+pub fn f_distribution_sf(f: f64, df1: f64, df2: f64) -> f64 {
+    if f <= 0.0 {
+        return 1.0;
+    }
+    incomplete_beta(df2 / (df2 + df1 * f), df2 * 0.5, df1 * 0.5)
+}
+
+/// Коэффициенты d2 для оценки σ по размаху в подгруппах объёмом 2-10.
+const D2: [f64; 9] = [
+    1.128, 1.693, 2.059, 2.326, 2.534, 2.704, 2.847, 2.970, 3.078,
+];
+
+/// Коэффициенты контрольных карт X̄-R для подгрупп объёмом 2-10.
+const A2: [f64; 9] = [
+    1.880, 1.023, 0.729, 0.577, 0.483, 0.419, 0.373, 0.337, 0.308,
+];
+const D3: [f64; 9] = [0.0, 0.0, 0.0, 0.0, 0.0, 0.076, 0.136, 0.184, 0.223];
+const D4: [f64; 9] = [
+    3.267, 2.574, 2.282, 2.114, 2.004, 1.924, 1.864, 1.816, 1.777,
+];
+
+pub fn d2(subgroup: usize) -> Option<f64> {
+    D2.get(subgroup.checked_sub(2)?).copied()
+}
+
+pub fn a2(subgroup: usize) -> Option<f64> {
+    A2.get(subgroup.checked_sub(2)?).copied()
+}
+
+pub fn d3(subgroup: usize) -> Option<f64> {
+    D3.get(subgroup.checked_sub(2)?).copied()
+}
+
+pub fn d4(subgroup: usize) -> Option<f64> {
+    D4.get(subgroup.checked_sub(2)?).copied()
+}
+
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Выборочное стандартное отклонение (делитель n - 1).
+pub fn std_dev(values: &[f64]) -> f64 {
+    let mean = mean(values);
+    let sum: f64 = values.iter().map(|value| (value - mean).powi(2)).sum();
+    (sum / (values.len() as f64 - 1.0)).sqrt()
+}
+
+pub fn range(values: &[f64]) -> f64 {
+    let max = values.iter().copied().fold(f64::MIN, f64::max);
+    let min = values.iter().copied().fold(f64::MAX, f64::min);
+    max - min
+}
+
+/// Внутригрупповое σ: R̄/d2 по последовательным подгруппам объёма `subgroup`
+/// (неполная последняя отбрасывается), при объёме 1 - по скользящему размаху MR̄/1.128.
+pub fn within_sigma(values: &[f64], subgroup: usize) -> Option<f64> {
+    if subgroup == 1 {
+        let ranges: Vec<f64> = values
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .collect();
+        if ranges.is_empty() {
+            return None;
+        }
+        return Some(mean(&ranges) / d2(2)?);
+    }
+    let ranges: Vec<f64> = values.chunks_exact(subgroup).map(range).collect();
+    if ranges.is_empty() {
+        return None;
+    }
+    Some(mean(&ranges) / d2(subgroup)?)
+}
+
+/// Интеграл функции по формуле Симпсона с чётным числом интервалов.
+pub fn integrate<F: Fn(f64) -> f64>(f: F, from: f64, to: f64, intervals: usize) -> f64 {
+    if to <= from {
+        return 0.0;
+    }
+    let intervals = intervals + intervals % 2;
+    let step = (to - from) / intervals as f64;
+    let sum: f64 = (1..intervals)
+        .map(|i| {
+            let weight = if i % 2 == 1 { 4.0 } else { 2.0 };
+            weight * f(from + step * i as f64)
+        })
+        .sum();
+    (f(from) + f(to) + sum) * step / 3.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, epsilon: f64) {
+        assert!(
+            (actual - expected).abs() < epsilon,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_distribution() {
+        assert_eq!(
+            Distribution::parse("normal"),
+            Some(Distribution::Normal(3.0))
+        );
+        assert_eq!(
+            Distribution::parse("normal:4"),
+            Some(Distribution::Normal(4.0))
+        );
+        assert_eq!(
+            Distribution::parse("normal:2,5"),
+            Some(Distribution::Normal(2.5))
+        );
+        assert_eq!(Distribution::parse("uniform"), Some(Distribution::Uniform));
+        assert_eq!(
+            Distribution::parse("triangular"),
+            Some(Distribution::Triangular)
+        );
+        assert_eq!(
+            Distribution::parse("shifted:0,2:0.15"),
+            Some(Distribution::Shifted {
+                asymmetry: 0.2,
+                spread: 0.15
+            })
+        );
+        assert_eq!(Distribution::parse("shifted:1.5:0.15"), None);
+        assert_eq!(Distribution::parse("shifted:0.2"), None);
+        assert_eq!(Distribution::parse("shifted:0.2:0"), None);
+        assert_eq!(Distribution::parse("normal:0"), None);
+        assert_eq!(Distribution::parse("gauss"), None);
+    }
+
+    #[test]
+    fn test_normal_cdf() {
+        assert_close(normal_cdf(0.0), 0.5, 1e-7);
+        assert_close(normal_cdf(1.0), 0.841344746, 1e-6);
+        assert_close(normal_cdf(-3.0), 0.001349898, 1e-6);
+        assert_close(normal_cdf(1.959964), 0.975, 1e-6);
+        assert_close(normal_quantile(0.975), 1.959964, 1e-5);
+        assert_close(normal_quantile(0.1), -1.281552, 1e-5);
+    }
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(42);
+        let first = rng.next_u64();
+        assert_eq!(Rng::new(42).next_u64(), first);
+        let values: Vec<f64> = (0..20000).map(|_| rng.next_f64()).collect();
+        assert!(values.iter().all(|value| (0.0..1.0).contains(value)));
+        assert_close(mean(&values), 0.5, 0.01);
+        let values: Vec<f64> = (0..20000).map(|_| rng.next_normal()).collect();
+        assert_close(mean(&values), 0.0, 0.03);
+        assert_close(std_dev(&values), 1.0, 0.03);
+        let values: Vec<f64> = (0..20000)
+            .map(|_| Distribution::Normal(3.0).sample(&mut rng, 0.0, 0.06))
+            .collect();
+        assert_close(mean(&values), 0.03, 0.0005);
+        assert_close(std_dev(&values), 0.01, 0.0005);
+    }
+
+    #[test]
+    fn test_distribution_cdf() {
+        let normal = Distribution::Normal(3.0);
+        assert_close(normal.sigma(0.0, 0.06), 0.01, 1e-12);
+        assert_close(normal.cdf(0.06, 0.0, 0.06), 0.998650102, 1e-6);
+        assert_close(Distribution::Uniform.cdf(0.015, 0.0, 0.06), 0.25, 1e-12);
+        assert_close(Distribution::Uniform.cdf(0.07, 0.0, 0.06), 1.0, 1e-12);
+        let triangular = Distribution::Triangular;
+        assert_close(triangular.sigma(0.0, 0.06), 0.06 / 24f64.sqrt(), 1e-12);
+        assert_close(triangular.cdf(0.015, 0.0, 0.06), 0.125, 1e-12);
+        assert_close(triangular.cdf(0.03, 0.0, 0.06), 0.5, 1e-12);
+        assert_close(triangular.cdf(0.045, 0.0, 0.06), 0.875, 1e-12);
+        assert_close(triangular.pdf(0.03, 0.0, 0.06), 1.0 / 0.03, 1e-9);
+        assert_close(
+            integrate(|x| triangular.pdf(x, 0.0, 0.06), 0.0, 0.06, 1000),
+            1.0,
+            1e-9,
+        );
+        let mut rng = Rng::new(7);
+        let values: Vec<f64> = (0..20000)
+            .map(|_| triangular.sample(&mut rng, 0.0, 0.06))
+            .collect();
+        assert!(values.iter().all(|value| (0.0..=0.06).contains(value)));
+        assert_close(mean(&values), 0.03, 0.0005);
+        assert_close(std_dev(&values), triangular.sigma(0.0, 0.06), 0.0005);
+    }
+
+    #[test]
+    fn test_f_distribution() {
+        assert_close(ln_gamma(5.0), 24f64.ln(), 1e-10);
+        assert_close(ln_gamma(0.5), PI.sqrt().ln(), 1e-10);
+        assert_close(incomplete_beta(0.5, 2.0, 2.0), 0.5, 1e-10);
+        assert_close(incomplete_beta(0.3, 1.0, 1.0), 0.3, 1e-10);
+        assert_close(f_distribution_sf(1.0, 4.0, 4.0), 0.5, 1e-9);
+        assert_close(f_distribution_sf(3.0, 2.0, 10.0), 1.6f64.powi(-5), 1e-9);
+        assert_close(f_distribution_sf(4.1, 3.0, 20.0), 0.020, 1e-3);
+    }
+
+    #[test]
+    fn test_descriptive_statistics() {
+        let values = [10.0, 10.2, 9.8, 10.1, 9.9];
+        assert_close(mean(&values), 10.0, 1e-12);
+        assert_close(std_dev(&values), 0.158113883, 1e-9);
+        assert_close(range(&values), 0.4, 1e-12);
+        assert_close(within_sigma(&values, 1).unwrap(), 0.243794326, 1e-9);
+        let values = [10.0, 10.2, 9.8, 10.1, 9.9, 10.3, 10.0];
+        assert_close(within_sigma(&values, 3).unwrap(), 0.236266982, 1e-9);
+        assert_eq!(within_sigma(&values, 11), None);
+        assert_eq!(within_sigma(&[10.0], 1), None);
+        assert_eq!(d2(5), Some(2.326));
+        assert_eq!(d2(1), None);
+        assert_eq!(a2(3), Some(1.023));
+        assert_eq!(d3(7), Some(0.076));
+        assert_eq!(d4(10), Some(1.777));
+        assert_eq!(d4(11), None);
+    }
+
+    #[test]
+    fn test_integrate() {
+        assert_close(integrate(|x| x * x, 0.0, 3.0, 10), 9.0, 1e-9);
+        let normal = Distribution::Normal(3.0);
+        let (from, to) = normal.support(-0.01, 0.01);
+        assert_close(
+            integrate(|x| normal.pdf(x, -0.01, 0.01), from, to, 400),
+            1.0,
+            1e-9,
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, ContentArrangement, Table};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy::MidpointAwayFromZero;
+
+use crate::fit::{calc_fit, parse_fit_input, search_fit, FitLimits, FitQuery, FitTols};
+use crate::materials::{Material, REFERENCE_TEMPERATURE};
+use crate::params::Params;
+
+/// Наибольшее число строк таблицы зазоров по диапазону температур.
+const MAX_ROWS: usize = 200;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ThermalQuery {
+    pub fit_query: FitQuery,
+    pub hole: Material,
+    pub shaft: Material,
+    pub hole_temperature: f64,
+    pub shaft_temperature: f64,
+    /// Диапазон (от, до, шаг) для таблицы зазоров при одинаковой температуре деталей.
+    pub range: (f64, f64, f64),
+}
+
+pub fn parse_thermal_input(input: &str) -> Option<ThermalQuery> {
+    let mut tokens = input.strip_prefix("thermal")?.split_whitespace();
+    let fit_query = parse_fit_input(tokens.next()?)?;
+    let mut keys: Vec<String> = ["th", "ts", "from", "to", "step"]
+        .iter()
+        .map(|key| key.to_string())
+        .collect();
+    keys.extend(Material::param_keys("hole", "h"));
+    keys.extend(Material::param_keys("shaft", "s"));
+    let allowed: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let params = Params::parse(tokens, &allowed)?;
+
+    let hole_temperature = params.number_or("th", REFERENCE_TEMPERATURE)?;
+    let query = ThermalQuery {
+        fit_query,
+        hole: Material::from_params(&params, "hole", "h")?,
+        shaft: Material::from_params(&params, "shaft", "s")?,
+        hole_temperature,
+        shaft_temperature: params.number_or("ts", hole_temperature)?,
+        range: (
+            params.number_or("from", -40.0)?,
+            params.number_or("to", 120.0)?,
+            params.number_or("step", 20.0)?,
+        ),
+    };
+    let (from, to, step) = query.range;
+    let temperatures = [query.hole_temperature, query.shaft_temperature, from, to];
+    let alphas = [query.hole.thermal_expansion, query.shaft.thermal_expansion];
+    // NaN, бесконечность и непредставимое удлинение иначе дали бы нулевое расширение.
+    let expansion_valid = temperatures.iter().all(|temperature| {
+        alphas
+            .iter()
+            .all(|alpha| Decimal::from_f64(alpha * (temperature - REFERENCE_TEMPERATURE)).is_some())
+    });
+    let valid = expansion_valid
+        && step.is_finite()
+        && from <= to
+        && step > 0.0
+        && (to - from) / step < MAX_ROWS as f64;
+    valid.then_some(query)
+}
+
+/// Размер детали из материала с коэффициентом `alpha` при температуре `temperature`.
+pub fn size_at_temperature(size: Decimal, alpha: f64, temperature: f64) -> Decimal {
+    let factor =
+        Decimal::from_f64(alpha * (temperature - REFERENCE_TEMPERATURE)).unwrap_or_default();
+    Decimal::round_dp_with_strategy(&(size + size * factor), 6, MidpointAwayFromZero).normalize()
+}
+
+/// Отклонения отверстия и вала от номинала при рабочих температурах.
+pub fn tols_at_temperatures(
+    size: Decimal,
+    tols: FitTols,
+    alphas: (f64, f64),
+    temperatures: (f64, f64),
+) -> FitTols {
+    let deviation = |tol: Decimal, alpha: f64, temperature: f64| {
+        size_at_temperature(size + tol, alpha, temperature) - size
+    };
+    (
+        (
+            deviation(tols.0 .0, alphas.0, temperatures.0),
+            deviation(tols.0 .1, alphas.0, temperatures.0),
+        ),
+        (
+            deviation(tols.1 .0, alphas.1, temperatures.1),
+            deviation(tols.1 .1, alphas.1, temperatures.1),
+        ),
+    )
+}
+
+pub fn handle_thermal(thermal_query: &ThermalQuery) {
+    match search_fit(&thermal_query.fit_query) {
+        Ok(Some(tols)) => print_thermal(thermal_query, tols),
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn fit_cells(fit_limits: &FitLimits) -> Vec<Cell> {
+    let color = fit_limits.fit_type.color();
+    vec![
+        Cell::new(fit_limits.max_clearance).fg(color),
+        Cell::new(fit_limits.min_clearance).fg(color),
+        Cell::new(fit_limits.fit_type.name()).fg(color),
+    ]
+}
+
+fn print_thermal(thermal_query: &ThermalQuery, tols: FitTols) {
+    let fit_query = &thermal_query.fit_query;
+    let size: Decimal = fit_query
+        .size
+        .parse()
+        .expect("Ошибка преобразования значения из String в Decimal");
+    let alphas = (
+        thermal_query.hole.thermal_expansion,
+        thermal_query.shaft.thermal_expansion,
+    );
+    let reference_limits = calc_fit(tols.0, tols.1);
+    let operating_tols = tols_at_temperatures(
+        size,
+        tols,
+        alphas,
+        (
+            thermal_query.hole_temperature,
+            thermal_query.shaft_temperature,
+        ),
+    );
+    let operating_limits = calc_fit(operating_tols.0, operating_tols.1);
+
+    let mut header = vec![Cell::new(format!(
+        "{}{}/{}",
+        fit_query.size,
+        fit_query.hole_class(),
+        fit_query.shaft_class()
+    ))];
+    header.extend(["Smax", "Smin", "тип"].map(Cell::new));
+    let mut reference_row = vec![Cell::new(format!("при {} °C", REFERENCE_TEMPERATURE))];
+    reference_row.extend(fit_cells(&reference_limits));
+    let mut operating_row = vec![Cell::new(format!(
+        "отверстие ({}) {} °C, вал ({}) {} °C",
+        thermal_query.hole.name,
+        thermal_query.hole_temperature,
+        thermal_query.shaft.name,
+        thermal_query.shaft_temperature
+    ))];
+    operating_row.extend(fit_cells(&operating_limits));
+
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header)
+        .add_row(reference_row)
+        .add_row(operating_row);
+    println!("{table_result}");
+    if operating_limits.fit_type != reference_limits.fit_type {
+        println!(
+            "Внимание: в рабочих условиях характер посадки меняется: {}",
+            operating_limits.fit_type.name()
+        );
+    }
+
+    let (from, to, step) = thermal_query.range;
+    let mut table_range = Table::new();
+    table_range
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["t, °C", "Smax", "Smin", "тип"]);
+    let mut temperature = from;
+    while temperature <= to + step * 1e-9 {
+        let range_tols = tols_at_temperatures(size, tols, alphas, (temperature, temperature));
+        let mut row = vec![Cell::new(temperature)];
+        row.extend(fit_cells(&calc_fit(range_tols.0, range_tols.1)));
+        table_range.add_row(row);
+        temperature += step;
+    }
+    println!("{table_range}");
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::fit::FitType;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_thermal_input() {
+        let query = parse_thermal_input("thermal 50H7/g6 hole=aluminium th=-40").unwrap();
+        assert_eq!(query.hole.name, "aluminium");
+        assert_eq!(query.shaft.name, "steel");
+        assert_eq!(query.hole_temperature, -40.0);
+        assert_eq!(query.shaft_temperature, -40.0);
+        assert_eq!(query.range, (-40.0, 120.0, 20.0));
+
+        let query =
+            parse_thermal_input("thermal 50H7/g6 th=80 ts=100 from=0 to=100 step=10").unwrap();
+        assert_eq!(query.hole_temperature, 80.0);
+        assert_eq!(query.shaft_temperature, 100.0);
+        assert_eq!(query.range, (0.0, 100.0, 10.0));
+
+        assert_eq!(parse_thermal_input("thermal 50H7/g6 step=0"), None);
+        assert!(parse_thermal_input("thermal 50H7/g6 from=-40 to=120 step=1").is_some());
+        assert_eq!(
+            parse_thermal_input("thermal 50H7/g6 from=-40 to=120 step=0.0001"),
+            None
+        );
+        assert_eq!(parse_thermal_input("thermal 50H7/g6 hole=wood"), None);
+        assert_eq!(parse_thermal_input("thermal 50H7/g6 th=NaN"), None);
+        assert_eq!(parse_thermal_input("thermal 50H7/g6 ts=inf"), None);
+        assert_eq!(parse_thermal_input("thermal 50H7/g6 alphah=NaN"), None);
+        assert_eq!(parse_thermal_input("thermal 50H7/g6 th=1e300"), None);
+        assert_eq!(parse_thermal_input("thermal 50H7"), None);
+    }
+
+    #[test]
+    fn test_size_at_temperature() {
+        assert_eq!(size_at_temperature(dec!(100), 11.5e-6, 20.0), dec!(100));
+        assert_eq!(
+            size_at_temperature(dec!(100), 11.5e-6, 120.0),
+            dec!(100.115)
+        );
+        assert_eq!(size_at_temperature(dec!(50), 23.5e-6, -40.0), dec!(49.9295));
+    }
+
+    #[test]
+    fn test_clearance_becomes_interference() {
+        // 50H7/g6: алюминиевый корпус и стальной вал при -40 °C
+        let tols = ((dec!(0.025), dec!(0)), (dec!(-0.009), dec!(-0.025)));
+        let operating = tols_at_temperatures(dec!(50), tols, (23.5e-6, 11.5e-6), (-40.0, -40.0));
+        let fit_limits = calc_fit(operating.0, operating.1);
+        assert_eq!(fit_limits.max_clearance, dec!(0.013948));
+        assert_eq!(fit_limits.min_clearance, dec!(-0.027006));
+        assert_eq!(fit_limits.fit_type, FitType::Transition);
+
+        let same = tols_at_temperatures(dec!(50), tols, (11.5e-6, 11.5e-6), (20.0, 20.0));
+        assert_eq!(same, tols);
+    }
+}
@@ -0,0 +1,798 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use regex::Regex;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::dimension_chain::{closing_nominal, it_value, parse_chain_link, Calculation, ChainLink};
+use crate::params::Params;
+use crate::replace_comma_with_dot;
+use crate::stackup::parse_dimension;
+use crate::statistics::{normal_quantile, Distribution};
+
+const DEFAULT_PROCESS: &str = "turn";
+const DEFAULT_RISK: f64 = 0.27;
+/// Квалитеты, по которым перебирается пользовательская модель стоимости.
+const CUSTOM_GRADES: std::ops::RangeInclusive<u8> = 5..=14;
+const MAX_LINKS: usize = 12;
+/// Запас на погрешность вычислений при сравнении с предельными отклонениями.
+const LIMIT_EPSILON: f64 = 1e-9;
+/// Число шагов, на которые делится допуск замыкающего звена в оценке стоимости.
+const BUDGET_STEPS: usize = 1000;
+
+/// Способ обработки с достижимыми квалитетами и относительной стоимостью по ним.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Process {
+    pub key: &'static str,
+    pub name: &'static str,
+    /// (квалитет, стоимость); стоимость чернового точения по IT12 принята за 1.
+    pub costs: &'static [(u8, f64)],
+}
+
+/// Типовые кривые «точность - стоимость» для экономической точности обработки.
+pub const PROCESSES: [Process; 7] = [
+    Process {
+        key: "turn",
+        name: "точение",
+        costs: &[
+            (6, 6.5),
+            (7, 4.2),
+            (8, 2.8),
+            (9, 2.0),
+            (10, 1.5),
+            (11, 1.2),
+            (12, 1.0),
+        ],
+    },
+    Process {
+        key: "bore",
+        name: "растачивание",
+        costs: &[
+            (6, 7.5),
+            (7, 5.0),
+            (8, 3.3),
+            (9, 2.3),
+            (10, 1.7),
+            (11, 1.3),
+            (12, 1.1),
+        ],
+    },
+    Process {
+        key: "mill",
+        name: "фрезерование",
+        costs: &[
+            (7, 5.5),
+            (8, 3.6),
+            (9, 2.4),
+            (10, 1.7),
+            (11, 1.3),
+            (12, 1.1),
+            (13, 1.0),
+        ],
+    },
+    Process {
+        key: "drill",
+        name: "сверление",
+        costs: &[(10, 1.6), (11, 1.2), (12, 1.0), (13, 0.9), (14, 0.8)],
+    },
+    Process {
+        key: "ream",
+        name: "развёртывание",
+        costs: &[(6, 4.5), (7, 2.9), (8, 2.1), (9, 1.7)],
+    },
+    Process {
+        key: "grind",
+        name: "шлифование",
+        costs: &[(5, 6.0), (6, 3.8), (7, 2.6), (8, 2.0)],
+    },
+    Process {
+        key: "hone",
+        name: "хонингование",
+        costs: &[(4, 9.0), (5, 6.5), (6, 4.8)],
+    },
+];
+
+impl Process {
+    pub fn find(key: &str) -> Option<&'static Process> {
+        PROCESSES
+            .iter()
+            .find(|process| process.key.eq_ignore_ascii_case(key))
+    }
+}
+
+/// Модель стоимости звена: кривая способа обработки или заданная пользователем
+/// зависимость C = a + b / T^k, T в мкм.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CostModel {
+    Process(&'static Process),
+    Reciprocal { a: f64, b: f64, k: f64 },
+}
+
+impl CostModel {
+    /// `turn`, `grind`, ... или `2+40/T`, `1.5+300/T^2`.
+    pub fn parse(input: &str) -> Option<Self> {
+        if let Some(process) = Process::find(input) {
+            return Some(CostModel::Process(process));
+        }
+        let number = r"\d+(?:[.,]\d+)?";
+        let regex = Regex::new(&format!(
+            r"^(?P<a>{0})\+(?P<b>{0})/T(?:\^(?P<k>{0}))?$",
+            number
+        ))
+        .expect("Ошибка обработки RegEx");
+        let captures = regex.captures(input)?;
+        let number = |name: &str| -> Option<f64> {
+            match captures.name(name) {
+                Some(value) => replace_comma_with_dot(value.as_str()).parse().ok(),
+                None => Some(1.0),
+            }
+        };
+        Some(CostModel::Reciprocal {
+            a: number("a")?,
+            b: number("b")?,
+            k: number("k")?,
+        })
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            CostModel::Process(process) => process.name.to_string(),
+            CostModel::Reciprocal { a, b, k } => format!("{} + {}/T^{}", a, b, k),
+        }
+    }
+
+    pub fn grades(&self) -> Vec<u8> {
+        match self {
+            CostModel::Process(process) => process.costs.iter().map(|(grade, _)| *grade).collect(),
+            CostModel::Reciprocal { .. } => CUSTOM_GRADES.collect(),
+        }
+    }
+
+    /// Стоимость обработки по квалитету `grade` с допуском `tolerance`, мм.
+    pub fn cost(&self, grade: u8, tolerance: f64) -> Option<f64> {
+        match self {
+            CostModel::Process(process) => process
+                .costs
+                .iter()
+                .find(|(process_grade, _)| *process_grade == grade)
+                .map(|(_, cost)| *cost),
+            CostModel::Reciprocal { a, b, k } => {
+                (tolerance > 0.0).then(|| a + b / (tolerance * 1000.0).powf(*k))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct AllocLink {
+    pub link: ChainLink,
+    pub model: CostModel,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct AllocQuery {
+    pub links: Vec<AllocLink>,
+    /// Замыкающее звено: номинал, верхнее и нижнее отклонения.
+    pub closing: (Decimal, Decimal, Decimal),
+    pub calculation: Calculation,
+    pub distribution: Distribution,
+}
+
+/// Вариант звена: квалитет, отклонения и стоимость.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Candidate {
+    pub grade: u8,
+    pub upper: f64,
+    pub lower: f64,
+    pub cost: f64,
+}
+
+impl Candidate {
+    pub fn tolerance(&self) -> f64 {
+        self.upper - self.lower
+    }
+}
+
+fn parse_alloc_link(input: &str) -> Option<AllocLink> {
+    let (link, model) = input.split_once('@').unwrap_or((input, DEFAULT_PROCESS));
+    Some(AllocLink {
+        link: parse_chain_link(link)?,
+        model: CostModel::parse(model)?,
+    })
+}
+
+pub fn parse_alloc_input(input: &str) -> Option<AllocQuery> {
+    let tokens = input.strip_prefix("alloc ")?.split_whitespace();
+    let (params, links): (Vec<&str>, Vec<&str>) =
+        tokens.partition(|token| token.contains('=') && !token.contains('@'));
+    let params = Params::parse(params.into_iter(), &["closing", "calc", "risk", "dist"])?;
+    let links = links
+        .into_iter()
+        .map(parse_alloc_link)
+        .collect::<Option<Vec<AllocLink>>>()?;
+    if links.is_empty() || links.len() > MAX_LINKS {
+        return None;
+    }
+    let risk = params.number_or("risk", DEFAULT_RISK)?;
+    if !(0.0 < risk && risk < 100.0) {
+        return None;
+    }
+    let calculation = match params.text("calc") {
+        None | Some("max") => Calculation::MaxMin,
+        Some("prob") => Calculation::Probabilistic(normal_quantile(1.0 - risk / 200.0)),
+        Some(_) => return None,
+    };
+    let distribution = match params.text("dist") {
+        Some(distribution) => Distribution::parse(distribution)?,
+        None => Distribution::Normal(3.0),
+    };
+    Some(AllocQuery {
+        links,
+        closing: parse_dimension(&replace_comma_with_dot(params.text("closing")?))?,
+        calculation,
+        distribution,
+    })
+}
+
+/// Нижнее и верхнее отклонения замыкающего звена при выбранных вариантах звеньев:
+/// середина поля ± половина допуска, рассчитанного выбранным методом.
+pub fn closing_deviations(
+    chosen: &[(bool, Candidate)],
+    calculation: Calculation,
+    distribution: Distribution,
+) -> (f64, f64) {
+    let middle: f64 = chosen
+        .iter()
+        .map(|(increasing, candidate)| {
+            signed_middle(*increasing, candidate, calculation, distribution)
+        })
+        .sum();
+    let tolerances: Vec<f64> = chosen
+        .iter()
+        .map(|(_, candidate)| candidate.tolerance())
+        .collect();
+    let half = calculation.closing_tolerance(&tolerances, distribution) / 2.0;
+    (middle - half, middle + half)
+}
+
+/// Вклад допуска звена в допуск замыкающего звена: для расчёта на максимум-минимум
+/// суммируются допуски, для вероятностного - квадраты (t · 2σ).
+fn contribution(calculation: Calculation, distribution: Distribution, tolerance: f64) -> f64 {
+    match calculation {
+        Calculation::MaxMin => tolerance,
+        Calculation::Probabilistic(t) => (2.0 * t * distribution.sigma(0.0, tolerance)).powi(2),
+    }
+}
+
+/// Перебор с отсечением: наименьшая суммарная стоимость, при которой замыкающее звено
+/// укладывается в `required` (нижнее, верхнее отклонения). Возвращает индексы вариантов.
+pub fn allocate(
+    increasing: &[bool],
+    candidates: &[Vec<Candidate>],
+    required: (f64, f64),
+    calculation: Calculation,
+    distribution: Distribution,
+) -> Option<Vec<usize>> {
+    let required_tolerance = required.1 - required.0 + LIMIT_EPSILON;
+    let budget = match calculation {
+        Calculation::MaxMin => required_tolerance,
+        Calculation::Probabilistic(_) => required_tolerance.powi(2),
+    };
+    let unit = budget / BUDGET_STEPS as f64;
+    // Вклады округляются вниз, поэтому оценка не больше истинной стоимости.
+    let mut cheapest_rest = vec![vec![0.0; BUDGET_STEPS + 1]; candidates.len() + 1];
+    let mut middle_rest = vec![(0.0, 0.0); candidates.len() + 1];
+    for (link, options) in candidates.iter().enumerate().rev() {
+        for steps in 0..=BUDGET_STEPS {
+            cheapest_rest[link][steps] = options
+                .iter()
+                .filter_map(|candidate| {
+                    let used = contribution(calculation, distribution, candidate.tolerance());
+                    let used = (used / unit).floor() as usize;
+                    (used <= steps).then(|| candidate.cost + cheapest_rest[link + 1][steps - used])
+                })
+                .fold(f64::INFINITY, f64::min);
+        }
+        let (low, high) = options
+            .iter()
+            .map(|candidate| signed_middle(increasing[link], candidate, calculation, distribution))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), middle| {
+                (low.min(middle), high.max(middle))
+            });
+        middle_rest[link] = (
+            middle_rest[link + 1].0 + low,
+            middle_rest[link + 1].1 + high,
+        );
+    }
+    let narrowest: Vec<f64> = candidates
+        .iter()
+        .map(|options| {
+            options
+                .iter()
+                .map(Candidate::tolerance)
+                .fold(f64::INFINITY, f64::min)
+        })
+        .collect();
+    let mut search = Search {
+        increasing,
+        candidates,
+        required,
+        calculation,
+        distribution,
+        budget,
+        unit,
+        cheapest_rest,
+        middle_rest,
+        tolerances: narrowest.clone(),
+        narrowest,
+        used: 0.0,
+        middle: 0.0,
+        current: Vec::new(),
+        best: None,
+    };
+    search.visit(0.0);
+    search.best.map(|(_, chosen)| chosen)
+}
+
+/// Середина поля звена с учётом знака; при вероятностном расчёте - центр
+/// распределения, смещённый на асимметрию закона.
+fn signed_middle(
+    increasing: bool,
+    candidate: &Candidate,
+    calculation: Calculation,
+    distribution: Distribution,
+) -> f64 {
+    let middle = match calculation {
+        Calculation::MaxMin => (candidate.upper + candidate.lower) / 2.0,
+        Calculation::Probabilistic(_) => distribution.mean(candidate.lower, candidate.upper),
+    };
+    if increasing {
+        middle
+    } else {
+        -middle
+    }
+}
+
+struct Search<'a> {
+    increasing: &'a [bool],
+    candidates: &'a [Vec<Candidate>],
+    required: (f64, f64),
+    calculation: Calculation,
+    distribution: Distribution,
+    /// Допустимая сумма вкладов звеньев и шаг её деления.
+    budget: f64,
+    unit: f64,
+    /// Наименьшая стоимость звеньев, начиная с i-го, при сумме вкладов не больше j шагов.
+    cheapest_rest: Vec<Vec<f64>>,
+    /// Наименьшая и наибольшая сумма середин полей звеньев, начиная с i-го, с учётом знака.
+    middle_rest: Vec<(f64, f64)>,
+    /// Наименьший допуск каждого звена.
+    narrowest: Vec<f64>,
+    /// Допуски выбранных звеньев, для остальных - наименьшие.
+    tolerances: Vec<f64>,
+    /// Сумма вкладов и сумма середин полей выбранных звеньев.
+    used: f64,
+    middle: f64,
+    current: Vec<usize>,
+    best: Option<(f64, Vec<usize>)>,
+}
+
+impl Search<'_> {
+    fn visit(&mut self, cost: f64) {
+        let depth = self.current.len();
+        let rest = self.budget - self.used;
+        if rest < 0.0 {
+            return;
+        }
+        let steps = ((rest / self.unit).floor() as usize).min(BUDGET_STEPS);
+        let bound = cost + self.cheapest_rest[depth][steps];
+        if bound.is_infinite() || self.best.as_ref().is_some_and(|(best, _)| bound >= *best) {
+            return;
+        }
+        // Допуск замыкающего звена растёт с допусками звеньев, поэтому при наименьших
+        // допусках оставшихся звеньев он наименьший из достижимых.
+        let half = self
+            .calculation
+            .closing_tolerance(&self.tolerances, self.distribution)
+            / 2.0;
+        let (low, high) = self.middle_rest[depth];
+        if self.middle + high < self.required.0 + half - LIMIT_EPSILON
+            || self.middle + low > self.required.1 - half + LIMIT_EPSILON
+        {
+            return;
+        }
+        if depth == self.candidates.len() {
+            self.best = Some((cost, self.current.clone()));
+            return;
+        }
+        let mut order: Vec<usize> = (0..self.candidates[depth].len()).collect();
+        order.sort_by(|a, b| {
+            self.candidates[depth][*a]
+                .cost
+                .total_cmp(&self.candidates[depth][*b].cost)
+        });
+        for option in order {
+            let candidate = self.candidates[depth][option];
+            let middle = signed_middle(
+                self.increasing[depth],
+                &candidate,
+                self.calculation,
+                self.distribution,
+            );
+            let used = contribution(self.calculation, self.distribution, candidate.tolerance());
+            self.middle += middle;
+            self.used += used;
+            self.tolerances[depth] = candidate.tolerance();
+            self.current.push(option);
+            self.visit(cost + candidate.cost);
+            self.current.pop();
+            self.tolerances[depth] = self.narrowest[depth];
+            self.used -= used;
+            self.middle -= middle;
+        }
+    }
+}
+
+/// Варианты звена по квалитетам модели стоимости. Ok(None) - допуск не найден ни для
+/// одного квалитета.
+fn link_candidates(link: &AllocLink) -> Result<Option<Vec<Candidate>>, String> {
+    let mut candidates = Vec::new();
+    for grade in link.model.grades() {
+        let tolerance = match it_value(link.link.nominal, grade)? {
+            Some(tolerance) => tolerance,
+            None => continue,
+        };
+        let (upper, lower) = link.link.position.deviations(tolerance);
+        let to_f64 = |value: Decimal| value.to_f64().unwrap_or_default();
+        if let Some(cost) = link.model.cost(grade, to_f64(tolerance)) {
+            candidates.push(Candidate {
+                grade,
+                upper: to_f64(upper),
+                lower: to_f64(lower),
+                cost,
+            });
+        }
+    }
+    Ok((!candidates.is_empty()).then_some(candidates))
+}
+
+pub fn handle_alloc(alloc_query: &AllocQuery) {
+    let chain_links: Vec<ChainLink> = alloc_query.links.iter().map(|link| link.link).collect();
+    let (nominal, upper, lower) = alloc_query.closing;
+    if closing_nominal(&chain_links) != nominal {
+        println!(
+            "Номинал замыкающего звена по составляющим {}, а задан {}",
+            closing_nominal(&chain_links).normalize(),
+            nominal
+        );
+        return;
+    }
+    let mut candidates = Vec::new();
+    for link in &alloc_query.links {
+        match link_candidates(link) {
+            Ok(Some(options)) => candidates.push(options),
+            Ok(None) => {
+                println!("Ничего не найдено для заданных параметров");
+                return;
+            }
+            Err(e) => {
+                println!("Ошибка при поиске в БД: {}", e);
+                return;
+            }
+        }
+    }
+    let increasing: Vec<bool> = chain_links.iter().map(|link| link.increasing).collect();
+    let required = (
+        lower.to_f64().unwrap_or_default(),
+        upper.to_f64().unwrap_or_default(),
+    );
+    match allocate(
+        &increasing,
+        &candidates,
+        required,
+        alloc_query.calculation,
+        alloc_query.distribution,
+    ) {
+        Some(chosen) => {
+            let chosen: Vec<Candidate> = chosen
+                .iter()
+                .zip(&candidates)
+                .map(|(option, options)| options[*option])
+                .collect();
+            print_alloc(alloc_query, &chosen, required)
+        }
+        None => println!(
+            "Требование к замыкающему звену не выполняется ни при одном сочетании \
+             квалитетов выбранных способов обработки и расположений полей допусков"
+        ),
+    }
+}
+
+fn print_alloc(alloc_query: &AllocQuery, chosen: &[Candidate], required: (f64, f64)) {
+    let mut table_links = Table::new();
+    table_links
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("звено"),
+            Cell::new("ξ"),
+            Cell::new("обработка"),
+            Cell::new("класс").fg(Color::Green),
+            Cell::new("ES").fg(Color::Red),
+            Cell::new("EI").fg(Color::Cyan),
+            Cell::new("T").fg(Color::Blue),
+            Cell::new("стоимость"),
+        ]);
+    for (index, (link, candidate)) in alloc_query.links.iter().zip(chosen).enumerate() {
+        table_links.add_row(vec![
+            Cell::new(format!("A{}", index + 1)),
+            Cell::new(if link.link.increasing { "+1" } else { "-1" }),
+            Cell::new(link.model.name()),
+            Cell::new(format!(
+                "{}{}{}",
+                link.link.nominal.normalize(),
+                link.link.position.field(),
+                candidate.grade
+            ))
+            .fg(Color::Green),
+            Cell::new(format!("{:+.4}", candidate.upper)).fg(Color::Red),
+            Cell::new(format!("{:+.4}", candidate.lower)).fg(Color::Cyan),
+            Cell::new(format!("{:.4}", candidate.tolerance())).fg(Color::Blue),
+            Cell::new(format!("{:.2}", candidate.cost)),
+        ]);
+    }
+    println!("{table_links}");
+
+    let increasing = alloc_query.links.iter().map(|link| link.link.increasing);
+    let pairs: Vec<(bool, Candidate)> = increasing.zip(chosen.iter().copied()).collect();
+    let (lower, upper) =
+        closing_deviations(&pairs, alloc_query.calculation, alloc_query.distribution);
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new(format!("AΔ = {}", alloc_query.closing.0)),
+            Cell::new("ESΔ").fg(Color::Red),
+            Cell::new("EIΔ").fg(Color::Cyan),
+            Cell::new("TΔ").fg(Color::Blue),
+        ])
+        .add_row(vec![
+            Cell::new("требуется"),
+            Cell::new(format!("{:+.4}", required.1)).fg(Color::Red),
+            Cell::new(format!("{:+.4}", required.0)).fg(Color::Cyan),
+            Cell::new(format!("{:.4}", required.1 - required.0)).fg(Color::Blue),
+        ])
+        .add_row(vec![
+            Cell::new(match alloc_query.calculation {
+                Calculation::MaxMin => "получено, максимум-минимум".to_string(),
+                Calculation::Probabilistic(t) => format!("получено, вероятностно, t = {:.2}", t),
+            }),
+            Cell::new(format!("{:+.4}", upper)).fg(Color::Red),
+            Cell::new(format!("{:+.4}", lower)).fg(Color::Cyan),
+            Cell::new(format!("{:.4}", upper - lower)).fg(Color::Blue),
+        ]);
+    println!("{table_result}");
+    println!(
+        "Суммарная относительная стоимость: {:.2}",
+        chosen.iter().map(|candidate| candidate.cost).sum::<f64>()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symmetric(grade: u8, tolerance: f64, cost: f64) -> Candidate {
+        Candidate {
+            grade,
+            upper: tolerance / 2.0,
+            lower: -tolerance / 2.0,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_cost_model() {
+        let turning = CostModel::parse("turn").unwrap();
+        assert_eq!(turning.grades(), vec![6, 7, 8, 9, 10, 11, 12]);
+        assert_eq!(turning.cost(8, 0.039), Some(2.8));
+        assert_eq!(turning.cost(5, 0.011), None);
+        assert_eq!(
+            CostModel::parse("2+40/T").unwrap(),
+            CostModel::Reciprocal {
+                a: 2.0,
+                b: 40.0,
+                k: 1.0
+            }
+        );
+        let custom = CostModel::parse("1,5+300/T^2").unwrap();
+        assert_eq!(custom.cost(7, 0.01), Some(4.5));
+        assert_eq!(custom.grades().len(), 10);
+        assert_eq!(CostModel::parse("lathe"), None);
+    }
+
+    #[test]
+    fn test_parse_alloc_input() {
+        let alloc_query =
+            parse_alloc_input("alloc +100H@bore -40h@grind -58@2+40/T closing=2+0.6-0.2 calc=prob")
+                .unwrap();
+        assert_eq!(alloc_query.links.len(), 3);
+        assert_eq!(
+            alloc_query.links[0].model,
+            CostModel::Process(&PROCESSES[1])
+        );
+        assert_eq!(
+            alloc_query.links[1].model,
+            CostModel::Process(&PROCESSES[5])
+        );
+        assert!(matches!(
+            alloc_query.calculation,
+            Calculation::Probabilistic(_)
+        ));
+        assert_eq!(
+            parse_alloc_input("alloc +100H -98 closing=2±0.1")
+                .unwrap()
+                .links[1]
+                .model,
+            CostModel::Process(&PROCESSES[0])
+        );
+        assert_eq!(parse_alloc_input("alloc +100H -98"), None);
+        assert_eq!(
+            parse_alloc_input("alloc +100H@lathe -98 closing=2±0.1"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_allocate() {
+        let candidates = vec![
+            vec![
+                symmetric(7, 0.025, 4.0),
+                symmetric(8, 0.039, 2.5),
+                symmetric(9, 0.062, 1.7),
+            ],
+            vec![
+                symmetric(7, 0.035, 4.0),
+                symmetric(8, 0.054, 2.5),
+                symmetric(9, 0.087, 1.8),
+            ],
+        ];
+        let increasing = [true, false];
+        let normal = Distribution::Normal(3.0);
+        // Максимум-минимум: 0.039 + 0.054 = 0.093 <= 0.1 дешевле, чем 0.062 + 0.035.
+        assert_eq!(
+            allocate(
+                &increasing,
+                &candidates,
+                (-0.05, 0.05),
+                Calculation::MaxMin,
+                normal
+            ),
+            Some(vec![1, 1])
+        );
+        // Вероятностно: √(0.062² + 0.054²) = 0.082 <= 0.1.
+        assert_eq!(
+            allocate(
+                &increasing,
+                &candidates,
+                (-0.05, 0.05),
+                Calculation::Probabilistic(3.0),
+                normal
+            ),
+            Some(vec![2, 1])
+        );
+        assert_eq!(
+            allocate(
+                &increasing,
+                &candidates,
+                (-0.02, 0.02),
+                Calculation::MaxMin,
+                normal
+            ),
+            None
+        );
+        // Допуск достаточен, но поле смещено относительно требуемого.
+        assert_eq!(
+            allocate(
+                &increasing,
+                &candidates,
+                (0.0, 0.2),
+                Calculation::MaxMin,
+                normal
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_allocate_offset_at_max_links() {
+        // 12 звеньев +10H по токарной обработке, IT6 … IT12.
+        let options: Vec<Candidate> = [
+            (6, 0.009, 6.5),
+            (7, 0.015, 4.2),
+            (8, 0.022, 2.8),
+            (9, 0.036, 2.0),
+            (10, 0.058, 1.5),
+            (11, 0.09, 1.2),
+            (12, 0.15, 1.0),
+        ]
+        .iter()
+        .map(|&(grade, tolerance, cost)| Candidate {
+            grade,
+            upper: tolerance,
+            lower: 0.0,
+            cost,
+        })
+        .collect();
+        let candidates = vec![options; MAX_LINKS];
+        let increasing = [true; MAX_LINKS];
+        let normal = Distribution::Normal(3.0);
+        // closing=120+9+4: середина поля не выше 12 · 0.15 / 2, смещение недостижимо.
+        assert_eq!(
+            allocate(
+                &increasing,
+                &candidates,
+                (4.0, 9.0),
+                Calculation::MaxMin,
+                normal
+            ),
+            None
+        );
+        let chosen = allocate(
+            &increasing,
+            &candidates,
+            (0.0, 0.5),
+            Calculation::MaxMin,
+            normal,
+        )
+        .unwrap();
+        let total: f64 = chosen
+            .iter()
+            .map(|option| candidates[0][*option].tolerance())
+            .sum();
+        assert!(total <= 0.5 + LIMIT_EPSILON);
+    }
+
+    #[test]
+    fn test_closing_deviations() {
+        let hole = Candidate {
+            grade: 8,
+            upper: 0.054,
+            lower: 0.0,
+            cost: 1.0,
+        };
+        let shaft = Candidate {
+            grade: 8,
+            upper: 0.0,
+            lower: -0.039,
+            cost: 1.0,
+        };
+        let chosen = [(true, hole), (false, shaft)];
+        let (lower, upper) =
+            closing_deviations(&chosen, Calculation::MaxMin, Distribution::Normal(3.0));
+        assert!((lower - 0.0).abs() < 1e-12 && (upper - 0.093).abs() < 1e-12);
+        let (lower, upper) = closing_deviations(
+            &chosen,
+            Calculation::Probabilistic(3.0),
+            Distribution::Normal(3.0),
+        );
+        let half = (0.054f64.powi(2) + 0.039f64.powi(2)).sqrt() / 2.0;
+        assert!((lower - (0.0465 - half)).abs() < 1e-12);
+        assert!((upper - (0.0465 + half)).abs() < 1e-12);
+        // Центры распределений смещены к верхним отклонениям на четверть допуска.
+        let shifted = Distribution::Shifted {
+            asymmetry: 0.5,
+            spread: 1.0 / 6.0,
+        };
+        let (lower, upper) = closing_deviations(&chosen, Calculation::Probabilistic(3.0), shifted);
+        let middle = 0.0465 + 0.054 / 4.0 - 0.039 / 4.0;
+        assert!((lower - (middle - half)).abs() < 1e-12);
+        assert!((upper - (middle + half)).abs() < 1e-12);
+        // Максимум-минимум от закона не зависит.
+        assert_eq!(
+            closing_deviations(&chosen, Calculation::MaxMin, shifted),
+            closing_deviations(&chosen, Calculation::MaxMin, Distribution::Normal(3.0))
+        );
+    }
+}
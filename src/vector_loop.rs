@@ -0,0 +1,714 @@
+use core::f64::consts::PI;
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use regex::Regex;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::fit::{parse_fit_input, search_fit, FitQuery};
+use crate::params::Params;
+use crate::replace_comma_with_dot;
+use crate::stackup::{
+    parse_source, summarize, MonteCarlo, Source, DEFAULT_SEED, DEFAULT_TRIALS, MAX_TRIALS,
+    TAIL_FRACTION,
+};
+use crate::statistics::{Distribution, Rng};
+
+/// Шаг перебора направлений при поиске наибольшего смещения точки, градусы.
+const DIRECTION_STEP: usize = 1;
+const MAX_ELEMENTS: usize = 20;
+
+/// Элемент векторного контура. Все углы отсчитываются от оси X против часовой стрелки.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Element {
+    /// Вектор `50±0.1@30±0.5`: длина и угол в градусах с допуском ±.
+    Vector {
+        length: Source,
+        angle: f64,
+        angle_tolerance: f64,
+    },
+    /// Плавание пальца в отверстии `float:10H7/g6`: центр пальца смещается в любом
+    /// направлении на величину до половины зазора.
+    Float(FitQuery),
+    /// Позиционный допуск `pos:0.1`: точка смещается в круге диаметром 0.1.
+    Position(f64),
+}
+
+impl Element {
+    pub fn name(&self) -> String {
+        match self {
+            Element::Vector {
+                length,
+                angle,
+                angle_tolerance,
+            } => {
+                if *angle_tolerance > 0.0 {
+                    format!("{} ∠{}°±{}°", length.name(), angle, angle_tolerance)
+                } else {
+                    format!("{} ∠{}°", length.name(), angle)
+                }
+            }
+            Element::Float(fit_query) => format!(
+                "плавание {}{}/{}",
+                fit_query.size,
+                fit_query.hole_class(),
+                fit_query.shaft_class()
+            ),
+            Element::Position(diameter) => format!("позиция ⌀{}", diameter),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LoopQuery {
+    pub elements: Vec<Element>,
+    /// Направление, по которому измеряется зазор, градусы.
+    pub direction: Option<f64>,
+    pub distribution: Distribution,
+    pub trials: usize,
+    pub seed: u64,
+    /// Требуемые пределы зазора.
+    pub required: (Option<f64>, Option<f64>),
+}
+
+/// Элемент с найденными предельными размерами.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Resolved {
+    Vector {
+        nominal: f64,
+        /// Наименьшая и наибольшая длина.
+        length: (f64, f64),
+        angle: f64,
+        angle_tolerance: f64,
+    },
+    /// Наибольшее радиальное смещение: отклонения отверстия (EI, ES) и вала (ei, es).
+    Float {
+        hole: (f64, f64),
+        shaft: (f64, f64),
+    },
+    Position {
+        radius: f64,
+    },
+}
+
+/// Линеаризованный вклад параметра: производные координат точки по параметру
+/// или смещение в любом направлении в пределах радиуса.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Term {
+    Linear {
+        gradient: (f64, f64),
+        half: f64,
+        sigma: f64,
+    },
+    Radial {
+        radius: f64,
+        sigma: f64,
+    },
+}
+
+impl Term {
+    /// Половина поля рассеяния и σ проекции на направление `unit`.
+    pub fn along(&self, unit: (f64, f64)) -> (f64, f64) {
+        match self {
+            Term::Linear {
+                gradient,
+                half,
+                sigma,
+            } => {
+                let derivative = (gradient.0 * unit.0 + gradient.1 * unit.1).abs();
+                (derivative * half, derivative * sigma)
+            }
+            Term::Radial { radius, sigma } => (*radius, *sigma),
+        }
+    }
+}
+
+/// Результат линеаризованного расчёта по одному направлению.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Projection {
+    pub nominal: f64,
+    pub mean: f64,
+    pub worst_case: (f64, f64),
+    pub sigma: f64,
+}
+
+fn unit(angle: f64) -> (f64, f64) {
+    let radians = angle.to_radians();
+    (radians.cos(), radians.sin())
+}
+
+fn dot(a: (f64, f64), b: (f64, f64)) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+/// Случайная точка, равномерно распределённая в круге радиуса `radius`.
+fn sample_disk(rng: &mut Rng, radius: f64) -> (f64, f64) {
+    let distance = radius * rng.next_f64().sqrt();
+    let angle = 2.0 * PI * rng.next_f64();
+    (distance * angle.cos(), distance * angle.sin())
+}
+
+impl Resolved {
+    /// Наибольшее радиальное смещение.
+    pub fn radius(&self) -> f64 {
+        match self {
+            Resolved::Vector { .. } => 0.0,
+            Resolved::Float { hole, shaft } => ((hole.1 - shaft.0) / 2.0).max(0.0),
+            Resolved::Position { radius } => *radius,
+        }
+    }
+
+    pub fn nominal(&self) -> (f64, f64) {
+        match self {
+            Resolved::Vector { nominal, angle, .. } => {
+                let (x, y) = unit(*angle);
+                (nominal * x, nominal * y)
+            }
+            _ => (0.0, 0.0),
+        }
+    }
+
+    pub fn middle(&self) -> (f64, f64) {
+        match self {
+            Resolved::Vector { length, angle, .. } => {
+                let (x, y) = unit(*angle);
+                let middle = (length.0 + length.1) / 2.0;
+                (middle * x, middle * y)
+            }
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// Центр распределения конечной точки элемента; при смещённом законе длина и угол
+    /// смещены от середин полей.
+    pub fn mean(&self, distribution: Distribution) -> (f64, f64) {
+        match self {
+            Resolved::Vector {
+                length,
+                angle,
+                angle_tolerance,
+                ..
+            } => {
+                let (x, y) =
+                    unit(angle + distribution.mean_shift(-angle_tolerance, *angle_tolerance));
+                let mean = distribution.mean(length.0, length.1);
+                (mean * x, mean * y)
+            }
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// Якобиан элемента: длина и угол вектора; смещения в круге при равномерном
+    /// распределении по площади дают σ = R/2 по любой оси.
+    pub fn terms(&self, distribution: Distribution) -> Vec<(String, Term)> {
+        match self {
+            Resolved::Vector {
+                length,
+                angle,
+                angle_tolerance,
+                ..
+            } => {
+                let (x, y) = unit(*angle);
+                let middle = (length.0 + length.1) / 2.0;
+                let mut terms = vec![(
+                    "длина".to_string(),
+                    Term::Linear {
+                        gradient: (x, y),
+                        half: (length.1 - length.0) / 2.0,
+                        sigma: distribution.sigma(length.0, length.1),
+                    },
+                )];
+                if *angle_tolerance > 0.0 {
+                    let per_degree = PI / 180.0 * middle;
+                    terms.push((
+                        "угол, °".to_string(),
+                        Term::Linear {
+                            gradient: (-y * per_degree, x * per_degree),
+                            half: *angle_tolerance,
+                            sigma: distribution.sigma(-angle_tolerance, *angle_tolerance),
+                        },
+                    ));
+                }
+                terms
+            }
+            _ => vec![(
+                "смещение".to_string(),
+                Term::Radial {
+                    radius: self.radius(),
+                    sigma: self.radius() / 2.0,
+                },
+            )],
+        }
+    }
+
+    pub fn sample(&self, rng: &mut Rng, distribution: Distribution) -> (f64, f64) {
+        match self {
+            Resolved::Vector {
+                length,
+                angle,
+                angle_tolerance,
+                ..
+            } => {
+                let length = distribution.sample(rng, length.0, length.1);
+                let angle = if *angle_tolerance > 0.0 {
+                    distribution.sample(rng, angle - angle_tolerance, angle + angle_tolerance)
+                } else {
+                    *angle
+                };
+                let (x, y) = unit(angle);
+                (length * x, length * y)
+            }
+            Resolved::Float { hole, shaft } => {
+                let hole = distribution.sample(rng, hole.0, hole.1);
+                let shaft = distribution.sample(rng, shaft.0, shaft.1);
+                sample_disk(rng, ((hole - shaft) / 2.0).max(0.0))
+            }
+            Resolved::Position { radius } => sample_disk(rng, *radius),
+        }
+    }
+}
+
+fn parse_element(input: &str) -> Option<Element> {
+    if let Some(fit) = input.strip_prefix("float:") {
+        return Some(Element::Float(parse_fit_input(fit)?));
+    }
+    if let Some(diameter) = input.strip_prefix("pos:") {
+        let diameter: f64 = replace_comma_with_dot(diameter).parse().ok()?;
+        return (diameter > 0.0).then_some(Element::Position(diameter));
+    }
+    let (length, angle) = input.split_once('@')?;
+    let regex = Regex::new(r"^(?P<angle>-?\d+(?:[.,]\d+)?)(?:±(?P<tolerance>\d+(?:[.,]\d+)?))?$")
+        .expect("Ошибка обработки RegEx");
+    let captures = regex.captures(angle)?;
+    Some(Element::Vector {
+        length: parse_source(length)?,
+        angle: replace_comma_with_dot(&captures["angle"]).parse().ok()?,
+        angle_tolerance: match captures.name("tolerance") {
+            Some(tolerance) => replace_comma_with_dot(tolerance.as_str()).parse().ok()?,
+            None => 0.0,
+        },
+    })
+}
+
+pub fn parse_loop_input(input: &str) -> Option<LoopQuery> {
+    let tokens = input.strip_prefix("loop ")?.split_whitespace();
+    let (params, elements): (Vec<&str>, Vec<&str>) = tokens.partition(|token| token.contains('='));
+    let params = Params::parse(
+        params.into_iter(),
+        &["dir", "dist", "n", "seed", "min", "max"],
+    )?;
+    let elements = elements
+        .into_iter()
+        .map(parse_element)
+        .collect::<Option<Vec<Element>>>()?;
+    if elements.is_empty() || elements.len() > MAX_ELEMENTS {
+        return None;
+    }
+    let direction = params.number("dir")?;
+    let required = (params.number("min")?, params.number("max")?);
+    if direction.is_none() && (required.0.is_some() || required.1.is_some()) {
+        return None;
+    }
+    let distribution = match params.text("dist") {
+        Some(distribution) => Distribution::parse(distribution)?,
+        None => Distribution::Normal(3.0),
+    };
+    let trials = params.number_or("n", DEFAULT_TRIALS)?;
+    let seed = params.number_or("seed", DEFAULT_SEED)?;
+    if trials.fract() != 0.0 || !(1000.0..=MAX_TRIALS).contains(&trials) || seed < 0.0 {
+        return None;
+    }
+    Some(LoopQuery {
+        elements,
+        direction,
+        distribution,
+        trials: trials as usize,
+        seed: seed as u64,
+        required,
+    })
+}
+
+/// Предельные размеры элементов. Ok(None) - поле допуска или посадка не найдены.
+pub fn resolve_elements(elements: &[Element]) -> Result<Option<Vec<Resolved>>, String> {
+    let to_f64 = |value: Decimal| value.to_f64().unwrap_or_default();
+    let mut resolved = Vec::new();
+    for element in elements {
+        resolved.push(match element {
+            Element::Vector {
+                length,
+                angle,
+                angle_tolerance,
+            } => match length.limits()? {
+                Some((upper, lower)) => Resolved::Vector {
+                    nominal: to_f64(length.nominal()),
+                    length: (to_f64(lower), to_f64(upper)),
+                    angle: *angle,
+                    angle_tolerance: *angle_tolerance,
+                },
+                None => return Ok(None),
+            },
+            Element::Float(fit_query) => match search_fit(fit_query)? {
+                Some(((hole_upper, hole_lower), (shaft_upper, shaft_lower))) => Resolved::Float {
+                    hole: (to_f64(hole_lower), to_f64(hole_upper)),
+                    shaft: (to_f64(shaft_lower), to_f64(shaft_upper)),
+                },
+                None => return Ok(None),
+            },
+            Element::Position(diameter) => Resolved::Position {
+                radius: diameter / 2.0,
+            },
+        });
+    }
+    Ok(Some(resolved))
+}
+
+/// Линеаризованный расчёт проекции конечной точки контура на направление `angle`:
+/// максимум-минимум по модулям производных и σ по сумме квадратов.
+pub fn project(resolved: &[Resolved], distribution: Distribution, angle: f64) -> Projection {
+    let direction = unit(angle);
+    let nominal = resolved
+        .iter()
+        .map(|element| dot(element.nominal(), direction))
+        .sum();
+    let middle: f64 = resolved
+        .iter()
+        .map(|element| dot(element.middle(), direction))
+        .sum();
+    let mean = resolved
+        .iter()
+        .map(|element| dot(element.mean(distribution), direction))
+        .sum();
+    let (half, variance) = resolved
+        .iter()
+        .flat_map(|element| element.terms(distribution))
+        .map(|(_, term)| term.along(direction))
+        .fold((0.0, 0.0), |(half, variance), (term_half, term_sigma)| {
+            (half + term_half, variance + term_sigma.powi(2))
+        });
+    Projection {
+        nominal,
+        mean,
+        worst_case: (middle - half, middle + half),
+        sigma: f64::sqrt(variance),
+    }
+}
+
+/// Наибольшее по всем направлениям смещение конечной точки от середины поля
+/// (линеаризованный максимум-минимум).
+pub fn worst_case_radius(resolved: &[Resolved], distribution: Distribution) -> f64 {
+    (0..180)
+        .step_by(DIRECTION_STEP)
+        .map(|angle| {
+            let projection = project(resolved, distribution, angle as f64);
+            (projection.worst_case.1 - projection.worst_case.0) / 2.0
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Радиус круга с центром в середине поля конечной точки, в который попадают 99.73%
+/// смоделированных точек; сравним с `worst_case_radius`.
+pub fn simulated_radius(resolved: &[Resolved], points: &[(f64, f64)]) -> f64 {
+    let middle = resolved
+        .iter()
+        .map(Resolved::middle)
+        .fold((0.0, 0.0), |point, (x, y)| (point.0 + x, point.1 + y));
+    let mut radii: Vec<f64> = points
+        .iter()
+        .map(|point| (point.0 - middle.0).hypot(point.1 - middle.1))
+        .collect();
+    radii.sort_by(|a, b| a.total_cmp(b));
+    radii[((radii.len() - 1) as f64 * (1.0 - 2.0 * TAIL_FRACTION)).round() as usize]
+}
+
+/// Моделирование конечной точки контура методом Монте-Карло.
+pub fn simulate_points(
+    resolved: &[Resolved],
+    distribution: Distribution,
+    trials: usize,
+    seed: u64,
+) -> Vec<(f64, f64)> {
+    let mut rng = Rng::new(seed);
+    (0..trials)
+        .map(|_| {
+            resolved
+                .iter()
+                .map(|element| element.sample(&mut rng, distribution))
+                .fold((0.0, 0.0), |point, (x, y)| (point.0 + x, point.1 + y))
+        })
+        .collect()
+}
+
+pub fn handle_loop(loop_query: &LoopQuery) {
+    match resolve_elements(&loop_query.elements) {
+        Ok(Some(resolved)) => print_loop(loop_query, &resolved),
+        Ok(None) => println!("Ничего не найдено для заданных параметров"),
+        Err(e) => println!("Ошибка при поиске в БД: {}", e),
+    }
+}
+
+fn print_loop(loop_query: &LoopQuery, resolved: &[Resolved]) {
+    let distribution = loop_query.distribution;
+    let mut directions = vec![("X".to_string(), 0.0), ("Y".to_string(), 90.0)];
+    if let Some(direction) = loop_query.direction {
+        directions.push((format!("зазор ∠{}°", direction), direction));
+    }
+
+    let mut table_terms = Table::new();
+    let mut header = vec![
+        Cell::new("элемент"),
+        Cell::new("параметр"),
+        Cell::new("±").fg(Color::Blue),
+    ];
+    header.extend(
+        directions
+            .iter()
+            .map(|(name, _)| Cell::new(format!("∂{}", name))),
+    );
+    table_terms
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header);
+    for (element, resolved) in loop_query.elements.iter().zip(resolved) {
+        for (name, term) in resolved.terms(distribution) {
+            let mut row = vec![Cell::new(element.name()), Cell::new(name)];
+            match term {
+                Term::Linear { gradient, half, .. } => {
+                    row.push(Cell::new(format!("{:.4}", half)).fg(Color::Blue));
+                    row.extend(directions.iter().map(|(_, angle)| {
+                        Cell::new(format!("{:+.4}", dot(gradient, unit(*angle))))
+                    }));
+                }
+                Term::Radial { radius, .. } => {
+                    row.push(Cell::new(format!("R {:.4}", radius)).fg(Color::Blue));
+                    row.extend(directions.iter().map(|_| Cell::new("любое направление")));
+                }
+            }
+            table_terms.add_row(row);
+        }
+    }
+    println!("{table_terms}");
+
+    let points = simulate_points(resolved, distribution, loop_query.trials, loop_query.seed);
+    let mut table_result = Table::new();
+    table_result
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new(""),
+            Cell::new("номинал"),
+            Cell::new("максимум-минимум"),
+            Cell::new("линейный ±3σ"),
+            Cell::new("Монте-Карло 0.135% .. 99.865%"),
+            Cell::new("σ лин. / М-К"),
+        ]);
+    let mut gap_simulation: Option<MonteCarlo> = None;
+    for (index, (name, angle)) in directions.iter().enumerate() {
+        // Третье направление, если задано, - зазор с требованиями min= и max=.
+        let is_gap = index == 2;
+        let projection = project(resolved, distribution, *angle);
+        let direction = unit(*angle);
+        let required = if is_gap {
+            loop_query.required
+        } else {
+            (None, None)
+        };
+        let simulation = summarize(
+            points.iter().map(|point| dot(*point, direction)).collect(),
+            required,
+        );
+        table_result.add_row(vec![
+            Cell::new(name),
+            Cell::new(format!("{:.4}", projection.nominal)),
+            Cell::new(format!(
+                "{:.4} .. {:.4}",
+                projection.worst_case.0, projection.worst_case.1
+            )),
+            Cell::new(format!(
+                "{:.4} .. {:.4}",
+                projection.mean - 3.0 * projection.sigma,
+                projection.mean + 3.0 * projection.sigma
+            )),
+            Cell::new(format!(
+                "{:.4} .. {:.4}",
+                simulation.spread.0, simulation.spread.1
+            )),
+            Cell::new(format!("{:.5} / {:.5}", projection.sigma, simulation.sigma)),
+        ]);
+        if is_gap {
+            gap_simulation = Some(simulation);
+        }
+    }
+    println!("{table_result}");
+
+    let sum = |point: fn(&Resolved) -> (f64, f64)| {
+        resolved
+            .iter()
+            .map(point)
+            .fold((0.0, 0.0), |sum, (x, y)| (sum.0 + x, sum.1 + y))
+    };
+    let (nominal, middle) = (sum(Resolved::nominal), sum(Resolved::middle));
+    println!(
+        "Конечная точка: номинал ({:.4}; {:.4}), середина поля ({:.4}; {:.4}); \
+         смещение от середины поля ⌀{:.4} максимум-минимум, ⌀{:.4} Монте-Карло \
+         (99.73% точек, {} испытаний)",
+        nominal.0,
+        nominal.1,
+        middle.0,
+        middle.1,
+        2.0 * worst_case_radius(resolved, distribution),
+        2.0 * simulated_radius(resolved, &points),
+        loop_query.trials
+    );
+
+    if let Some(simulation) = gap_simulation {
+        let (min, max) = loop_query.required;
+        if min.is_some() || max.is_some() {
+            println!(
+                "Зазор вне требований {} .. {}: ниже {:.0} ppm, выше {:.0} ppm",
+                min.map_or("-".to_string(), |min| min.to_string()),
+                max.map_or("-".to_string(), |max| max.to_string()),
+                simulation.below * 1e6,
+                simulation.above * 1e6
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, epsilon: f64) {
+        assert!(
+            (actual - expected).abs() < epsilon,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_loop_input() {
+        let loop_query =
+            parse_loop_input("loop 50±0.1@30±0.5 40h9@-90 float:10H7/g6 pos:0,1 dir=90 min=0")
+                .unwrap();
+        assert_eq!(loop_query.elements.len(), 4);
+        assert_eq!(
+            loop_query.elements[0],
+            Element::Vector {
+                length: Source::Deviations {
+                    nominal: dec!(50),
+                    upper: dec!(0.1),
+                    lower: dec!(-0.1),
+                },
+                angle: 30.0,
+                angle_tolerance: 0.5,
+            }
+        );
+        assert!(matches!(
+            loop_query.elements[1],
+            Element::Vector { angle, angle_tolerance, .. } if angle == -90.0 && angle_tolerance == 0.0
+        ));
+        assert!(matches!(loop_query.elements[2], Element::Float(_)));
+        assert_eq!(loop_query.elements[3], Element::Position(0.1));
+        assert_eq!(loop_query.direction, Some(90.0));
+        assert_eq!(loop_query.required, (Some(0.0), None));
+        assert_eq!(parse_loop_input("loop 50±0.1@30 min=0"), None);
+        assert_eq!(parse_loop_input("loop 50±0.1"), None);
+        assert_eq!(parse_loop_input("loop pos:0"), None);
+    }
+
+    #[test]
+    fn test_project() {
+        let uniform = Distribution::Uniform;
+        let resolved = [
+            Resolved::Vector {
+                nominal: 100.0,
+                length: (99.9, 100.1),
+                angle: 0.0,
+                angle_tolerance: 0.0,
+            },
+            Resolved::Vector {
+                nominal: 50.0,
+                length: (50.0, 50.0),
+                angle: 90.0,
+                angle_tolerance: 1.0,
+            },
+        ];
+        let x = project(&resolved, uniform, 0.0);
+        assert_close(x.nominal, 100.0, 1e-9);
+        // Поворот вектора длиной 50 на ±1° смещает его конец по X на ±0.8727.
+        assert_close(x.worst_case.1 - x.mean, 0.1 + 50.0 * PI / 180.0, 1e-9);
+        let y = project(&resolved, uniform, 90.0);
+        assert_close(y.nominal, 50.0, 1e-9);
+        assert_close(y.worst_case.1 - y.worst_case.0, 0.0, 1e-9);
+
+        let resolved = [Resolved::Float {
+            hole: (0.0, 0.018),
+            shaft: (-0.017, -0.006),
+        }];
+        assert_close(resolved[0].radius(), 0.0175, 1e-12);
+        let projection = project(&resolved, uniform, 45.0);
+        assert_close(projection.worst_case.1, 0.0175, 1e-12);
+        assert_close(projection.sigma, 0.00875, 1e-12);
+        assert_close(worst_case_radius(&resolved, uniform), 0.0175, 1e-12);
+    }
+
+    #[test]
+    fn test_simulate_points() {
+        let normal = Distribution::Normal(3.0);
+        let resolved = [
+            Resolved::Vector {
+                nominal: 30.0,
+                length: (29.97, 30.03),
+                angle: 45.0,
+                angle_tolerance: 0.2,
+            },
+            Resolved::Position { radius: 0.05 },
+        ];
+        let points = simulate_points(&resolved, normal, 200_000, 7);
+        for angle in [0.0, 90.0, 135.0] {
+            let projection = project(&resolved, normal, angle);
+            let simulation = summarize(
+                points
+                    .iter()
+                    .map(|point| dot(*point, unit(angle)))
+                    .collect(),
+                (None, None),
+            );
+            assert_close(simulation.mean, projection.mean, 1e-3);
+            assert_close(simulation.sigma / projection.sigma, 1.0, 0.02);
+        }
+        assert!(points
+            .iter()
+            .all(|point| (point.0.hypot(point.1) - 30.0).abs() < 0.2));
+    }
+
+    #[test]
+    fn test_simulated_radius_asymmetric_field() {
+        // loop 40h9@0 10±0.01@90: поле h9 целиком ниже номинала.
+        let resolved = [
+            Resolved::Vector {
+                nominal: 40.0,
+                length: (39.938, 40.0),
+                angle: 0.0,
+                angle_tolerance: 0.0,
+            },
+            Resolved::Vector {
+                nominal: 10.0,
+                length: (9.99, 10.01),
+                angle: 90.0,
+                angle_tolerance: 0.0,
+            },
+        ];
+        for distribution in [Distribution::Normal(3.0), Distribution::Uniform] {
+            let points = simulate_points(&resolved, distribution, 100_000, 1);
+            let worst_case = worst_case_radius(&resolved, distribution);
+            assert_close(worst_case, 0.031f64.hypot(0.01), 1e-3);
+            assert!(simulated_radius(&resolved, &points) <= worst_case);
+        }
+    }
+}